@@ -0,0 +1,183 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::snapshot::DataSnapshot;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListChange<T> {
+    pub added: Vec<T>,
+    pub removed: Vec<T>,
+}
+impl<T: Ord + Clone> ListChange<T> {
+    fn diff(_old: impl Iterator<Item = T>, _new: impl Iterator<Item = T>) -> Self {
+        let old: BTreeSet<T> = _old.collect();
+        let new: BTreeSet<T> = _new.collect();
+        ListChange {
+            added: new.difference(&old).cloned().collect(),
+            removed: old.difference(&new).cloned().collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+//everything a refresh would change in the snapshot, meant to be pasted into a PR
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Changeset {
+    pub manifest_version: Option<(String, String)>,
+    pub dim_perk_mappings: ListChange<(u32, u32)>,
+    pub procedural_intrinsic_mappings: ListChange<(u32, u32)>,
+    //formula hashes, timestamp changes of existing hashes can't happen
+    pub perk_timestamps: ListChange<u64>,
+}
+
+impl Changeset {
+    pub fn diff(_old: &DataSnapshot, _new: &DataSnapshot) -> Self {
+        let manifest_version = if _old.last_manifest_version != _new.last_manifest_version {
+            Some((
+                _old.last_manifest_version.clone(),
+                _new.last_manifest_version.clone(),
+            ))
+        } else {
+            None
+        };
+        Changeset {
+            manifest_version,
+            dim_perk_mappings: ListChange::diff(
+                _old.dim_perk_mappings.iter().cloned(),
+                _new.dim_perk_mappings.iter().cloned(),
+            ),
+            procedural_intrinsic_mappings: ListChange::diff(
+                _old.procedural_intrinsic_mappings.iter().cloned(),
+                _new.procedural_intrinsic_mappings.iter().cloned(),
+            ),
+            perk_timestamps: ListChange::diff(
+                _old.perk_timestamps.keys().cloned(),
+                _new.perk_timestamps.keys().cloned(),
+            ),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.manifest_version.is_none()
+            && self.dim_perk_mappings.is_empty()
+            && self.procedural_intrinsic_mappings.is_empty()
+            && self.perk_timestamps.is_empty()
+    }
+}
+
+fn write_mappings(
+    f: &mut fmt::Formatter,
+    _title: &str,
+    _change: &ListChange<(u32, u32)>,
+) -> fmt::Result {
+    if _change.is_empty() {
+        return Ok(());
+    }
+    writeln!(
+        f,
+        "{} (+{} -{})",
+        _title,
+        _change.added.len(),
+        _change.removed.len()
+    )?;
+    for (hash, base) in _change.added.iter() {
+        writeln!(f, "+ {} -> {}", hash, base)?;
+    }
+    for (hash, base) in _change.removed.iter() {
+        writeln!(f, "- {} -> {}", hash, base)?;
+    }
+    writeln!(f)
+}
+
+impl fmt::Display for Changeset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "snapshot is up to date");
+        }
+        if let Some((old, new)) = &self.manifest_version {
+            writeln!(f, "manifest version: {} -> {}\n", old, new)?;
+        }
+        write_mappings(f, "dim enhanced perk mappings", &self.dim_perk_mappings)?;
+        write_mappings(
+            f,
+            "procedural intrinsic mappings",
+            &self.procedural_intrinsic_mappings,
+        )?;
+        let timestamps = &self.perk_timestamps;
+        if !timestamps.is_empty() {
+            writeln!(
+                f,
+                "formula timestamps (+{} -{})",
+                timestamps.added.len(),
+                timestamps.removed.len()
+            )?;
+            for hash in timestamps.added.iter() {
+                writeln!(f, "+ {}", hash)?;
+            }
+            for hash in timestamps.removed.iter() {
+                writeln!(f, "- {}", hash)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> DataSnapshot {
+        DataSnapshot {
+            last_manifest_version: "1".to_string(),
+            dim_perk_mappings: vec![(10, 1), (20, 2)],
+            procedural_intrinsic_mappings: vec![(1294026524, 903)],
+            perk_timestamps: [(100, 5), (200, 6)].into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn unchanged_snapshot_is_empty() {
+        let changeset = Changeset::diff(&snapshot(), &snapshot());
+        assert!(changeset.is_empty());
+        assert_eq!(changeset.to_string(), "snapshot is up to date\n");
+    }
+
+    #[test]
+    fn changeset_lists_every_change() {
+        let old = snapshot();
+        let mut new = snapshot();
+        new.last_manifest_version = "2".to_string();
+        new.dim_perk_mappings = vec![(20, 2), (30, 3)];
+        //a timestamp moving on an existing hash isn't a change, only hashes are compared
+        new.perk_timestamps = [(100, 9), (300, 7)].into_iter().collect();
+
+        let changeset = Changeset::diff(&old, &new);
+        assert_eq!(
+            changeset.manifest_version,
+            Some(("1".to_string(), "2".to_string()))
+        );
+        assert_eq!(changeset.dim_perk_mappings.added, vec![(30, 3)]);
+        assert_eq!(changeset.dim_perk_mappings.removed, vec![(10, 1)]);
+        assert!(changeset.procedural_intrinsic_mappings.is_empty());
+        assert_eq!(changeset.perk_timestamps.added, vec![300]);
+        assert_eq!(changeset.perk_timestamps.removed, vec![200]);
+
+        let text = changeset.to_string();
+        for line in [
+            "manifest version: 1 -> 2",
+            "dim enhanced perk mappings (+1 -1)",
+            "+ 30 -> 3",
+            "- 10 -> 1",
+            "formula timestamps (+1 -1)",
+            "+ 300",
+            "- 200",
+        ] {
+            assert!(text.contains(line), "missing \"{}\" in\n{}", line, text);
+        }
+        assert!(!text.contains("procedural intrinsic mappings"), "{}", text);
+    }
+}
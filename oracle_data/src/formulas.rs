@@ -0,0 +1,519 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+
+use phf::{phf_map, Map as PhfMap};
+
+use crate::reserves::{flatten_reserve, ReserveFormulaJson, ReserveNode};
+use crate::validate::{
+    has_errors, parse_weapon_formulas, validate_weapon_formulas, FormulaIssue, IssueLevel,
+};
+use crate::{calculate_hash, PartialHash};
+
+pub const NAME_TO_ID: PhfMap<&'static str, i32> = phf_map! {
+    "Auto Rifle" =>        6i32,
+    "Combat Bow"=>         31i32,
+    "Fusion Rifle"=>       11i32,
+    "Grenade Launcher" =>  23i32,
+    "Hand Cannon" =>       9i32,
+    "Linear Fusion Rifle"=>22i32,
+    "Machine Gun"=>        8i32,
+    "Pulse Rifle"=>        13i32,
+    "Rocket Launcher"=>    10i32,
+    "Scout Rifle"=>        14i32,
+    "Shotgun"=>            7i32,
+    "Sniper Rifle"=>       12i32,
+    "Submachine Gun" =>    24i32,
+    "Glaive"=>             33i32,
+    "Trace Rifle"=>        25i32,
+    "Sidearm"=>            17i32,
+};
+
+//these types reflect whats in src/types/rs_types.rs
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default)]
+pub struct StatQuadraticFormula {
+    #[serde(default)]
+    pub evpp: f64,
+    pub vpp: f64,
+    pub offset: f64,
+}
+
+//the engine uses this type directly, validation and stat calcs share one solver
+impl StatQuadraticFormula {
+    pub fn solve_at(&self, _x: f64) -> f64 {
+        self.evpp * _x * _x + self.vpp * _x + self.offset
+    }
+
+    pub fn solve_at_i(&self, x: i32) -> f64 {
+        self.solve_at(x.clamp(0, 100) as f64)
+    }
+}
+
+impl Hash for StatQuadraticFormula {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.evpp.partial_hash(state);
+        self.vpp.partial_hash(state);
+        self.offset.partial_hash(state);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default)]
+pub struct DamageMods {
+    #[serde(default)]
+    pub pve: f64,
+    pub minor: f64,
+    pub elite: f64,
+    pub miniboss: f64,
+    pub champion: f64,
+    pub boss: f64,
+    pub vehicle: f64,
+    #[serde(default)]
+    pub timestamp: u64,
+}
+
+impl Hash for DamageMods {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pve.partial_hash(state);
+        self.minor.partial_hash(state);
+        self.elite.partial_hash(state);
+        self.miniboss.partial_hash(state);
+        self.champion.partial_hash(state);
+        self.boss.partial_hash(state);
+        self.vehicle.partial_hash(state);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default)]
+#[serde(try_from = "RangeJson")]
+pub struct RangeFormula {
+    pub start: StatQuadraticFormula,
+    pub end: StatQuadraticFormula,
+    pub floor_percent: f64,
+    #[serde(default)]
+    pub pve_floor_percent: Option<f64>,
+    #[serde(default)]
+    pub fusion: bool,
+    #[serde(default)]
+    pub timestamp: u64,
+}
+
+impl Hash for RangeFormula {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.start.hash(state);
+        self.end.hash(state);
+        self.floor_percent.partial_hash(state);
+        self.fusion.hash(state);
+    }
+}
+
+impl From<RangeJson> for RangeFormula {
+    fn from(value: RangeJson) -> Self {
+        let start = StatQuadraticFormula {
+            vpp: value.vpp_start,
+            offset: value.offset_start,
+            ..Default::default()
+        };
+        let end = StatQuadraticFormula {
+            vpp: value.vpp_end,
+            offset: value.offset_end,
+            ..Default::default()
+        };
+        RangeFormula {
+            start,
+            end,
+            floor_percent: value.floor_percent,
+            pve_floor_percent: value.pve_floor_percent,
+            fusion: value.fusion.unwrap_or_default(),
+            timestamp: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default)]
+pub struct ReloadFormula {
+    #[serde(flatten)]
+    pub reload_data: StatQuadraticFormula,
+    #[serde(default)]
+    pub ammo_percent: f64,
+    #[serde(default)]
+    pub timestamp: u64,
+}
+
+impl Hash for ReloadFormula {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.reload_data.hash(state);
+        self.ammo_percent.partial_hash(state);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default)]
+pub struct HandlingFormula {
+    pub ready: StatQuadraticFormula,
+    pub stow: StatQuadraticFormula,
+    pub ads: StatQuadraticFormula,
+    #[serde(default)]
+    pub timestamp: u64,
+}
+
+impl Hash for HandlingFormula {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ready.hash(state);
+        self.stow.hash(state);
+        self.ads.hash(state);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default)]
+pub struct AmmoFormula {
+    pub mag: StatQuadraticFormula,
+    #[serde(default)]
+    pub round_to: i32,
+    #[serde(default)]
+    pub reserve_id: u32,
+    #[serde(default)]
+    pub timestamp: u64,
+}
+
+impl Hash for AmmoFormula {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.mag.hash(state);
+        self.round_to.hash(state);
+        self.reserve_id.hash(state);
+    }
+}
+
+fn default_i32_1() -> i32 {
+    1
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default)]
+#[serde(from = "SubFamJson")]
+pub struct FiringData {
+    pub damage: f64,
+    pub crit_mult: f64,
+    pub pve_damage: f64,
+    pub pve_crit_mult: f64,
+    pub burst_delay: f64,
+    pub inner_burst_delay: f64,
+    #[serde(default)]
+    pub burst_size: i32,
+    #[serde(default)]
+    pub one_ammo: bool,
+    #[serde(default)]
+    pub charge: bool,
+    #[serde(default)]
+    pub timestamp: u64,
+}
+
+impl From<SubFamJson> for FiringData {
+    fn from(value: SubFamJson) -> Self {
+        FiringData {
+            damage: value.damage,
+            crit_mult: (value.crit_mult) / 51.0 + 1.5,
+            pve_damage: value.pve_damage,
+            pve_crit_mult: (value.pve_crit_mult) / 51.0 + 1.5,
+            burst_delay: value.burst_delay / 30.0,
+            inner_burst_delay: value.inner_burst_delay / 30.0,
+            burst_size: value.burst_size,
+            one_ammo: value.one_ammo.unwrap_or_default(),
+            charge: value.charge.unwrap_or_default(),
+            timestamp: 0,
+        }
+    }
+}
+
+impl Hash for FiringData {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.damage.partial_hash(state);
+        self.crit_mult.partial_hash(state);
+        self.burst_delay.partial_hash(state);
+        self.inner_burst_delay.partial_hash(state);
+        self.burst_size.hash(state);
+        self.one_ammo.hash(state);
+        self.charge.hash(state);
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct DataPointers {
+    pub h: usize,
+    pub r: usize,
+    pub rl: usize,
+    pub s: usize,
+    pub f: usize,
+    pub a: usize,
+}
+
+//BTreeMaps so the generated arrays come out in the same order every build
+#[derive(Clone, Deserialize)]
+pub struct WeaponFormulaJson {
+    #[serde(flatten)]
+    pub types: BTreeMap<String, WeaponFormula>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct WeaponFormula {
+    #[serde(flatten)]
+    pub intrinsics: BTreeMap<String, WeaponIntrinsic>,
+    pub cat: BTreeMap<String, Category>,
+    #[serde(rename = "subFam")]
+    pub sub_fam: BTreeMap<String, FiringData>,
+    #[serde(rename = "magProf")]
+    pub mag_prof: BTreeMap<String, AmmoFormula>,
+    //keyed by weapon or intrinsic hash like the intrinsics
+    #[serde(default)]
+    pub overrides: BTreeMap<String, WeaponOverrideJson>,
+    //keyed by the reserve_id magProf entries and overrides use
+    #[serde(default)]
+    pub reserves: BTreeMap<String, ReserveFormulaJson>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WeaponFlag {
+    //ttk doesn't stretch shot delay by the fusion/linear fusion charge factor
+    FlatChargeDelay,
+}
+
+//replaces parts of whatever the weapon's own or intrinsic entry points to
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WeaponOverrideJson {
+    #[serde(default)]
+    pub name: String,
+    //same format as subFam entries
+    #[serde(default)]
+    pub firing: Option<FiringData>,
+    //same format as cat range entries
+    #[serde(default)]
+    pub range: Option<RangeFormula>,
+    #[serde(default)]
+    pub ads_mult: Option<f64>,
+    #[serde(default)]
+    pub reserve_id: Option<u32>,
+    #[serde(default)]
+    pub flags: Vec<WeaponFlag>,
+}
+
+const fn default_pve() -> f64 {
+    1.0
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeaponIntrinsic {
+    #[serde(default)]
+    pub name: String,
+    pub cat: String,
+    pub sub_fam: String,
+    pub mag_prof: String,
+    #[serde(default = "default_pve")]
+    pub pve: f64,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+pub struct Category {
+    pub range: RangeFormula,
+    pub reload: ReloadFormula,
+    pub handling: HandlingFormula,
+    pub combatant_scalars: DamageMods,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+struct RangeJson {
+    vpp_start: f64,
+    offset_start: f64,
+    vpp_end: f64,
+    offset_end: f64,
+    floor_percent: f64,
+    #[serde(default)]
+    pve_floor_percent: Option<f64>,
+    fusion: Option<bool>,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+struct SubFamJson {
+    damage: f64,
+    crit_mult: f64,
+    pve_damage: f64,
+    pve_crit_mult: f64,
+    burst_delay: f64,
+    #[serde(default = "default_i32_1")]
+    burst_size: i32,
+    inner_burst_delay: f64,
+    one_ammo: Option<bool>,
+    charge: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Hash)]
+pub struct WeaponPath(pub u32, pub u32);
+
+//these mirror src/types/rs_types.rs too, firing and range point into the tables
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WeaponFlags {
+    pub flat_charge_delay: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WeaponOverride {
+    pub firing: Option<usize>,
+    pub range: Option<usize>,
+    pub ads_mult: Option<f64>,
+    pub reserve_id: Option<u32>,
+    pub flags: WeaponFlags,
+}
+
+//deduplicated formula arrays plus the pointers into them, what build.rs writes out
+#[derive(Debug, Clone, Default)]
+pub struct FormulaTables {
+    pub data_pointers: Vec<(WeaponPath, DataPointers)>,
+    pub overrides: Vec<(WeaponPath, WeaponOverride)>,
+    //keyed by family id and reserve id
+    pub reserves: Vec<(WeaponPath, Vec<ReserveNode>)>,
+    pub range: Vec<RangeFormula>,
+    pub handling: Vec<HandlingFormula>,
+    pub reload: Vec<ReloadFormula>,
+    pub scalar: Vec<DamageMods>,
+    pub firing: Vec<FiringData>,
+    pub ammo: Vec<AmmoFormula>,
+}
+
+//Ok holds the warnings, Err every issue found when there's at least one error
+pub fn load_weapon_formulas(
+    _path: &std::path::Path,
+) -> Result<(WeaponFormulaJson, Vec<FormulaIssue>), Vec<FormulaIssue>> {
+    let text = std::fs::read_to_string(_path).map_err(|err| {
+        vec![FormulaIssue {
+            level: IssueLevel::ERROR,
+            path: _path.display().to_string(),
+            message: err.to_string(),
+        }]
+    })?;
+    let (json, mut issues) = parse_weapon_formulas(&text);
+    issues.extend(validate_weapon_formulas(&json));
+    if has_errors(&issues) {
+        return Err(issues);
+    }
+    Ok((json, issues))
+}
+
+fn find_uuid<T: Hash>(vec: &[T], uuid: &T) -> Option<usize> {
+    vec.iter()
+        .position(|x| calculate_hash(&x) == calculate_hash(uuid))
+}
+
+//pushes _formula unless an identical one is already in _data, _timestamp gets the
+//formula hash of every new entry and returns its timestamp
+fn push_unique<T: Hash>(
+    _data: &mut Vec<T>,
+    mut _formula: T,
+    _timestamp: &mut impl FnMut(u64) -> u64,
+    _set_timestamp: impl FnOnce(&mut T, u64),
+) -> usize {
+    if let Some(index) = find_uuid(_data, &_formula) {
+        return index;
+    }
+    let timestamp = _timestamp(calculate_hash(&_formula));
+    _set_timestamp(&mut _formula, timestamp);
+    _data.push(_formula);
+    _data.len() - 1
+}
+
+pub fn build_formula_tables(
+    _json: &WeaponFormulaJson,
+    mut _timestamp: impl FnMut(u64) -> u64,
+) -> FormulaTables {
+    let mut tables = FormulaTables::default();
+    for (weapon_family, val) in _json.types.iter() {
+        for (weapon_hash, weapon_def) in val.intrinsics.iter() {
+            //validate_weapon_formulas reports all of these, skip instead of panicking
+            let (Some(cat), Some(mag), Some(fam), Some(family_id), Ok(hash)) = (
+                val.cat.get(&weapon_def.cat).copied(),
+                val.mag_prof.get(&weapon_def.mag_prof).copied(),
+                val.sub_fam.get(&weapon_def.sub_fam).copied(),
+                NAME_TO_ID.get(weapon_family.as_str()),
+                weapon_hash.parse::<u32>(),
+            ) else {
+                continue;
+            };
+
+            let mut scalar: DamageMods = cat.combatant_scalars;
+            scalar.pve = weapon_def.pve;
+
+            let data = DataPointers {
+                rl: push_unique(&mut tables.reload, cat.reload, &mut _timestamp, |f, t| {
+                    f.timestamp = t
+                }),
+                r: push_unique(&mut tables.range, cat.range, &mut _timestamp, |f, t| {
+                    f.timestamp = t
+                }),
+                h: push_unique(
+                    &mut tables.handling,
+                    cat.handling,
+                    &mut _timestamp,
+                    |f, t| f.timestamp = t,
+                ),
+                s: push_unique(&mut tables.scalar, scalar, &mut _timestamp, |f, t| {
+                    f.timestamp = t
+                }),
+                a: push_unique(&mut tables.ammo, mag, &mut _timestamp, |f, t| {
+                    f.timestamp = t
+                }),
+                f: push_unique(&mut tables.firing, fam, &mut _timestamp, |f, t| {
+                    f.timestamp = t
+                }),
+            };
+
+            tables
+                .data_pointers
+                .push((WeaponPath(*family_id as u32, hash), data));
+        }
+        for (override_hash, weapon_override) in val.overrides.iter() {
+            let (Some(family_id), Ok(hash)) = (
+                NAME_TO_ID.get(weapon_family.as_str()),
+                override_hash.parse::<u32>(),
+            ) else {
+                continue;
+            };
+            let mut flags = WeaponFlags::default();
+            for flag in weapon_override.flags.iter() {
+                match flag {
+                    WeaponFlag::FlatChargeDelay => flags.flat_charge_delay = true,
+                }
+            }
+            let firing = weapon_override.firing.map(|firing| {
+                push_unique(&mut tables.firing, firing, &mut _timestamp, |f, t| {
+                    f.timestamp = t
+                })
+            });
+            let range = weapon_override.range.map(|range| {
+                push_unique(&mut tables.range, range, &mut _timestamp, |f, t| {
+                    f.timestamp = t
+                })
+            });
+            tables.overrides.push((
+                WeaponPath(*family_id as u32, hash),
+                WeaponOverride {
+                    firing,
+                    range,
+                    ads_mult: weapon_override.ads_mult,
+                    reserve_id: weapon_override.reserve_id,
+                    flags,
+                },
+            ));
+        }
+        for (reserve_id, reserve) in val.reserves.iter() {
+            let (Some(family_id), Ok(id)) = (
+                NAME_TO_ID.get(weapon_family.as_str()),
+                reserve_id.parse::<u32>(),
+            ) else {
+                continue;
+            };
+            tables.reserves.push((
+                WeaponPath(*family_id as u32, id),
+                flatten_reserve(&reserve.formula),
+            ));
+        }
+    }
+    tables
+}
@@ -0,0 +1,30 @@
+//shared between oracle_engine's build.rs (codegen) and the oracle-data refresh tool,
+//nothing in here touches the network
+#[cfg(feature = "snapshot")]
+pub mod changeset;
+pub mod formulas;
+pub mod manifest;
+pub mod reserves;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+pub mod validate;
+
+use fnv::FnvHasher;
+use ordered_float::NotNan;
+use std::hash::{Hash, Hasher};
+
+//stable across builds and platforms, snapshot timestamps are keyed by this
+pub fn calculate_hash<T: Hash>(t: &T) -> u64 {
+    let mut s = FnvHasher::default();
+    t.hash(&mut s);
+    s.finish()
+}
+
+trait PartialHash {
+    fn partial_hash<H: Hasher>(self, state: &mut H);
+}
+impl PartialHash for f64 {
+    fn partial_hash<H: Hasher>(self, state: &mut H) {
+        NotNan::new(self).unwrap().hash(state);
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use oracle_data::changeset::Changeset;
+use oracle_data::formulas::{build_formula_tables, load_weapon_formulas};
+use oracle_data::manifest::{parse_enhanced_mapping, parse_intrinsic_mapping};
+use oracle_data::snapshot::{DataSnapshot, FORMULAS_PATH, SNAPSHOT_PATH};
+
+const USAGE: &str = "usage: oracle-data [options]
+
+refreshes the data snapshot build.rs generates code from, by default only the
+formula timestamps are refreshed and the changes are printed
+
+  --mirror DIR              local mirror holding any of manifest_version.txt,
+                            DestinyInventoryItemDefinition.json and
+                            trait-to-enhanced-trait.json
+  --items PATH              DestinyInventoryItemDefinition json from the manifest
+  --manifest-version VER    manifest version the item definitions came from
+  --enhanced PATH           DIM's trait-to-enhanced-trait.json
+  --snapshot PATH           defaults to build_resources/data_snapshot.ron
+  --formulas PATH           defaults to build_resources/weapon_formulas.json
+  --changeset PATH          write the changeset here instead of stdout
+  --write                   write the refreshed snapshot
+  --check                   exit with an error if the snapshot is out of date";
+
+#[derive(Debug, Default)]
+struct Args {
+    items: Option<PathBuf>,
+    manifest_version: Option<String>,
+    enhanced: Option<PathBuf>,
+    snapshot: Option<PathBuf>,
+    formulas: Option<PathBuf>,
+    changeset: Option<PathBuf>,
+    write: bool,
+    check: bool,
+}
+
+fn parse_args(_args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut args = Args::default();
+    let mut mirror: Option<PathBuf> = None;
+    let mut iter = _args;
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--mirror" => mirror = Some(value()?.into()),
+            "--items" => args.items = Some(value()?.into()),
+            "--manifest-version" => args.manifest_version = Some(value()?),
+            "--enhanced" => args.enhanced = Some(value()?.into()),
+            "--snapshot" => args.snapshot = Some(value()?.into()),
+            "--formulas" => args.formulas = Some(value()?.into()),
+            "--changeset" => args.changeset = Some(value()?.into()),
+            "--write" => args.write = true,
+            "--check" => args.check = true,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ => return Err(format!("unknown argument {}\n\n{}", arg, USAGE)),
+        }
+    }
+    //explicit paths win over the mirror
+    if let Some(mirror) = mirror {
+        let items = mirror.join("DestinyInventoryItemDefinition.json");
+        if args.items.is_none() && items.exists() {
+            args.items = Some(items);
+        }
+        let enhanced = mirror.join("trait-to-enhanced-trait.json");
+        if args.enhanced.is_none() && enhanced.exists() {
+            args.enhanced = Some(enhanced);
+        }
+        let version = mirror.join("manifest_version.txt");
+        if args.manifest_version.is_none() && version.exists() {
+            args.manifest_version = Some(read(&version)?.trim().to_string());
+        }
+    }
+    if args.items.is_some() && args.manifest_version.is_none() {
+        return Err("item definitions need a --manifest-version".to_string());
+    }
+    Ok(args)
+}
+
+fn read(_path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(_path)
+        .map_err(|err| format!("error reading {}: {}", _path.display(), err))
+}
+
+fn refresh(_args: &Args, _old: &DataSnapshot) -> Result<DataSnapshot, String> {
+    let mut new = _old.clone();
+    if let Some(path) = &_args.enhanced {
+        new.dim_perk_mappings = parse_enhanced_mapping(&read(path)?)?;
+    }
+    if let Some(path) = &_args.items {
+        new.procedural_intrinsic_mappings = parse_intrinsic_mapping(&read(path)?)?;
+        new.last_manifest_version = _args.manifest_version.clone().unwrap_or_default();
+    }
+
+    //formulas keep the time they first showed up, removed ones are dropped
+    let formulas_path = _args
+        .formulas
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(FORMULAS_PATH));
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|err| err.to_string())?
+        .as_secs();
+    let mut timestamps = BTreeMap::new();
+    let (formulas, warnings) = load_weapon_formulas(&formulas_path).map_err(|issues| {
+        let issues: Vec<String> = issues.iter().map(|issue| issue.to_string()).collect();
+        format!(
+            "{} is invalid\n{}",
+            formulas_path.display(),
+            issues.join("\n")
+        )
+    })?;
+    for warning in warnings {
+        eprintln!("{}", warning);
+    }
+    build_formula_tables(&formulas, |hash| {
+        *timestamps
+            .entry(hash)
+            .or_insert_with(|| _old.perk_timestamps.get(&hash).cloned().unwrap_or(now))
+    });
+    new.perk_timestamps = timestamps;
+    new.sort();
+    Ok(new)
+}
+
+fn run() -> Result<bool, String> {
+    let args = parse_args(std::env::args().skip(1))?;
+    let snapshot_path = args
+        .snapshot
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(SNAPSHOT_PATH));
+    let old = DataSnapshot::load(&snapshot_path)?;
+    let new = refresh(&args, &old)?;
+    let changeset = Changeset::diff(&old, &new);
+
+    match &args.changeset {
+        Some(path) => std::fs::write(path, changeset.to_string())
+            .map_err(|err| format!("error writing {}: {}", path.display(), err))?,
+        None => print!("{}", changeset),
+    }
+    if changeset.is_empty() {
+        return Ok(true);
+    }
+    if args.write {
+        new.save(&snapshot_path)?;
+        eprintln!("wrote {}", snapshot_path.display());
+    } else if !args.check {
+        eprintln!(
+            "dry run, pass --write to update {}",
+            snapshot_path.display()
+        );
+    }
+    Ok(args.write || !args.check)
+}
+
+fn main() {
+    match run() {
+        Ok(true) => {}
+        Ok(false) => {
+            eprintln!("snapshot is out of date, run oracle-data --write");
+            std::process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    }
+}
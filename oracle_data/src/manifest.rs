@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use phf::{phf_map, Map as PhfMap};
+use serde_json::Value;
+
+const INTRINSIC_MAP: PhfMap<u32, &'static [&'static str]> = phf_map! {
+901u32 => &["High-Impact Frame"],
+902u32 => &["VEIST Rapid-Fire", "Rapid-Fire Frame", "Rapid-Fire Glaive"],
+903u32 => &["Adaptive Frame", "Adaptive Glaive", "Adaptive Burst"],
+904u32 => &["Aggressive Frame", "Aggressive Glaive", "Aggressive Burst"],
+905u32 => &["Lightweight Frame", "MIDA Synergy"],
+906u32 => &["Precision Frame", "Häkke Precision Frame"],
+907u32 => &["Double Fire", "Heavy Burst"],
+908u32 => &["Wave Frame", "Compressed Wave Frame"],
+911u32 => &["Legacy PR-55 Frame"],
+912u32 => &["Support Frame"],
+913u32 => &["Area Denial Frame"],
+914u32 => &["Rocket-Assisted Frame"],
+915u32 => &["Shot Package"],
+916u32 => &["Pinpoint Slug Frame"]
+};
+
+//DIM's trait-to-enhanced-trait.json, returns (enhanced hash, base hash) pairs
+pub fn parse_enhanced_mapping(_json: &str) -> Result<Vec<(u32, u32)>, String> {
+    let dct: HashMap<String, u32> = serde_json::from_str(_json)
+        .map_err(|err| format!("dim enhanced mapping could not be parsed: {}", err))?;
+    let mut mappings = Vec::new();
+    for (base, enhanced) in dct {
+        let base = base
+            .parse::<u32>()
+            .map_err(|_| format!("dim enhanced mapping has a bad perk hash \"{}\"", base))?;
+        mappings.push((enhanced, base));
+    }
+    mappings.sort();
+    Ok(mappings)
+}
+
+//DestinyInventoryItemDefinition from the manifest, maps every intrinsic plug whose
+//name matches a known frame to its procedural intrinsic id
+pub fn parse_intrinsic_mapping(_json: &str) -> Result<Vec<(u32, u32)>, String> {
+    let item_data: Value = serde_json::from_str(_json)
+        .map_err(|err| format!("item definitions could not be parsed: {}", err))?;
+    let items = item_data
+        .as_object()
+        .ok_or("item definitions aren't an object keyed by hash")?;
+    let mut mappings = Vec::new();
+    for (key, value) in items {
+        let display_name = value["itemTypeDisplayName"].as_str().unwrap_or_default();
+        if !display_name.contains("Intrinsic") {
+            continue;
+        }
+        let hash = key
+            .parse::<u32>()
+            .map_err(|_| format!("item definitions have a bad hash \"{}\"", key))?;
+        let name = value["displayProperties"]["name"]
+            .as_str()
+            .unwrap_or_default();
+        for (id, names) in INTRINSIC_MAP.entries() {
+            if names.contains(&name) {
+                mappings.push((hash, *id));
+            }
+        }
+    }
+    mappings.sort();
+    Ok(mappings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enhanced_mapping_is_sorted_by_enhanced_hash() {
+        let mappings =
+            parse_enhanced_mapping(r#"{"3400784728": 2060207536, "1015611457": 247725512}"#)
+                .unwrap();
+        assert_eq!(
+            mappings,
+            vec![(247725512, 1015611457), (2060207536, 3400784728)]
+        );
+    }
+
+    #[test]
+    fn enhanced_mapping_rejects_bad_input() {
+        let err = parse_enhanced_mapping(r#"{"rampage": 2060207536}"#).unwrap_err();
+        assert!(err.contains("\"rampage\""), "{}", err);
+        let err = parse_enhanced_mapping(r#"{"3400784728": "2060207536"}"#).unwrap_err();
+        assert!(err.contains("could not be parsed"), "{}", err);
+    }
+
+    #[test]
+    fn intrinsic_mapping_only_reads_intrinsics() {
+        let items = r#"{
+            "1294026524": {"itemTypeDisplayName": "Intrinsic", "displayProperties": {"name": "Adaptive Frame"}},
+            "2757685314": {"itemTypeDisplayName": "Intrinsic", "displayProperties": {"name": "Adaptive Glaive"}},
+            "3449390870": {"itemTypeDisplayName": "Trait", "displayProperties": {"name": "Adaptive Frame"}},
+            "1458010786": {"itemTypeDisplayName": "Intrinsic", "displayProperties": {"name": "Something New"}}
+        }"#;
+        assert_eq!(
+            parse_intrinsic_mapping(items).unwrap(),
+            vec![(1294026524, 903), (2757685314, 903)]
+        );
+
+        let err = parse_intrinsic_mapping(
+            r#"{"frame": {"itemTypeDisplayName": "Intrinsic", "displayProperties": {}}}"#,
+        )
+        .unwrap_err();
+        assert!(err.contains("\"frame\""), "{}", err);
+        assert!(parse_intrinsic_mapping("[]").is_err());
+    }
+}
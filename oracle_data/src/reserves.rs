@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+
+//reserve id a magProf gets when it doesn't set one, primaries have no reserve curve
+pub const PRIMARY_RESERVE_ID: u32 = 0;
+
+//what a reserve curve can read
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ReserveInput {
+    //the mag size formula's result rounded up
+    #[serde(rename = "magSize")]
+    MAG_SIZE,
+    //how much the mag size formula got rounded up by
+    #[serde(rename = "magRemainder")]
+    MAG_REMAINDER,
+    #[serde(rename = "magStat")]
+    MAG_STAT,
+    #[serde(rename = "invStat")]
+    INV_STAT,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReserveFormulaJson {
+    #[serde(default)]
+    pub name: String,
+    pub formula: ReserveExprJson,
+}
+
+//a plain number, a quadratic in one input, or pieces/products/sums of those
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ReserveExprJson {
+    Constant(f64),
+    Quadratic(ReserveQuadraticJson),
+    Piecewise(ReservePiecewiseJson),
+    Product(ReserveProductJson),
+    Sum(ReserveSumJson),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReserveQuadraticJson {
+    pub input: ReserveInput,
+    #[serde(default)]
+    pub evpp: f64,
+    #[serde(default)]
+    pub vpp: f64,
+    #[serde(default)]
+    pub offset: f64,
+}
+
+//the first piece whose min..=max holds the input wins, default otherwise
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReservePiecewiseJson {
+    pub piecewise: ReserveInput,
+    pub pieces: Vec<ReservePieceJson>,
+    pub default: Box<ReserveExprJson>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReservePieceJson {
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    pub value: ReserveExprJson,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReserveProductJson {
+    pub product: Vec<ReserveExprJson>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReserveSumJson {
+    pub sum: Vec<ReserveExprJson>,
+}
+
+//flattened so build.rs can write curves out as const arrays, node 0 is the root and
+//composite nodes point at their children by index. the engine uses these as is
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum ReserveNode {
+    CONSTANT(f64),
+    QUADRATIC {
+        input: ReserveInput,
+        evpp: f64,
+        vpp: f64,
+        offset: f64,
+    },
+    //pieces are the `count` PIECE nodes starting at `first`
+    PIECEWISE {
+        input: ReserveInput,
+        first: usize,
+        count: usize,
+        default: usize,
+    },
+    PIECE {
+        min: Option<f64>,
+        max: Option<f64>,
+        value: usize,
+    },
+    PRODUCT {
+        first: usize,
+        count: usize,
+    },
+    SUM {
+        first: usize,
+        count: usize,
+    },
+}
+
+pub fn flatten_reserve(_expr: &ReserveExprJson) -> Vec<ReserveNode> {
+    let mut nodes = vec![ReserveNode::CONSTANT(0.0)];
+    flatten_into(_expr, 0, &mut nodes);
+    nodes
+}
+
+//children of a node have to sit next to each other, so they get their slots before
+//any of them is filled in
+fn reserve_slots(_nodes: &mut Vec<ReserveNode>, _count: usize) -> usize {
+    let first = _nodes.len();
+    _nodes.extend(std::iter::repeat(ReserveNode::CONSTANT(0.0)).take(_count));
+    first
+}
+
+fn flatten_into(_expr: &ReserveExprJson, _slot: usize, _nodes: &mut Vec<ReserveNode>) {
+    let node = match _expr {
+        ReserveExprJson::Constant(val) => ReserveNode::CONSTANT(*val),
+        ReserveExprJson::Quadratic(quad) => ReserveNode::QUADRATIC {
+            input: quad.input,
+            evpp: quad.evpp,
+            vpp: quad.vpp,
+            offset: quad.offset,
+        },
+        ReserveExprJson::Piecewise(piecewise) => {
+            let first = reserve_slots(_nodes, piecewise.pieces.len());
+            for (i, piece) in piecewise.pieces.iter().enumerate() {
+                let value = reserve_slots(_nodes, 1);
+                flatten_into(&piece.value, value, _nodes);
+                _nodes[first + i] = ReserveNode::PIECE {
+                    min: piece.min,
+                    max: piece.max,
+                    value,
+                };
+            }
+            let default = reserve_slots(_nodes, 1);
+            flatten_into(&piecewise.default, default, _nodes);
+            ReserveNode::PIECEWISE {
+                input: piecewise.piecewise,
+                first,
+                count: piecewise.pieces.len(),
+                default,
+            }
+        }
+        ReserveExprJson::Product(ReserveProductJson { product: terms })
+        | ReserveExprJson::Sum(ReserveSumJson { sum: terms }) => {
+            let first = reserve_slots(_nodes, terms.len());
+            for (i, term) in terms.iter().enumerate() {
+                flatten_into(term, first + i, _nodes);
+            }
+            if matches!(_expr, ReserveExprJson::Product(_)) {
+                ReserveNode::PRODUCT {
+                    first,
+                    count: terms.len(),
+                }
+            } else {
+                ReserveNode::SUM {
+                    first,
+                    count: terms.len(),
+                }
+            }
+        }
+    };
+    _nodes[_slot] = node;
+}
+
+//used by the engine's reserve calc and for validating reserve curves
+pub fn solve_reserve(
+    _nodes: &[ReserveNode],
+    _mag_size: f64,
+    _mag_stat: i32,
+    _inv_stat: i32,
+) -> f64 {
+    solve_node(_nodes, 0, &|input| match input {
+        ReserveInput::MAG_SIZE => _mag_size.ceil(),
+        ReserveInput::MAG_REMAINDER => _mag_size.ceil() - _mag_size,
+        ReserveInput::MAG_STAT => _mag_stat as f64,
+        ReserveInput::INV_STAT => _inv_stat as f64,
+    })
+}
+
+fn solve_node(_nodes: &[ReserveNode], _index: usize, _input: &dyn Fn(ReserveInput) -> f64) -> f64 {
+    match _nodes[_index] {
+        ReserveNode::CONSTANT(val) => val,
+        ReserveNode::QUADRATIC {
+            input,
+            evpp,
+            vpp,
+            offset,
+        } => {
+            let x = _input(input);
+            evpp * x * x + vpp * x + offset
+        }
+        ReserveNode::PIECEWISE {
+            input,
+            first,
+            count,
+            default,
+        } => {
+            let x = _input(input);
+            let value = _nodes[first..first + count]
+                .iter()
+                .find_map(|piece| match *piece {
+                    ReserveNode::PIECE { min, max, value }
+                        if min.map_or(true, |min| x >= min) && max.map_or(true, |max| x <= max) =>
+                    {
+                        Some(value)
+                    }
+                    _ => None,
+                })
+                .unwrap_or(default);
+            solve_node(_nodes, value, _input)
+        }
+        ReserveNode::PIECE { value, .. } => solve_node(_nodes, value, _input),
+        ReserveNode::PRODUCT { first, count } => (first..first + count)
+            .map(|i| solve_node(_nodes, i, _input))
+            .product(),
+        ReserveNode::SUM { first, count } => (first..first + count)
+            .map(|i| solve_node(_nodes, i, _input))
+            .sum(),
+    }
+}
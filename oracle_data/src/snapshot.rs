@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+//bump when the snapshot layout changes so old snapshots fail loudly
+pub const SNAPSHOT_VERSION: u32 = 2;
+pub const SNAPSHOT_PATH: &str = "build_resources/data_snapshot.ron";
+pub const FORMULAS_PATH: &str = "build_resources/weapon_formulas.json";
+//older weapon_formulas.json files compiled in as named snapshots, one per file
+pub const FORMULA_SNAPSHOTS_DIR: &str = "build_resources/formula_snapshots";
+
+//checked in copy of everything derived from the manifest and DIM, only oracle-data
+//writes this, build.rs just reads it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataSnapshot {
+    pub format_version: u32,
+    pub last_manifest_version: String,
+    pub dim_perk_mappings: Vec<(u32, u32)>,
+    pub procedural_intrinsic_mappings: Vec<(u32, u32)>,
+    //formula hash to the unix time it first showed up
+    pub perk_timestamps: BTreeMap<u64, u64>,
+}
+impl Default for DataSnapshot {
+    fn default() -> Self {
+        DataSnapshot {
+            format_version: SNAPSHOT_VERSION,
+            last_manifest_version: String::new(),
+            dim_perk_mappings: Vec::new(),
+            procedural_intrinsic_mappings: Vec::new(),
+            perk_timestamps: BTreeMap::new(),
+        }
+    }
+}
+
+impl DataSnapshot {
+    pub fn load(_path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(_path)
+            .map_err(|err| format!("error reading {}: {}", _path.display(), err))?;
+        let snapshot: DataSnapshot = ron::de::from_str(&text)
+            .map_err(|err| format!("error parsing {}: {}", _path.display(), err))?;
+        if snapshot.format_version != SNAPSHOT_VERSION {
+            return Err(format!(
+                "{} is version {}, expected {}",
+                _path.display(),
+                snapshot.format_version,
+                SNAPSHOT_VERSION
+            ));
+        }
+        Ok(snapshot)
+    }
+
+    pub fn to_ron(&self) -> Result<String, String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| format!("error serializing snapshot: {}", err))
+    }
+
+    pub fn save(&self, _path: &Path) -> Result<(), String> {
+        std::fs::write(_path, self.to_ron()? + "\n")
+            .map_err(|err| format!("error writing {}: {}", _path.display(), err))
+    }
+
+    pub fn sort(&mut self) {
+        self.dim_perk_mappings.sort();
+        self.procedural_intrinsic_mappings.sort();
+    }
+
+    //dim enhanced perks followed by the procedural intrinsics
+    pub fn enhance_perk_mapping(&self) -> Vec<(u32, u32)> {
+        self.dim_perk_mappings
+            .iter()
+            .chain(self.procedural_intrinsic_mappings.iter())
+            .cloned()
+            .collect()
+    }
+}
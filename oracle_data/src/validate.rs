@@ -0,0 +1,632 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::formulas::{
+    Category, FiringData, RangeFormula, StatQuadraticFormula, WeaponFormula, WeaponFormulaJson,
+    WeaponIntrinsic, NAME_TO_ID,
+};
+use crate::reserves::{flatten_reserve, solve_reserve, ReserveExprJson, PRIMARY_RESERVE_ID};
+
+//formula outputs are allowed to wobble this much against the expected direction
+const MONOTONIC_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IssueLevel {
+    WARNING,
+    ERROR,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormulaIssue {
+    pub level: IssueLevel,
+    //family/key/field, e.g. Hand Cannon/cat/default/reload
+    pub path: String,
+    pub message: String,
+}
+impl fmt::Display for FormulaIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let level = match self.level {
+            IssueLevel::WARNING => "warning",
+            IssueLevel::ERROR => "error",
+        };
+        write!(f, "{}: {}: {}", level, self.path, self.message)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Issues(Vec<FormulaIssue>);
+impl Issues {
+    fn error(&mut self, _path: String, _message: String) {
+        self.0.push(FormulaIssue {
+            level: IssueLevel::ERROR,
+            path: _path,
+            message: _message,
+        });
+    }
+
+    fn warning(&mut self, _path: String, _message: String) {
+        self.0.push(FormulaIssue {
+            level: IssueLevel::WARNING,
+            path: _path,
+            message: _message,
+        });
+    }
+}
+
+pub fn has_errors(_issues: &[FormulaIssue]) -> bool {
+    _issues.iter().any(|issue| issue.level == IssueLevel::ERROR)
+}
+
+fn parse_entry<T: DeserializeOwned>(
+    _value: &Value,
+    _path: String,
+    _issues: &mut Issues,
+) -> Option<T> {
+    match serde_json::from_value(_value.clone()) {
+        Ok(val) => Some(val),
+        Err(err) => {
+            _issues.error(_path, err.to_string());
+            None
+        }
+    }
+}
+
+fn parse_table<T: DeserializeOwned>(
+    _value: Option<&Value>,
+    _path: String,
+    _issues: &mut Issues,
+) -> BTreeMap<String, T> {
+    let mut table = BTreeMap::new();
+    match _value.and_then(|val| val.as_object()) {
+        Some(obj) => {
+            for (name, val) in obj {
+                if let Some(entry) = parse_entry(val, format!("{}/{}", _path, name), _issues) {
+                    table.insert(name.clone(), entry);
+                }
+            }
+        }
+        None => _issues.error(_path, "missing or not an object".to_string()),
+    }
+    table
+}
+
+//deserializes every family, category and intrinsic on its own so one typo doesn't
+//hide the rest, broken entries are left out of the result
+pub fn parse_weapon_formulas(_json: &str) -> (WeaponFormulaJson, Vec<FormulaIssue>) {
+    let mut issues = Issues::default();
+    let mut types = BTreeMap::new();
+    let root: Value = match serde_json::from_str(_json) {
+        Ok(root) => root,
+        Err(err) => {
+            issues.error("weapon_formulas.json".to_string(), err.to_string());
+            return (WeaponFormulaJson { types }, issues.0);
+        }
+    };
+    let Some(families) = root.as_object() else {
+        issues.error(
+            "weapon_formulas.json".to_string(),
+            "not an object".to_string(),
+        );
+        return (WeaponFormulaJson { types }, issues.0);
+    };
+    for (family, val) in families {
+        let Some(obj) = val.as_object() else {
+            issues.error(family.clone(), "not an object".to_string());
+            continue;
+        };
+        let mut formula = WeaponFormula {
+            intrinsics: BTreeMap::new(),
+            cat: parse_table(obj.get("cat"), format!("{}/cat", family), &mut issues),
+            sub_fam: parse_table(obj.get("subFam"), format!("{}/subFam", family), &mut issues),
+            mag_prof: parse_table(
+                obj.get("magProf"),
+                format!("{}/magProf", family),
+                &mut issues,
+            ),
+            overrides: match obj.get("overrides") {
+                Some(val) => parse_table(Some(val), format!("{}/overrides", family), &mut issues),
+                None => BTreeMap::new(),
+            },
+            reserves: match obj.get("reserves") {
+                Some(val) => parse_table(Some(val), format!("{}/reserves", family), &mut issues),
+                None => BTreeMap::new(),
+            },
+        };
+        for (key, val) in obj {
+            if matches!(
+                key.as_str(),
+                "cat" | "subFam" | "magProf" | "overrides" | "reserves"
+            ) {
+                continue;
+            }
+            let path = format!("{}/{}", family, key);
+            if let Some(intrinsic) = parse_entry::<WeaponIntrinsic>(val, path, &mut issues) {
+                formula.intrinsics.insert(key.clone(), intrinsic);
+            }
+        }
+        types.insert(family.clone(), formula);
+    }
+    (WeaponFormulaJson { types }, issues.0)
+}
+
+//runs _formula over stat 0-100, _increasing is the direction a better stat should move it.
+//only warns, a few real curves bend back near the ends
+fn check_curve(
+    _formula: &StatQuadraticFormula,
+    _path: String,
+    _what: &str,
+    _increasing: bool,
+    _issues: &mut Issues,
+) {
+    //all zero formulas are placeholders for data nobody has measured yet
+    if _formula.evpp == 0.0 && _formula.vpp == 0.0 && _formula.offset == 0.0 {
+        return;
+    }
+    let values: Vec<f64> = (0..=100)
+        .map(|stat| _formula.solve_at(stat as f64))
+        .collect();
+    if let Some((stat, value)) = values
+        .iter()
+        .enumerate()
+        .find(|(_, value)| !value.is_finite() || **value <= 0.0)
+    {
+        _issues.warning(
+            _path,
+            format!("{} is {} at stat {}, expected positive", _what, value, stat),
+        );
+        return;
+    }
+    let wrong_way = values.windows(2).position(|pair| {
+        let diff = pair[1] - pair[0];
+        if _increasing {
+            diff < -MONOTONIC_TOLERANCE
+        } else {
+            diff > MONOTONIC_TOLERANCE
+        }
+    });
+    if let Some(stat) = wrong_way {
+        _issues.warning(
+            _path,
+            format!(
+                "{} {} from stat {} to {}",
+                _what,
+                if _increasing {
+                    "decreases"
+                } else {
+                    "increases"
+                },
+                stat,
+                stat + 1
+            ),
+        );
+    }
+}
+
+fn check_category(_category: &Category, _path: &str, _issues: &mut Issues) {
+    check_curve(
+        &_category.reload.reload_data,
+        format!("{}/reload", _path),
+        "reload time",
+        false,
+        _issues,
+    );
+    let handling = &_category.handling;
+    for (formula, name) in [
+        (&handling.ready, "ready"),
+        (&handling.stow, "stow"),
+        (&handling.ads, "ads"),
+    ] {
+        check_curve(
+            formula,
+            format!("{}/handling/{}", _path, name),
+            &format!("{} time", name),
+            false,
+            _issues,
+        );
+    }
+    check_range(&_category.range, &format!("{}/range", _path), _issues);
+}
+
+fn check_range(range: &RangeFormula, _path: &str, _issues: &mut Issues) {
+    check_curve(
+        &range.start,
+        _path.to_string(),
+        "falloff start",
+        true,
+        _issues,
+    );
+    check_curve(&range.end, _path.to_string(), "falloff end", true, _issues);
+    let placeholder = range.end.evpp == 0.0 && range.end.vpp == 0.0 && range.end.offset == 0.0;
+    let crossed = (0..=100)
+        .find(|stat| range.end.solve_at(*stat as f64) < range.start.solve_at(*stat as f64));
+    if let (false, Some(stat)) = (placeholder, crossed) {
+        _issues.warning(
+            _path.to_string(),
+            format!("falloff end is before falloff start at stat {}", stat),
+        );
+    }
+    if !(0.0..=1.0).contains(&range.floor_percent) {
+        _issues.warning(
+            format!("{}/floor_percent", _path),
+            format!("{} isn't between 0 and 1", range.floor_percent),
+        );
+    }
+}
+
+fn check_firing(firing: &FiringData, _path: String, _issues: &mut Issues) {
+    //0 damage marks a placeholder
+    if firing.damage == 0.0 {
+        return;
+    }
+    if firing.damage < 0.0 {
+        _issues.warning(_path.clone(), "damage is negative".to_string());
+    }
+    if firing.burst_size < 1 {
+        _issues.warning(_path.clone(), "burst_size is below 1".to_string());
+    }
+    if firing.burst_delay < 0.0 || firing.inner_burst_delay < 0.0 {
+        _issues.warning(_path, "burst delays can't be negative".to_string());
+    }
+}
+
+fn check_reserve(_expr: &ReserveExprJson, _path: String, _issues: &mut Issues) {
+    match _expr {
+        ReserveExprJson::Constant(_) | ReserveExprJson::Quadratic(_) => {}
+        ReserveExprJson::Piecewise(piecewise) => {
+            for (i, piece) in piecewise.pieces.iter().enumerate() {
+                let path = format!("{}/pieces/{}", _path, i);
+                if let (Some(min), Some(max)) = (piece.min, piece.max) {
+                    if min > max {
+                        _issues.warning(
+                            path.clone(),
+                            format!("min {} is above max {}, the piece never applies", min, max),
+                        );
+                    }
+                }
+                check_reserve(&piece.value, format!("{}/value", path), _issues);
+            }
+            check_reserve(&piecewise.default, format!("{}/default", _path), _issues);
+        }
+        ReserveExprJson::Product(product) => {
+            for (i, term) in product.product.iter().enumerate() {
+                check_reserve(term, format!("{}/product/{}", _path, i), _issues);
+            }
+        }
+        ReserveExprJson::Sum(sum) => {
+            for (i, term) in sum.sum.iter().enumerate() {
+                check_reserve(term, format!("{}/sum/{}", _path, i), _issues);
+            }
+        }
+    }
+}
+
+//reserves are checked against the mag curve of each magProf that uses them, exotic
+//curves are often tables of exact inventory stats so they aren't checked for direction
+fn check_reserve_curve(
+    _expr: &ReserveExprJson,
+    _mag: &StatQuadraticFormula,
+    _path: String,
+    _issues: &mut Issues,
+) {
+    let nodes = flatten_reserve(_expr);
+    for mag_stat in 0..=100 {
+        let mag_size = _mag.solve_at(mag_stat as f64);
+        for inv_stat in 0..=100 {
+            let value = solve_reserve(&nodes, mag_size, mag_stat, inv_stat);
+            if !value.is_finite() || value <= 0.0 {
+                _issues.warning(
+                    _path,
+                    format!(
+                        "reserves are {} at mag stat {} and inventory stat {}, expected positive",
+                        value, mag_stat, inv_stat
+                    ),
+                );
+                return;
+            }
+        }
+    }
+}
+
+//missing references, bad hashes and unknown families are errors, formulas that give odd
+//outputs, unused tables and duplicate hashes are warnings since some are on purpose
+pub fn validate_weapon_formulas(_json: &WeaponFormulaJson) -> Vec<FormulaIssue> {
+    let mut issues = Issues::default();
+    //hash to (family, intrinsic name)
+    let mut seen_hashes: BTreeMap<u32, Vec<(&str, &str)>> = BTreeMap::new();
+
+    for (family, val) in _json.types.iter() {
+        if NAME_TO_ID.get(family.as_str()).is_none() {
+            issues.error(family.clone(), "unknown weapon family".to_string());
+        }
+
+        let mut used_cat = BTreeSet::new();
+        let mut used_sub_fam = BTreeSet::new();
+        let mut used_mag_prof = BTreeSet::new();
+        for (hash, intrinsic) in val.intrinsics.iter() {
+            let path = format!("{}/{}", family, hash);
+            match hash.parse::<u32>() {
+                Ok(parsed) => seen_hashes
+                    .entry(parsed)
+                    .or_default()
+                    .push((family.as_str(), intrinsic.name.as_str())),
+                Err(_) => issues.error(path.clone(), "key isn't a u32 hash".to_string()),
+            }
+            for (name, table, used, field) in [
+                (
+                    &intrinsic.cat,
+                    val.cat.contains_key(&intrinsic.cat),
+                    &mut used_cat,
+                    "cat",
+                ),
+                (
+                    &intrinsic.sub_fam,
+                    val.sub_fam.contains_key(&intrinsic.sub_fam),
+                    &mut used_sub_fam,
+                    "subFam",
+                ),
+                (
+                    &intrinsic.mag_prof,
+                    val.mag_prof.contains_key(&intrinsic.mag_prof),
+                    &mut used_mag_prof,
+                    "magProf",
+                ),
+            ] {
+                used.insert(name.as_str());
+                if !table {
+                    issues.error(
+                        format!("{}/{}", path, field),
+                        format!("\"{}\" isn't in {}/{}", name, family, field),
+                    );
+                }
+            }
+            if !intrinsic.pve.is_finite() || intrinsic.pve <= 0.0 {
+                issues.error(
+                    format!("{}/pve", path),
+                    format!("{} isn't a positive multiplier", intrinsic.pve),
+                );
+            }
+        }
+
+        for (field, names, used) in [
+            ("cat", val.cat.keys().collect::<Vec<_>>(), &used_cat),
+            ("subFam", val.sub_fam.keys().collect(), &used_sub_fam),
+            ("magProf", val.mag_prof.keys().collect(), &used_mag_prof),
+        ] {
+            for name in names {
+                if !used.contains(name.as_str()) {
+                    issues.warning(
+                        format!("{}/{}/{}", family, field, name),
+                        "not used by any intrinsic".to_string(),
+                    );
+                }
+            }
+        }
+
+        for (name, category) in val.cat.iter() {
+            check_category(category, &format!("{}/cat/{}", family, name), &mut issues);
+        }
+        for (name, firing) in val.sub_fam.iter() {
+            check_firing(firing, format!("{}/subFam/{}", family, name), &mut issues);
+        }
+        for (hash, weapon_override) in val.overrides.iter() {
+            let path = format!("{}/overrides/{}", family, hash);
+            if hash.parse::<u32>().is_err() {
+                issues.error(path.clone(), "key isn't a u32 hash".to_string());
+            }
+            if let Some(firing) = &weapon_override.firing {
+                check_firing(firing, format!("{}/firing", path), &mut issues);
+            }
+            if let Some(range) = &weapon_override.range {
+                check_range(range, &format!("{}/range", path), &mut issues);
+            }
+            if let Some(ads_mult) = weapon_override.ads_mult {
+                if !ads_mult.is_finite() || ads_mult <= 0.0 {
+                    issues.warning(
+                        format!("{}/adsMult", path),
+                        format!("{} isn't a positive multiplier", ads_mult),
+                    );
+                }
+            }
+            if weapon_override.firing.is_none()
+                && weapon_override.range.is_none()
+                && weapon_override.ads_mult.is_none()
+                && weapon_override.reserve_id.is_none()
+                && weapon_override.flags.is_empty()
+            {
+                issues.warning(path, "doesn't override anything".to_string());
+            }
+        }
+        let mut used_reserves = BTreeSet::new();
+        let reserve_refs = val
+            .mag_prof
+            .iter()
+            .map(|(name, ammo)| (format!("magProf/{}/reserve_id", name), ammo.reserve_id))
+            .chain(val.overrides.iter().filter_map(|(hash, weapon_override)| {
+                weapon_override
+                    .reserve_id
+                    .map(|id| (format!("overrides/{}/reserveId", hash), id))
+            }));
+        for (path, id) in reserve_refs {
+            if id == PRIMARY_RESERVE_ID {
+                continue;
+            }
+            used_reserves.insert(id.to_string());
+            if !val.reserves.contains_key(&id.to_string()) {
+                issues.error(
+                    format!("{}/{}", family, path),
+                    format!("{} isn't in {}/reserves", id, family),
+                );
+            }
+        }
+        for (id, reserve) in val.reserves.iter() {
+            let path = format!("{}/reserves/{}", family, id);
+            if id.parse::<u32>().is_err() {
+                issues.error(path.clone(), "key isn't a u32 reserve id".to_string());
+            }
+            if !used_reserves.contains(id) {
+                issues.warning(
+                    path.clone(),
+                    "not used by any magProf or override".to_string(),
+                );
+            }
+            check_reserve(&reserve.formula, format!("{}/formula", path), &mut issues);
+        }
+        for (name, ammo) in val.mag_prof.iter() {
+            check_curve(
+                &ammo.mag,
+                format!("{}/magProf/{}", family, name),
+                "mag size",
+                true,
+                &mut issues,
+            );
+            if let Some(reserve) = val.reserves.get(&ammo.reserve_id.to_string()) {
+                check_reserve_curve(
+                    &reserve.formula,
+                    &ammo.mag,
+                    format!("{}/magProf/{}/reserve_id", family, name),
+                    &mut issues,
+                );
+            }
+        }
+    }
+
+    //frame intrinsics are shared between families on purpose, a hash under two
+    //different names is almost always a copy paste mistake
+    for (hash, uses) in seen_hashes {
+        let names: BTreeSet<&str> = uses.iter().map(|(_, name)| *name).collect();
+        if uses.len() > 1 && names.len() > 1 {
+            let families: Vec<String> = uses
+                .iter()
+                .map(|(family, name)| format!("{} as \"{}\"", family, name))
+                .collect();
+            issues.warning(
+                hash.to_string(),
+                format!("hash is used in {}", families.join(", ")),
+            );
+        }
+    }
+    issues.0.sort_by_key(|issue| std::cmp::Reverse(issue.level));
+    issues.0
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    //one pulse rifle family that passes validation, tests break a single part of it
+    fn pulse_family() -> Value {
+        json!({
+            "69420": {"name": "Test Frame", "cat": "default", "subFam": "Adaptive", "magProf": "default"},
+            "cat": {
+                "default": {
+                    "range": {"vpp_start": 0.1, "offset_start": 10.0, "vpp_end": 0.2, "offset_end": 20.0, "floor_percent": 0.5},
+                    "reload": {"evpp": 0.0, "vpp": -0.01, "offset": 3.0},
+                    "handling": {
+                        "ready": {"vpp": -0.01, "offset": 2.0},
+                        "stow": {"vpp": -0.01, "offset": 2.0},
+                        "ads": {"vpp": -0.01, "offset": 2.0}
+                    },
+                    "combatant_scalars": {"vehicle": 1.0, "boss": 1.0, "champion": 1.0, "miniboss": 1.0, "elite": 1.0, "minor": 1.0}
+                }
+            },
+            "subFam": {
+                "Adaptive": {"damage": 10.0, "crit_mult": 2.0, "pve_damage": 10.0, "pve_crit_mult": 2.0, "burst_delay": 2, "burst_size": 3, "inner_burst_delay": 1}
+            },
+            "magProf": {
+                "default": {"mag": {"evpp": 0.0, "vpp": 0.2, "offset": 20.0}, "reserve_id": 0}
+            }
+        })
+    }
+
+    fn issues_for(_json: Value) -> Vec<FormulaIssue> {
+        let (json, mut issues) = parse_weapon_formulas(&_json.to_string());
+        issues.extend(validate_weapon_formulas(&json));
+        issues
+    }
+
+    fn find<'a>(_issues: &'a [FormulaIssue], _path: &str) -> &'a FormulaIssue {
+        _issues
+            .iter()
+            .find(|issue| issue.path == _path)
+            .unwrap_or_else(|| panic!("no issue at {} in {:#?}", _path, _issues))
+    }
+
+    #[test]
+    fn valid_family_has_no_issues() {
+        let issues = issues_for(json!({ "Pulse Rifle": pulse_family() }));
+        assert!(issues.is_empty(), "{:#?}", issues);
+    }
+
+    #[test]
+    fn missing_references_are_errors() {
+        for (field, value) in [
+            ("cat", "missing"),
+            ("subFam", "Aggressive"),
+            ("magProf", "large"),
+        ] {
+            let mut family = pulse_family();
+            family["69420"][field] = json!(value);
+            let issues = issues_for(json!({ "Pulse Rifle": family }));
+            let issue = find(&issues, &format!("Pulse Rifle/69420/{}", field));
+            assert_eq!(issue.level, IssueLevel::ERROR);
+            assert!(issue.message.contains(value), "{}", issue);
+            assert!(has_errors(&issues));
+        }
+    }
+
+    #[test]
+    fn bad_hash_keys_are_errors() {
+        let mut family = pulse_family();
+        family["adaptive"] = family["69420"].clone();
+        let issues = issues_for(json!({ "Pulse Rifle": family }));
+        assert_eq!(
+            find(&issues, "Pulse Rifle/adaptive").level,
+            IssueLevel::ERROR
+        );
+    }
+
+    #[test]
+    fn unknown_families_are_errors() {
+        let issues = issues_for(json!({ "Pulse Rfile": pulse_family() }));
+        let issue = find(&issues, "Pulse Rfile");
+        assert_eq!(issue.level, IssueLevel::ERROR);
+        assert!(issue.message.contains("unknown weapon family"), "{}", issue);
+    }
+
+    #[test]
+    fn curves_going_the_wrong_way_are_warnings() {
+        let mut family = pulse_family();
+        //bottoms out at stat 50 and gets slower after
+        family["cat"]["default"]["reload"] = json!({"evpp": 0.0004, "vpp": -0.04, "offset": 3.0});
+        let issues = issues_for(json!({ "Pulse Rifle": family }));
+        let issue = find(&issues, "Pulse Rifle/cat/default/reload");
+        assert_eq!(issue.level, IssueLevel::WARNING);
+        assert!(
+            issue.message.contains("increases from stat 50 to 51"),
+            "{}",
+            issue
+        );
+        assert!(!has_errors(&issues));
+    }
+
+    //errors fail the build, warnings like curves bending back at the ends are
+    //left for whoever refits the data
+    #[test]
+    fn built_in_formulas_have_no_errors() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../build_resources/weapon_formulas.json");
+        let (json, mut issues) = parse_weapon_formulas(&std::fs::read_to_string(path).unwrap());
+        issues.extend(validate_weapon_formulas(&json));
+        let errors: Vec<String> = issues
+            .iter()
+            .filter(|issue| issue.level == IssueLevel::ERROR)
+            .map(|issue| issue.to_string())
+            .collect();
+        assert!(errors.is_empty(), "{:#?}", errors);
+    }
+}
@@ -1,8 +1,8 @@
 #![allow(dead_code)]
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AmmoType {
     PRIMARY = 1,
     SPECIAL = 2,
@@ -211,9 +211,19 @@ impl StatHashes {
                 | StatHashes::CHARGE_RATE
         )
     }
+    //stats that never show as a bar in game
+    pub fn is_hidden_stat(&self) -> bool {
+        matches!(
+            self,
+            StatHashes::AIM_ASSIST
+                | StatHashes::AIRBORNE
+                | StatHashes::RECOIL_DIR
+                | StatHashes::ZOOM
+        )
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DamageType {
     ARC,
     VOID,
@@ -1,6 +1,9 @@
+use serde::{Deserialize, Serialize};
+
 use crate::activity::Activity;
+use crate::error::OracleError;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[derive(Default)]
 pub enum EnemyType {
     MINOR,
@@ -14,6 +17,211 @@ pub enum EnemyType {
     CHAMPION,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChampionType {
+    BARRIER,
+    OVERLOAD,
+    UNSTOPPABLE,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChampionProfile {
+    pub champion_type: ChampionType,
+    pub health: f64,
+    //fraction of max health regenerated per second once the champion
+    //hasn't taken damage for regen_delay seconds, stuns pause regen
+    pub regen_per_second: f64,
+    pub regen_delay: f64,
+    //fractions of max health the champion goes immune at, damage past
+    //a threshold is lost until the immune phase ends or is stunned away
+    pub immune_thresholds: Vec<f64>,
+    pub immune_duration: f64,
+    pub stun_duration: f64,
+    //time after a stun ends before the champion can be stunned again
+    pub stun_cooldown: f64,
+    pub stunned_damage_mult: f64,
+}
+impl ChampionProfile {
+    //defaults are rough values from gm testing, all of them can be overwritten
+    pub fn new(_champion_type: ChampionType, _health: f64) -> Self {
+        match _champion_type {
+            ChampionType::BARRIER => ChampionProfile {
+                champion_type: _champion_type,
+                health: _health,
+                regen_per_second: 0.04,
+                regen_delay: 3.0,
+                immune_thresholds: vec![0.75, 0.5, 0.25],
+                immune_duration: 8.0,
+                stun_duration: 4.0,
+                stun_cooldown: 0.0,
+                stunned_damage_mult: 1.0,
+            },
+            ChampionType::OVERLOAD => ChampionProfile {
+                champion_type: _champion_type,
+                health: _health,
+                regen_per_second: 0.06,
+                regen_delay: 2.0,
+                immune_thresholds: Vec::new(),
+                immune_duration: 0.0,
+                stun_duration: 10.0,
+                stun_cooldown: 2.0,
+                stunned_damage_mult: 1.0,
+            },
+            ChampionType::UNSTOPPABLE => ChampionProfile {
+                champion_type: _champion_type,
+                health: _health,
+                regen_per_second: 0.02,
+                regen_delay: 4.0,
+                immune_thresholds: Vec::new(),
+                immune_duration: 0.0,
+                stun_duration: 3.5,
+                stun_cooldown: 3.0,
+                stunned_damage_mult: 1.0,
+            },
+        }
+    }
+
+    //NaN slips past plain comparisons, so everything is checked with is_finite first
+    pub fn validate(&self) -> Result<(), OracleError> {
+        if !self.health.is_finite() || self.health <= 0.0 {
+            return Err(OracleError::InvalidInput(
+                "Champion health must be positive".to_string(),
+            ));
+        }
+        let timings = [
+            ("regen per second", self.regen_per_second),
+            ("regen delay", self.regen_delay),
+            ("immune duration", self.immune_duration),
+            ("stun duration", self.stun_duration),
+            ("stun cooldown", self.stun_cooldown),
+            ("stunned damage mult", self.stunned_damage_mult),
+        ];
+        for (name, value) in timings {
+            if !value.is_finite() || value < 0.0 {
+                return Err(OracleError::InvalidInput(format!(
+                    "Champion {} must be a non-negative number, got {}",
+                    name, value
+                )));
+            }
+        }
+        if let Some(threshold) = self
+            .immune_thresholds
+            .iter()
+            .find(|threshold| !threshold.is_finite() || !(0.0..=1.0).contains(*threshold))
+        {
+            return Err(OracleError::InvalidInput(format!(
+                "Immune thresholds must be between 0 and 1, got {}",
+                threshold
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BossEncounter {
+    //fraction of hits that land on a crit spot while one is exposed
+    pub precision_ratio: f64,
+    //(start, end) in seconds the crit spot is exposed, empty means always exposed
+    pub crit_windows: Vec<(f64, f64)>,
+    //(start, end, multiplier) applied to all damage dealt in that window
+    pub dr_phases: Vec<(f64, f64, f64)>,
+    //(start, end) in seconds where no damage is taken
+    pub immune_periods: Vec<(f64, f64)>,
+}
+impl Default for BossEncounter {
+    fn default() -> Self {
+        BossEncounter {
+            precision_ratio: 1.0,
+            crit_windows: Vec::new(),
+            dr_phases: Vec::new(),
+            immune_periods: Vec::new(),
+        }
+    }
+}
+impl BossEncounter {
+    pub fn validate(&self) -> Result<(), OracleError> {
+        if !self.precision_ratio.is_finite() || !(0.0..=1.0).contains(&self.precision_ratio) {
+            return Err(OracleError::InvalidInput(
+                "Precision ratio must be between 0 and 1".to_string(),
+            ));
+        }
+        let dr_windows: Vec<(f64, f64)> = self
+            .dr_phases
+            .iter()
+            .map(|(start, end, _)| (*start, *end))
+            .collect();
+        check_windows("Crit windows", &self.crit_windows)?;
+        check_windows("DR phases", &dr_windows)?;
+        check_windows("Immune periods", &self.immune_periods)?;
+        if let Some((_, _, mult)) = self
+            .dr_phases
+            .iter()
+            .find(|(_, _, mult)| !mult.is_finite() || *mult < 0.0)
+        {
+            return Err(OracleError::InvalidInput(format!(
+                "DR multipliers must be non-negative numbers, got {}",
+                mult
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn crit_available(&self, _time: f64) -> bool {
+        self.crit_windows.is_empty()
+            || self
+                .crit_windows
+                .iter()
+                .any(|(start, end)| _time >= *start && _time < *end)
+    }
+    //blends the crit multiplier with bodyshots based on precision ratio
+    pub fn crit_scale(&self, _crit_mult: f64, _time: f64) -> f64 {
+        if self.crit_available(_time) {
+            1.0 + (_crit_mult - 1.0) * self.precision_ratio
+        } else {
+            1.0
+        }
+    }
+    pub fn damage_scale(&self, _time: f64) -> f64 {
+        if self
+            .immune_periods
+            .iter()
+            .any(|(start, end)| _time >= *start && _time < *end)
+        {
+            return 0.0;
+        }
+        self.dr_phases
+            .iter()
+            .filter(|(start, end, _)| _time >= *start && _time < *end)
+            .map(|(_, _, mult)| mult)
+            .product()
+    }
+}
+
+//windows have to start at or after 0, end after they start and not overlap each other,
+//an infinite end lasts the rest of the fight
+fn check_windows(_name: &str, _windows: &[(f64, f64)]) -> Result<(), OracleError> {
+    if let Some((start, end)) = _windows
+        .iter()
+        .find(|(start, end)| !start.is_finite() || end.is_nan() || *start < 0.0 || start >= end)
+    {
+        return Err(OracleError::InvalidInput(format!(
+            "{} need 0 <= start < end, got [{}, {}]",
+            _name, start, end
+        )));
+    }
+    let mut sorted = _windows.to_vec();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+    if let Some(pair) = sorted.windows(2).find(|pair| pair[1].0 < pair[0].1) {
+        return Err(OracleError::InvalidInput(format!(
+            "{} can't overlap, [{}, {}] and [{}, {}] do",
+            _name, pair[0].0, pair[0].1, pair[1].0, pair[1].1
+        )));
+    }
+    Ok(())
+}
 
 #[derive(Debug, Clone, Default)]
 pub struct Enemy {
@@ -22,6 +230,8 @@ pub struct Enemy {
     pub damage_resistance: f64,
     pub type_: EnemyType,
     pub tier: u8,
+    pub champion: Option<ChampionProfile>,
+    pub encounter: BossEncounter,
 }
 impl Enemy {
     pub fn get_adjusted_health(&self, _activity: Activity) -> f64 {
@@ -0,0 +1,71 @@
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+
+use crate::d2_enums::StatHashes;
+
+//what every public api returns instead of panicking, js gets {code, message}
+#[derive(Debug, Clone, PartialEq)]
+pub enum OracleError {
+    //arguments that couldn't be read or don't make sense for the current state
+    InvalidInput(String),
+    //no formula data for the weapon type, intrinsic and weapon hash
+    UnknownWeapon {
+        weapon_type_id: u8,
+        intrinsic_hash: u32,
+        hash: u32,
+    },
+    //a calculation needs a stat the weapon doesn't have set
+    MissingStat(u32),
+    //formula databases that don't load and results that don't convert for js
+    Data(String),
+}
+impl OracleError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            OracleError::InvalidInput(_) => "INVALID_INPUT",
+            OracleError::UnknownWeapon { .. } => "UNKNOWN_WEAPON",
+            OracleError::MissingStat(_) => "MISSING_STAT",
+            OracleError::Data(_) => "DATA",
+        }
+    }
+}
+impl std::fmt::Display for OracleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OracleError::InvalidInput(message) | OracleError::Data(message) => {
+                write!(f, "{}", message)
+            }
+            OracleError::UnknownWeapon {
+                weapon_type_id,
+                intrinsic_hash,
+                hash,
+            } => write!(
+                f,
+                "Could not find weapon data for type: {}, intrinsic: {}, hash: {}",
+                weapon_type_id, intrinsic_hash, hash
+            ),
+            OracleError::MissingStat(stat) => write!(
+                f,
+                "The weapon needs {:?} ({}) set for this calculation",
+                StatHashes::from(*stat),
+                stat
+            ),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsOracleError {
+    code: &'static str,
+    message: String,
+}
+
+impl From<OracleError> for JsValue {
+    fn from(_err: OracleError) -> Self {
+        let js_err = JsOracleError {
+            code: _err.code(),
+            message: _err.to_string(),
+        };
+        serde_wasm_bindgen::to_value(&js_err).unwrap_or_else(|_| JsValue::from_str(&js_err.message))
+    }
+}
@@ -8,19 +8,25 @@ pub mod abilities;
 pub mod activity;
 pub mod d2_enums;
 pub mod enemies;
+pub mod error;
 pub mod logging;
 pub mod perks;
+pub mod state;
 #[cfg(test)]
 mod test;
 pub mod types;
 pub mod weapons;
 
 use crate::perks::{Perk, Perks};
-use crate::weapons::{Stat, Weapon};
+use crate::weapons::{
+    add_clear_calc::AddWave, masterwork::MasterworkData, roll_optimizer::RollObjective,
+    sockets::SocketLayout, stat_curves::CurveScalars, Stat, Weapon,
+};
 use abilities::Ability;
 use activity::Activity;
 use d2_enums::StatHashes;
-use enemies::Enemy;
+use enemies::{BossEncounter, ChampionProfile, Enemy};
+use error::OracleError;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::panic;
@@ -44,9 +50,9 @@ mod database {
 //JavaScript
 
 use crate::types::js_types::{
-    JsAmmoResponse, JsDifficultyOptions, JsDpsResponse, JsEnemyType, JsFiringResponse,
-    JsHandlingResponse, JsMetaData, JsRangeResponse, JsReloadResponse, JsResillienceSummary,
-    JsStat,
+    JsAmmoResponse, JsChampionType, JsDifficultyOptions, JsDpsResponse, JsEnemyType,
+    JsFiringResponse, JsHandlingResponse, JsMetaData, JsRangeResponse, JsReloadResponse,
+    JsResillienceSummary, JsStat,
 };
 
 use wasm_bindgen::prelude::*;
@@ -60,6 +66,7 @@ pub struct PersistentData {
     pub ability: Ability,
     pub enemy: Enemy,
     pub log_level: LogLevel,
+    pub sockets: SocketLayout,
 }
 impl PersistentData {
     pub fn new() -> PersistentData {
@@ -90,23 +97,131 @@ pub fn start() {
     console_log!("D2 Calculator Loaded");
 }
 
+fn to_js<T: serde::Serialize>(_value: &T, _what: &str) -> Result<JsValue, OracleError> {
+    serde_wasm_bindgen::to_value(_value)
+        .map_err(|_| OracleError::Data(format!("Could not convert {} to JsValue", _what)))
+}
+
+fn from_js<T: serde::de::DeserializeOwned>(_value: JsValue, _what: &str) -> Result<T, OracleError> {
+    serde_wasm_bindgen::from_value(_value)
+        .map_err(|err| OracleError::InvalidInput(format!("Invalid {}: {}", _what, err)))
+}
+
 //---------------WEAPONS---------------//
 
 #[wasm_bindgen(js_name = "getMetadata")]
-pub fn get_metadata() -> Result<JsMetaData, JsValue> {
+pub fn get_metadata() -> Result<JsMetaData, OracleError> {
+    let info = weapons::formula_db::with_formula_db(|db| db.info.clone());
     let metadata = JsMetaData {
         api_timestamp: built_info::BUILT_TIME_UTC,
         api_version: built_info::PKG_VERSION,
-        api_commit: built_info::GIT_COMMIT_HASH.unwrap(),
-        api_branch: built_info::GIT_HEAD_REF.unwrap(),
+        //builds from a source tarball have no git info
+        api_commit: built_info::GIT_COMMIT_HASH.unwrap_or("unknown"),
+        api_branch: built_info::GIT_HEAD_REF.unwrap_or("unknown"),
+        formula_source: format!("{:?}", info.source),
+        formula_version: info.version,
+        formula_timestamp: info.timestamp as u32,
     };
     Ok(metadata)
 }
 
+#[wasm_bindgen(js_name = "loadFormulaDatabase")]
+///Loads formulas in the weapon_formulas.json format. Entries replace built-in ones with
+///the same weapon type and hash, or all built-in data when _replace is true. Only
+///weapons set afterwards use them. Returns the validation warnings
+pub fn load_formula_database(
+    _json: String,
+    _version: String,
+    _timestamp: u32,
+    _replace: Option<bool>,
+) -> Result<JsValue, OracleError> {
+    let (db, warnings) = weapons::formula_db::FormulaDatabase::from_json(
+        &_json,
+        &_version,
+        _timestamp as u64,
+        _replace.unwrap_or(false),
+    )?;
+    weapons::formula_db::set_formula_db(db);
+    to_js(&warnings, "warnings")
+}
+
+#[wasm_bindgen(js_name = "resetFormulaDatabase")]
+///Goes back to the formulas compiled into the engine
+pub fn reset_formula_database() {
+    weapons::formula_db::reset_formula_db();
+}
+
+#[wasm_bindgen(js_name = "loadFormulaSnapshot")]
+///Loads a formula database in the weapon_formulas.json format as a named snapshot without
+///making it active, _replace works like loadFormulaDatabase. Returns the validation warnings
+pub fn load_formula_snapshot(
+    _name: String,
+    _json: String,
+    _timestamp: u32,
+    _replace: Option<bool>,
+) -> Result<JsValue, OracleError> {
+    let (db, warnings) = weapons::formula_db::FormulaDatabase::from_json(
+        &_json,
+        &_name,
+        _timestamp as u64,
+        _replace.unwrap_or(false),
+    )?;
+    weapons::formula_db::add_formula_snapshot(&_name, db)?;
+    to_js(&warnings, "warnings")
+}
+
+#[wasm_bindgen(js_name = "getFormulaSnapshots")]
+///Snapshots that can be diffed or made active, "built-in" and "active" always work too
+pub fn get_formula_snapshots() -> Result<JsValue, OracleError> {
+    to_js(&weapons::formula_db::list_formula_snapshots(), "snapshots")
+}
+
+#[wasm_bindgen(js_name = "useFormulaSnapshot")]
+///Makes a snapshot the active formula database, only weapons set afterwards use it
+pub fn use_formula_snapshot(_name: String) -> Result<(), OracleError> {
+    weapons::formula_db::use_formula_snapshot(&_name)
+}
+
+#[wasm_bindgen(js_name = "removeFormulaSnapshot")]
+///Returns false when there was no snapshot with that name
+pub fn remove_formula_snapshot(_name: String) -> bool {
+    weapons::formula_db::remove_formula_snapshot(&_name)
+}
+
+#[wasm_bindgen(js_name = "diffFormulaSnapshots")]
+///Evaluates the current weapon with its traits, stats and masterwork under both snapshots
+///and returns every output that changed, dps runs against the current enemy
+pub fn diff_formula_snapshots(_before: String, _after: String) -> Result<JsValue, OracleError> {
+    let persistent = PERS_DATA.with(|perm_data| perm_data.borrow().clone());
+    let diff = weapons::formula_diff::diff_formula_snapshots(
+        &persistent.weapon,
+        &_before,
+        &_after,
+        &persistent.enemy,
+        persistent.activity.get_pl_delta(),
+    )?;
+    to_js(&diff, "formula diff")
+}
+
 #[wasm_bindgen(js_name = "stringifyWeapon")]
-pub fn weapon_as_string() -> Result<JsValue, JsValue> {
+pub fn weapon_as_string() -> Result<JsValue, OracleError> {
     let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
-    Ok(serde_wasm_bindgen::to_value(&weapon).unwrap())
+    to_js(&weapon, "weapon")
+}
+
+#[wasm_bindgen(js_name = "exportState")]
+///The weapon with its traits, stats, masterwork and sockets plus the encounter as a
+///versioned JSON string that importState reads back
+pub fn export_state() -> Result<String, OracleError> {
+    PERS_DATA.with(|perm_data| perm_data.borrow().export_state().to_json())
+}
+
+#[wasm_bindgen(js_name = "importState")]
+///Replaces the weapon and encounter with a state from exportState, formulas come from
+///the active database. Nothing changes if the state is invalid
+pub fn import_state(_state: String) -> Result<(), OracleError> {
+    let state = state::SavedState::from_json(&_state)?;
+    PERS_DATA.with(|perm_data| perm_data.borrow_mut().import_state(state))
 }
 
 //
@@ -118,80 +233,152 @@ pub fn weapon_as_string() -> Result<JsValue, JsValue> {
 // }
 
 #[wasm_bindgen(js_name = "setWeapon")]
+///Errors when the weapon type id is unknown or there's no formula data for the intrinsic
+///and hash, the current weapon is kept in that case
 pub fn set_weapon(
     _hash: u32,
     _weapon_type_id: u8,
     _intrinsic_hash: u32,
     _ammo_type_id: u32,
     _damage_type_id: u32,
-) -> Result<(), JsValue> {
+) -> Result<(), OracleError> {
+    let weapon = Weapon::try_generate_weapon(
+        _hash,
+        _weapon_type_id,
+        _intrinsic_hash,
+        _ammo_type_id,
+        _damage_type_id,
+    )?;
     PERS_DATA.with(|perm_data| {
-        let new_weapon = Weapon::generate_weapon(
-            _hash,
-            _weapon_type_id,
-            _intrinsic_hash,
-            _ammo_type_id,
-            _damage_type_id,
-        );
-
-        if let Some(weapon) = new_weapon {
-            perm_data.borrow_mut().weapon = weapon;
-        } else {
-            logging::log(
-                format!(
-                    "Could not find weapon data for type: {}, intrinsic: {}, Err: {:?}",
-                    _weapon_type_id, _intrinsic_hash, new_weapon
-                )
-                .as_str(),
-                LogLevel::Error.into(),
-            );
-            perm_data.borrow_mut().weapon = Weapon::default();
-        }
+        let mut perm_data = perm_data.borrow_mut();
+        perm_data.weapon = weapon;
+        //plug sets belong to the old weapon
+        perm_data.sockets = SocketLayout::default();
     });
     Ok(())
 }
 
+#[wasm_bindgen(js_name = "hasWeaponData")]
+///Whether setWeapon would find formula data for the weapon, hash falls back to the intrinsic
+pub fn has_weapon_data(_weapon_type_id: u8, _intrinsic_hash: u32, _hash: Option<u32>) -> bool {
+    Weapon::has_weapon_data(_weapon_type_id, _intrinsic_hash, _hash.unwrap_or(0))
+}
+
 #[wasm_bindgen(js_name = "getStats")]
-pub fn get_stats() -> Result<JsValue, JsValue> {
+pub fn get_stats() -> Result<JsValue, OracleError> {
     let stat_map = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.stats.clone());
     let mut js_stat_map = HashMap::new();
     for (key, value) in stat_map {
         js_stat_map.insert(key, JsStat::from(value));
     }
-    let value = serde_wasm_bindgen::to_value(&js_stat_map);
-    if value.is_err() {
-        return Err(JsValue::from_str("Could not convert stats to JsValue"));
-    }
-    Ok(value.unwrap())
+    to_js(&js_stat_map, "stats")
 }
 
 #[wasm_bindgen(js_name = "setStats")]
-pub fn set_stats(_stats: JsValue) -> Result<(), JsValue> {
-    let in_stats: HashMap<u32, i32> = serde_wasm_bindgen::from_value(_stats).unwrap();
+pub fn set_stats(_stats: JsValue) -> Result<(), OracleError> {
+    let in_stats: HashMap<u32, i32> = from_js(_stats, "stats")?;
     let mut stats = HashMap::new();
     for (key, value) in in_stats {
         stats.insert(key, Stat::from(value));
     }
-    PERS_DATA.with(|perm_data| perm_data.borrow_mut().weapon.stats = stats);
+    //set_stats re-applies trait and masterwork bumps on top of the new base stats
+    PERS_DATA.with(|perm_data| perm_data.borrow_mut().weapon.set_stats(stats));
     Ok(())
 }
 
+#[wasm_bindgen(js_name = "getStatBreakdown")]
+///Every stat plus the hidden ones with the unclamped total, the clamped value and
+///what each base, part, trait and masterwork source adds
+pub fn get_stat_breakdown() -> Result<JsValue, OracleError> {
+    let mut weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    weapon.update_stats();
+    to_js(&weapon.stat_breakdown(), "stat breakdown")
+}
+
+#[wasm_bindgen(js_name = "setMasterwork")]
+///Takes {stat, tier, adept, craftedLevel, enhancedIntrinsic, intrinsicStats}, the bumps
+///show up as masterworkValue in getStats
+pub fn set_masterwork(_masterwork: JsValue) -> Result<(), OracleError> {
+    let masterwork: MasterworkData = from_js(_masterwork, "masterwork")?;
+    PERS_DATA.with(|perm_data| perm_data.borrow_mut().weapon.set_masterwork(masterwork))
+}
+
 #[wasm_bindgen(js_name = "addTrait")]
-pub fn add_perk(_stats: JsValue, _value: u32, _hash: u32) -> Result<(), JsValue> {
+///Errors if the perk conflicts with the weapon or its other perks,
+///_replace swaps out whatever is in the same barrel, magazine, origin etc slot
+pub fn add_perk(
+    _stats: JsValue,
+    _value: u32,
+    _hash: u32,
+    _replace: Option<bool>,
+) -> Result<(), OracleError> {
     let data = perks::enhanced_check(_hash);
     let perk = Perk {
-        stat_buffs: serde_wasm_bindgen::from_value(_stats).unwrap(),
+        stat_buffs: from_js(_stats, "trait stats")?,
         enhanced: data.1,
         value: _value,
         raw_hash: _hash,
         hash: data.0,
     };
-    PERS_DATA.with(|perm_data| perm_data.borrow_mut().weapon.add_perk(perk));
-    Ok(())
+    PERS_DATA.with(|perm_data| {
+        perm_data
+            .borrow_mut()
+            .weapon
+            .try_add_perk(perk, _replace.unwrap_or(false))
+    })
+}
+
+#[wasm_bindgen(js_name = "setSockets")]
+///Takes the allowed plugs per socket, {barrel, magazine, trait1, trait2, origin, masterwork, mod, catalyst}
+///each a list of {hash, stats}. Cleared when the weapon changes
+pub fn set_sockets(_layout: JsValue) -> Result<(), OracleError> {
+    let layout: SocketLayout = from_js(_layout, "socket layout")?;
+    PERS_DATA.with(|perm_data| {
+        let mut perm_data = perm_data.borrow_mut();
+        layout.validate(&perm_data.weapon.weapon_type)?;
+        perm_data.sockets = layout;
+        Ok(())
+    })
+}
+
+#[wasm_bindgen(js_name = "getRollCount")]
+pub fn get_roll_count() -> usize {
+    PERS_DATA.with(|perm_data| perm_data.borrow().sockets.roll_count())
+}
+
+#[wasm_bindgen(js_name = "getRollStats")]
+///Every roll from the socket layout with its final stats, other traits on the weapon are kept.
+///Swapped trait pairs are listed once, errors past the same roll limit as optimizeRolls
+pub fn get_roll_stats() -> Result<JsValue, OracleError> {
+    let rolls = PERS_DATA.with(|perm_data| {
+        let perm_data = perm_data.borrow();
+        perm_data.weapon.roll_stats(&perm_data.sockets)
+    })?;
+    to_js(&rolls, "rolls")
+}
+
+#[wasm_bindgen(js_name = "optimizeRolls")]
+///Ranks the rolls from setSockets, objective is {metric, constraints: [{metric, min, max}], top, resilience, pvp}
+///with metrics RANGE, RELOAD_TIME, READY_TIME, ADS_TIME, MAG_SIZE, TTK and DPS
+pub fn optimize_rolls(_objective: JsValue, _use_rpl: bool) -> Result<JsValue, OracleError> {
+    let objective: RollObjective = from_js(_objective, "objective")?;
+    let persistent = PERS_DATA.with(|perm_data| perm_data.borrow().clone());
+    let mut enemy = persistent.enemy.clone();
+    if _use_rpl {
+        enemy.health /= persistent.activity.get_rpl_mult();
+    }
+    let ranked = weapons::roll_optimizer::optimize_rolls(
+        &persistent.weapon,
+        &persistent.sockets,
+        &objective,
+        &enemy,
+        persistent.activity.get_pl_delta(),
+    )?;
+    to_js(&ranked, "rolls")
 }
 
 #[wasm_bindgen(js_name = "resetTraits")]
-pub fn reset_perks() -> Result<(), JsValue> {
+pub fn reset_perks() -> Result<(), OracleError> {
     PERS_DATA.with(|perm_data| perm_data.borrow_mut().weapon.reset_perks());
     Ok(())
 }
@@ -213,19 +400,22 @@ pub fn change_perk_value(perk_hash: u32, new_value: u32) {
 }
 
 #[wasm_bindgen(js_name = "getTraitOptions")]
-pub fn get_perk_options_js(_perks: Vec<u32>) -> Result<JsValue, JsValue> {
+pub fn get_perk_options_js(_perks: Vec<u32>) -> Result<JsValue, OracleError> {
     let options = perks::perk_options_handler::get_perk_options(_perks);
-    let value = serde_wasm_bindgen::to_value(&options);
-    if value.is_err() {
-        return Err(JsValue::from_str(
-            "Could not convert perk options to JsValue",
-        ));
-    }
-    Ok(value.unwrap())
+    to_js(&options, "perk options")
+}
+
+#[wasm_bindgen(js_name = "getPerkCoverage")]
+///Lists every perk with its registered modifiers, option type and anything that looks out of sync,
+///value reads are checked against the current weapon
+pub fn get_perk_coverage_js() -> Result<JsValue, OracleError> {
+    let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    let coverage = perks::perk_coverage::get_perk_coverage(&weapon.static_calc_input());
+    to_js(&coverage, "perk coverage")
 }
 
 #[wasm_bindgen(js_name = "getWeaponRangeFalloff")]
-pub fn get_weapon_range(_dynamic_traits: bool, _pvp: bool) -> Result<JsRangeResponse, JsValue> {
+pub fn get_weapon_range(_dynamic_traits: bool, _pvp: bool) -> Result<JsRangeResponse, OracleError> {
     let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
     if _dynamic_traits {
         Ok(weapon
@@ -240,7 +430,7 @@ pub fn get_weapon_range(_dynamic_traits: bool, _pvp: bool) -> Result<JsRangeResp
 pub fn get_weapon_handling(
     _dynamic_traits: bool,
     _pvp: bool,
-) -> Result<JsHandlingResponse, JsValue> {
+) -> Result<JsHandlingResponse, OracleError> {
     let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
     if _dynamic_traits {
         Ok(weapon
@@ -252,7 +442,10 @@ pub fn get_weapon_handling(
 }
 
 #[wasm_bindgen(js_name = "getWeaponReloadTimes")]
-pub fn get_weapon_reload(_dynamic_traits: bool, _pvp: bool) -> Result<JsReloadResponse, JsValue> {
+pub fn get_weapon_reload(
+    _dynamic_traits: bool,
+    _pvp: bool,
+) -> Result<JsReloadResponse, OracleError> {
     let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
     if _dynamic_traits {
         Ok(weapon
@@ -264,7 +457,7 @@ pub fn get_weapon_reload(_dynamic_traits: bool, _pvp: bool) -> Result<JsReloadRe
 }
 
 #[wasm_bindgen(js_name = "getWeaponAmmoSizes")]
-pub fn get_weapon_ammo(_dynamic_traits: bool, _pvp: bool) -> Result<JsAmmoResponse, JsValue> {
+pub fn get_weapon_ammo(_dynamic_traits: bool, _pvp: bool) -> Result<JsAmmoResponse, OracleError> {
     let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
     if _dynamic_traits {
         Ok(weapon
@@ -275,36 +468,172 @@ pub fn get_weapon_ammo(_dynamic_traits: bool, _pvp: bool) -> Result<JsAmmoRespon
     }
 }
 
+fn curve_scalars(_scalars: JsValue) -> Result<CurveScalars, OracleError> {
+    if _scalars.is_undefined() || _scalars.is_null() {
+        return Ok(CurveScalars::default());
+    }
+    from_js(_scalars, "scalars")
+}
+
+#[wasm_bindgen(js_name = "getStatCurves")]
+///Reload, handling, range, ammo and velocity at every stat value from 0 to 100, doesn't
+///touch the loaded weapon. Scalars are {reloadScale, drawScale, stowScale, adsScale,
+///rangeScale, reserveScale, velocityScale, pvp}, all optional
+pub fn get_stat_curves(
+    _weapon_type_id: u8,
+    _intrinsic_hash: u32,
+    _weapon_hash: Option<u32>,
+    _scalars: JsValue,
+) -> Result<JsValue, OracleError> {
+    let scalars = curve_scalars(_scalars)?;
+    let curves = weapons::stat_curves::get_stat_curves(
+        _weapon_type_id,
+        _intrinsic_hash,
+        _weapon_hash.unwrap_or(0),
+        &scalars,
+    )
+    .ok_or(OracleError::UnknownWeapon {
+        weapon_type_id: _weapon_type_id,
+        intrinsic_hash: _intrinsic_hash,
+        hash: _weapon_hash.unwrap_or(0),
+    })?;
+    to_js(&curves, "curves")
+}
+
+#[wasm_bindgen(js_name = "getStatCurvePoint")]
+///Same as getStatCurves for a single stat value
+pub fn get_stat_curve_point(
+    _weapon_type_id: u8,
+    _intrinsic_hash: u32,
+    _stat: i32,
+    _weapon_hash: Option<u32>,
+    _scalars: JsValue,
+) -> Result<JsValue, OracleError> {
+    let scalars = curve_scalars(_scalars)?;
+    let point = weapons::stat_curves::get_stat_curve_point(
+        _weapon_type_id,
+        _intrinsic_hash,
+        _weapon_hash.unwrap_or(0),
+        _stat,
+        &scalars,
+    )
+    .ok_or(OracleError::UnknownWeapon {
+        weapon_type_id: _weapon_type_id,
+        intrinsic_hash: _intrinsic_hash,
+        hash: _weapon_hash.unwrap_or(0),
+    })?;
+    to_js(&point, "curve point")
+}
+
+#[wasm_bindgen(js_name = "getStatBreakpoints")]
+///Magazine or inventory size values where mag size or reserves change on the current weapon
+pub fn get_stat_breakpoints(_stat_hash: u32) -> Result<JsValue, OracleError> {
+    let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    let breakpoints = weapon.stat_breakpoints(_stat_hash)?;
+    to_js(&breakpoints, "breakpoints")
+}
+
+#[wasm_bindgen(js_name = "getTraitBreakpoints")]
+///Values of a trait on the weapon where the pvp shots to kill change at _resilience
+pub fn get_trait_breakpoints(_perk_hash: u32, _resilience: i32) -> Result<JsValue, OracleError> {
+    let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    let breakpoints = weapon.perk_breakpoints(_perk_hash, _resilience)?;
+    to_js(&breakpoints, "breakpoints")
+}
+
 #[wasm_bindgen(js_name = "getWeaponTtk")]
-pub fn get_weapon_ttk(_overshield: f64) -> Result<JsValue, JsValue> {
+pub fn get_weapon_ttk(_overshield: f64) -> Result<JsValue, OracleError> {
     let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    weapon.check_firing_stats()?;
     let ttk_data = weapon.calc_ttk(_overshield);
     let js_ttk_data: Vec<JsResillienceSummary> = ttk_data.into_iter().map(|r| r.into()).collect();
-    Ok(serde_wasm_bindgen::to_value(&js_ttk_data).unwrap())
+    to_js(&js_ttk_data, "ttk data")
 }
 
-///DEPRECATED for now
-//
-// #[wasm_bindgen(js_name = "getWeaponDps")]
-// pub fn get_weapon_dps(_use_rpl: bool) -> Result<JsDpsResponse, JsValue> {
-//     let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
-//     let enemy = PERS_DATA.with(|perm_data| perm_data.borrow().enemy.clone());
-//     let pl_dmg_mult = PERS_DATA.with(|perm_data| perm_data.borrow().activity.get_pl_delta());
-//     let mut dps_response = weapon.calc_dps(enemy, pl_dmg_mult);
-//     let rpl_mult = PERS_DATA.with(|perm_data| perm_data.borrow().activity.get_rpl_mult());
-//     if _use_rpl {
-//         dps_response.apply_rpl(rpl_mult)
-//     }
-//     Ok(dps_response.into())
-// }
+#[wasm_bindgen(js_name = "getWeaponChampionTtk")]
+///Stuns need an anti-champion pseudo mod (1008 barrier, 1009 overload, 1010 unstoppable)
+///added as a trait, arbalest is the only exotic intrinsic recognized on its own and
+///subclass verbs aren't modelled
+pub fn get_weapon_champion_ttk(_use_rpl: bool) -> Result<JsValue, OracleError> {
+    let persistent = PERS_DATA.with(|perm_data| perm_data.borrow().clone());
+    let champion = match &persistent.enemy.champion {
+        Some(champion) => champion,
+        None => {
+            return Err(OracleError::InvalidInput(
+                "No champion set, call setChampion first".to_string(),
+            ))
+        }
+    };
+    persistent.weapon.check_firing_stats()?;
+    let rpl_mult = if _use_rpl {
+        persistent.activity.get_rpl_mult()
+    } else {
+        1.0
+    };
+    let ttk_data = persistent.weapon.calc_champion_ttk(
+        &persistent.enemy,
+        champion,
+        persistent.activity.get_pl_delta(),
+        rpl_mult,
+    );
+    to_js(&ttk_data, "champion ttk data")
+}
+
+#[wasm_bindgen(js_name = "getWeaponDps")]
+///Runs the dps simulation against the current enemy and boss encounter
+pub fn get_weapon_dps(_use_rpl: bool) -> Result<JsDpsResponse, OracleError> {
+    let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    weapon.check_firing_stats()?;
+    let mut enemy = PERS_DATA.with(|perm_data| perm_data.borrow().enemy.clone());
+    let pl_dmg_mult = PERS_DATA.with(|perm_data| perm_data.borrow().activity.get_pl_delta());
+    let rpl_mult = PERS_DATA.with(|perm_data| perm_data.borrow().activity.get_rpl_mult());
+    if _use_rpl {
+        //rpl gets applied after the sim so scale health to match for kills
+        enemy.health /= rpl_mult;
+    }
+    let mut dps_response = weapon.calc_dps(enemy, pl_dmg_mult);
+    if _use_rpl {
+        dps_response.apply_rpl(rpl_mult)
+    }
+    Ok(dps_response.into())
+}
+
+#[wasm_bindgen(js_name = "getWeaponAddClear")]
+///Simulates clearing a line of identical adds, spacing is in meters. Crits use
+///the precision ratio from setBossEncounter
+pub fn get_weapon_add_clear(
+    _count: u32,
+    _health: f64,
+    _spacing: f64,
+    _enemy_type: JsEnemyType,
+    _use_rpl: bool,
+) -> Result<JsValue, OracleError> {
+    let persistent = PERS_DATA.with(|perm_data| perm_data.borrow().clone());
+    persistent.weapon.check_firing_stats()?;
+    let mut pl_dmg_mult = persistent.activity.get_pl_delta();
+    if _use_rpl {
+        pl_dmg_mult *= persistent.activity.get_rpl_mult();
+    }
+    let wave = AddWave {
+        count: _count,
+        health: _health,
+        spacing: _spacing,
+        enemy_type: _enemy_type.into(),
+        precision_ratio: persistent.enemy.encounter.precision_ratio,
+    };
+    wave.validate()?;
+    let clear_data = persistent.weapon.calc_add_clear(&wave, pl_dmg_mult);
+    to_js(&clear_data, "add clear data")
+}
 
 #[wasm_bindgen(js_name = "getWeaponFiringData")]
 pub fn get_weapon_firing_data(
     _dynamic_traits: bool,
     _pvp: bool,
     _use_rpl: bool,
-) -> Result<JsFiringResponse, JsValue> {
+) -> Result<JsFiringResponse, OracleError> {
     let persistent = PERS_DATA.with(|_perm_data| _perm_data.borrow().clone());
+    persistent.weapon.check_firing_stats()?;
     let mut response: types::rs_types::FiringResponse;
     let calc_input: Option<CalculationInput> = if _dynamic_traits {
         let mut buffer = persistent.weapon.static_calc_input();
@@ -332,7 +661,7 @@ pub fn get_weapon_flinch(
     _dynamic_traits: bool,
     _pvp: bool,
     _resilience: u8,
-) -> Result<f64, JsValue> {
+) -> Result<f64, OracleError> {
     let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
     if _dynamic_traits {
         Ok(weapon.calc_flinch_resist(
@@ -347,15 +676,15 @@ pub fn get_weapon_flinch(
 }
 
 #[wasm_bindgen(js_name = "getMiscData")]
-pub fn get_misc_data(_dynamic_traits: bool, _pvp: bool) -> Result<JsValue, JsValue> {
+pub fn get_misc_data(_dynamic_traits: bool, _pvp: bool) -> Result<JsValue, OracleError> {
     let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
     if _dynamic_traits {
-        Ok(serde_wasm_bindgen::to_value(
+        to_js(
             &weapon.get_misc_stats(Some(weapon.static_calc_input()), _pvp),
+            "misc data",
         )
-        .unwrap())
     } else {
-        Ok(serde_wasm_bindgen::to_value(&weapon.get_misc_stats(None, _pvp)).unwrap())
+        to_js(&weapon.get_misc_stats(None, _pvp), "misc data")
     }
 }
 
@@ -367,7 +696,7 @@ pub fn set_encounter(
     _override_cap: i32,
     _difficulty: JsDifficultyOptions,
     _enemy_type: JsEnemyType,
-) -> Result<(), JsValue> {
+) -> Result<(), OracleError> {
     PERS_DATA.with(|perm_data| {
         let activity = &mut perm_data.borrow_mut().activity;
         activity.rpl = _recommend_pl;
@@ -382,27 +711,112 @@ pub fn set_encounter(
     Ok(())
 }
 
+#[wasm_bindgen(js_name = "setEnemyHealth")]
+///Health of each enemy in the dps sim, they respawn when killed so kill perks
+///proc on their own. 0 turns kills off and kill perks go back to their set values
+pub fn set_enemy_health(_health: f64) -> Result<(), OracleError> {
+    if !_health.is_finite() || _health < 0.0 {
+        return Err(OracleError::InvalidInput(
+            "Enemy health can't be negative".to_string(),
+        ));
+    }
+    PERS_DATA.with(|perm_data| perm_data.borrow_mut().enemy.health = _health);
+    Ok(())
+}
+
+#[wasm_bindgen(js_name = "setBossEncounter")]
+///Windows are lists of [start, end] in seconds, dr phases are [start, end, multiplier]
+pub fn set_boss_encounter(
+    _precision_ratio: f64,
+    _crit_windows: JsValue,
+    _dr_phases: JsValue,
+    _immune_periods: JsValue,
+) -> Result<(), OracleError> {
+    let encounter = BossEncounter {
+        precision_ratio: _precision_ratio,
+        crit_windows: from_js(_crit_windows, "crit windows")?,
+        dr_phases: from_js(_dr_phases, "dr phases")?,
+        immune_periods: from_js(_immune_periods, "immune periods")?,
+    };
+    encounter.validate()?;
+    PERS_DATA.with(|perm_data| perm_data.borrow_mut().enemy.encounter = encounter);
+    Ok(())
+}
+
+#[wasm_bindgen(js_name = "resetBossEncounter")]
+pub fn reset_boss_encounter() {
+    PERS_DATA.with(|perm_data| perm_data.borrow_mut().enemy.encounter = BossEncounter::default());
+}
+
+#[wasm_bindgen(js_name = "setChampion")]
+///Uses the default regen, immune and stun timings for the champion type
+pub fn set_champion(_champion_type: JsChampionType, _health: f64) -> Result<(), OracleError> {
+    let champion = ChampionProfile::new(_champion_type.into(), _health);
+    champion.validate()?;
+    PERS_DATA.with(|perm_data| perm_data.borrow_mut().enemy.champion = Some(champion));
+    Ok(())
+}
+
+#[wasm_bindgen(js_name = "setChampionTimings")]
+///Thresholds are fractions of max health, durations are in seconds
+pub fn set_champion_timings(
+    _regen_per_second: f64,
+    _regen_delay: f64,
+    _immune_thresholds: Vec<f64>,
+    _immune_duration: f64,
+    _stun_duration: f64,
+    _stun_cooldown: f64,
+    _stunned_damage_mult: f64,
+) -> Result<(), OracleError> {
+    PERS_DATA.with(|perm_data| {
+        let mut perm_data = perm_data.borrow_mut();
+        let Some(current) = perm_data.enemy.champion.as_ref() else {
+            return Err(OracleError::InvalidInput(
+                "No champion set, call setChampion first".to_string(),
+            ));
+        };
+        let champion = ChampionProfile {
+            regen_per_second: _regen_per_second,
+            regen_delay: _regen_delay,
+            immune_thresholds: _immune_thresholds,
+            immune_duration: _immune_duration,
+            stun_duration: _stun_duration,
+            stun_cooldown: _stun_cooldown,
+            stunned_damage_mult: _stunned_damage_mult,
+            ..current.clone()
+        };
+        champion.validate()?;
+        perm_data.enemy.champion = Some(champion);
+        Ok(())
+    })
+}
+
+#[wasm_bindgen(js_name = "clearChampion")]
+pub fn clear_champion() {
+    PERS_DATA.with(|perm_data| perm_data.borrow_mut().enemy.champion = None);
+}
+
 #[wasm_bindgen(js_name = "setLoggingLevel")]
-pub fn set_logging_level(_level: usize) -> Result<(), JsValue> {
+pub fn set_logging_level(_level: usize) -> Result<(), OracleError> {
     PERS_DATA.with(|perm_data| {
-        perm_data.borrow_mut().log_level = _level.into();
-    });
-    Ok(())
+        perm_data.borrow_mut().log_level = LogLevel::try_from(_level)?;
+        Ok(())
+    })
 }
 
 #[wasm_bindgen(js_name = "getModifierResponseSummary")]
-pub fn get_modifier_response(_dynamic_traits: bool, _pvp: bool) -> Result<JsValue, JsValue> {
+pub fn get_modifier_response(_dynamic_traits: bool, _pvp: bool) -> Result<JsValue, OracleError> {
     let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
     let modifier = weapon.get_modifier_summary(
         _dynamic_traits.then_some(weapon.static_calc_input()),
         _pvp,
         None,
     );
-    Ok(serde_wasm_bindgen::to_value(&modifier).unwrap())
+    to_js(&modifier, "modifier summary")
 }
 
 #[wasm_bindgen(js_name = "getScalarResponseSummary")]
-pub fn get_scalar_response(_pvp: bool) -> Result<JsScalarResponse, JsValue> {
+pub fn get_scalar_response(_pvp: bool) -> Result<JsScalarResponse, OracleError> {
     let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
     let input_data = weapon.static_calc_input();
     let mut cached_data = HashMap::new();
@@ -1,3 +1,5 @@
+use crate::error::OracleError;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[derive(Default)]
 pub enum LogLevel {
@@ -7,14 +9,18 @@ pub enum LogLevel {
     Info,
     Debug,
 }
-impl From<usize> for LogLevel {
-    fn from(i: usize) -> Self {
+impl TryFrom<usize> for LogLevel {
+    type Error = OracleError;
+    fn try_from(i: usize) -> Result<Self, OracleError> {
         match i {
-            0 => LogLevel::Error,
-            1 => LogLevel::Warning,
-            2 => LogLevel::Info,
-            3 => LogLevel::Debug,
-            _ => panic!("Invalid log level"),
+            0 => Ok(LogLevel::Error),
+            1 => Ok(LogLevel::Warning),
+            2 => Ok(LogLevel::Info),
+            3 => Ok(LogLevel::Debug),
+            _ => Err(OracleError::InvalidInput(format!(
+                "Invalid log level {}, expected 0 to 3",
+                i
+            ))),
         }
     }
 }
@@ -42,5 +48,6 @@ pub fn extern_log(s: &str, log_level: LogLevel) {
 }
 
 pub fn log(s: &str, log_level: usize) {
-    extern_log(s, log_level.into())
+    //anything past debug is just more verbose debug
+    extern_log(s, log_level.try_into().unwrap_or(LogLevel::Debug))
 }
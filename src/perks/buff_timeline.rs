@@ -0,0 +1,218 @@
+use serde::Serialize;
+
+use super::{Perk, Perks};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum BuffTrigger {
+    //active from the start of the fight, stacks come from the perk value
+    FightStart,
+    Kill,
+    //reloading with kills in the mag, stacks are the kills from that mag
+    KillReload,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BuffRule {
+    pub trigger: BuffTrigger,
+    pub duration: f64,
+    pub max_stacks: u32,
+    //triggering again while active resets the duration, otherwise it only adds stacks
+    pub refresh: bool,
+    //the next reload ends the buff even with duration left
+    pub ends_on_reload: bool,
+    //used up by the first shot fired while it's active
+    pub ends_on_shot: bool,
+}
+
+pub fn get_buff_rule(_perk: &Perk) -> Option<BuffRule> {
+    let rule = |trigger, duration, max_stacks| BuffRule {
+        trigger,
+        duration,
+        max_stacks,
+        refresh: true,
+        ends_on_reload: false,
+        ends_on_shot: false,
+    };
+    match Perks::from(_perk.hash) {
+        Perks::Rampage => Some(rule(
+            BuffTrigger::Kill,
+            if _perk.enhanced { 5.0 } else { 4.0 },
+            3,
+        )),
+        Perks::AdrenalineJunkie => Some(rule(
+            BuffTrigger::Kill,
+            if _perk.enhanced { 6.0 } else { 4.5 },
+            5,
+        )),
+        Perks::FeedingFrenzy => Some(rule(BuffTrigger::Kill, 3.5, 5)),
+        Perks::KillClip => Some(BuffRule {
+            ends_on_reload: true,
+            ..rule(
+                BuffTrigger::KillReload,
+                if _perk.enhanced { 5.0 } else { 4.0 },
+                1,
+            )
+        }),
+        Perks::MultikillClip => Some(BuffRule {
+            ends_on_reload: true,
+            ..rule(BuffTrigger::KillReload, f64::INFINITY, 5)
+        }),
+        //rough duration, nothing's been timed for it yet
+        Perks::Voltshot => Some(BuffRule {
+            ends_on_shot: true,
+            ..rule(BuffTrigger::KillReload, 3.0, 1)
+        }),
+        Perks::Adagio => Some(rule(
+            BuffTrigger::FightStart,
+            if _perk.enhanced { 8.0 } else { 7.0 },
+            1,
+        )),
+        _ => None,
+    }
+}
+
+//a stretch of time a buff sits at the same stack count
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BuffWindow {
+    pub perk: u32,
+    pub start: f64,
+    pub end: f64,
+    pub stacks: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BuffTimeline {
+    //(perk hash, raw hash, rule)
+    rules: Vec<(u32, u32, BuffRule)>,
+    windows: Vec<BuffWindow>,
+}
+impl BuffTimeline {
+    pub fn new(_perks: &[Perk]) -> Self {
+        let mut timeline = BuffTimeline::default();
+        for perk in _perks {
+            if let Some(rule) = get_buff_rule(perk) {
+                timeline.rules.push((perk.hash, perk.raw_hash, rule));
+                if rule.trigger == BuffTrigger::FightStart {
+                    timeline.add_stacks(timeline.rules.len() - 1, 0.0, perk.value);
+                }
+            }
+        }
+        timeline
+    }
+
+    fn add_stacks(&mut self, _rule_idx: usize, _time: f64, _stacks: u32) {
+        let (_, raw_hash, rule) = self.rules[_rule_idx];
+        if _stacks == 0 {
+            return;
+        }
+        let mut stacks = _stacks;
+        let mut end = _time + rule.duration;
+        if let Some(active) = self
+            .windows
+            .iter_mut()
+            .rev()
+            .find(|window| window.perk == raw_hash && window.end > _time)
+        {
+            stacks += active.stacks;
+            if !rule.refresh {
+                end = active.end;
+            }
+            active.end = _time;
+        }
+        self.windows.push(BuffWindow {
+            perk: raw_hash,
+            start: _time,
+            end,
+            stacks: stacks.min(rule.max_stacks),
+        });
+    }
+
+    pub fn trigger(&mut self, _trigger: BuffTrigger, _time: f64, _stacks: u32) {
+        for i in 0..self.rules.len() {
+            if self.rules[i].2.trigger == _trigger {
+                self.add_stacks(i, _time, _stacks);
+            }
+        }
+    }
+
+    //call before triggering anything from the reload
+    pub fn reload(&mut self, _time: f64) {
+        for (_, raw_hash, rule) in self.rules.iter() {
+            if !rule.ends_on_reload {
+                continue;
+            }
+            for window in self.windows.iter_mut() {
+                if window.perk == *raw_hash && window.end > _time {
+                    window.end = _time;
+                }
+            }
+        }
+    }
+
+    //call once a shot is done with the time the next one fires, buffs the shot
+    //used up last until then
+    pub fn shot(&mut self, _time: f64) {
+        for (_, raw_hash, rule) in self.rules.iter() {
+            if !rule.ends_on_shot {
+                continue;
+            }
+            for window in self.windows.iter_mut() {
+                if window.perk == *raw_hash && window.start < _time && window.end > _time {
+                    window.end = _time;
+                }
+            }
+        }
+    }
+
+    pub fn has_rule(&self, _perk: u32) -> bool {
+        self.rules.iter().any(|(hash, _, _)| *hash == _perk)
+    }
+
+    pub fn get_trigger(&self, _perk: u32) -> Option<BuffTrigger> {
+        self.rules
+            .iter()
+            .find(|(hash, _, _)| *hash == _perk)
+            .map(|(_, _, rule)| rule.trigger)
+    }
+
+    pub fn stacks(&self, _perk: u32, _time: f64) -> u32 {
+        let raw_hash = match self.rules.iter().find(|(hash, _, _)| *hash == _perk) {
+            Some((_, raw_hash, _)) => *raw_hash,
+            None => return 0,
+        };
+        self.windows
+            .iter()
+            .rev()
+            .find(|window| window.perk == raw_hash && window.start <= _time && _time < window.end)
+            .map_or(0, |window| window.stacks)
+    }
+
+    pub fn is_active(&self, _perk: u32, _time: f64) -> bool {
+        self.stacks(_perk, _time) > 0
+    }
+
+    //seconds the buff was up between 0 and _end
+    pub fn active_time(&self, _perk: u32, _end: f64) -> f64 {
+        let raw_hash = match self.rules.iter().find(|(hash, _, _)| *hash == _perk) {
+            Some((_, raw_hash, _)) => *raw_hash,
+            None => return 0.0,
+        };
+        self.windows
+            .iter()
+            .filter(|window| window.perk == raw_hash)
+            .map(|window| (window.end.min(_end) - window.start).max(0.0))
+            .sum()
+    }
+
+    //cuts off anything still running when the sim ends
+    pub fn finish(&mut self, _time: f64) {
+        for window in self.windows.iter_mut() {
+            window.end = window.end.min(_time);
+        }
+        self.windows.retain(|window| window.end > window.start);
+    }
+
+    pub fn windows(&self) -> &[BuffWindow] {
+        &self.windows
+    }
+}
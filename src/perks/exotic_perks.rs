@@ -262,6 +262,8 @@ pub fn exotic_perks() {
                 weapon_scale: true,
                 crit_scale: false,
                 combatant_scale: true,
+                splash_radius: 0.0,
+                on_kill: false,
             }
         }),
     );
@@ -338,6 +340,8 @@ pub fn exotic_perks() {
                 weapon_scale: true,
                 crit_scale: false,
                 combatant_scale: true,
+                splash_radius: 0.0,
+                on_kill: false,
             }
         }),
     );
@@ -363,6 +367,8 @@ pub fn exotic_perks() {
     //         weapon_scale: true,
     //         crit_scale: false,
     //         combatant_scale: true,
+    //         splash_radius: 0.0,
+    //         on_kill: false,
     //     }
     // }
 
@@ -389,6 +395,8 @@ pub fn exotic_perks() {
     //         weapon_scale: true,
     //         crit_scale: false,
     //         combatant_scale: true,
+    //         splash_radius: 0.0,
+    //         on_kill: false,
     //     };
     // }
 
@@ -1340,7 +1348,7 @@ pub fn exotic_perks() {
             let hits_needed = if _input.pvp { 5 } else { 14 };
 
             if _input.calc_data.shots_fired_this_mag < (hits_needed as f64)
-                && _input.calc_data.intrinsic_hash != Perks::Judgment.into()
+                && _input.calc_data.intrinsic_hash != u32::from(Perks::Judgment)
                 && _input.value == 0
             {
                 return DamageModifierResponse::default();
@@ -1,6 +1,10 @@
 use crate::{
     d2_enums::{AmmoType, BungieHash, DamageSource, DamageType, StatBump, StatHashes, WeaponType},
     enemies::EnemyType,
+    perks::{
+        buff_timeline::{BuffTimeline, BuffTrigger},
+        Perks,
+    },
     types::rs_types::{FiringData, HandlingResponse},
     weapons::{Stat, Weapon},
 };
@@ -29,6 +33,13 @@ pub struct CalculationInput<'a> {
     pub enemy_type: &'a EnemyType,
     pub perk_value_map: &'a HashMap<u32, u32>,
     pub has_overshield: bool,
+    //only true when the sim is tracking enemy health, otherwise kill
+    //perks fall back to the user set value
+    pub kill_events: bool,
+    pub kills_total: f64,
+    pub kills_this_mag: f64,
+    pub time_since_last_kill: f64,
+    pub buffs: Option<&'a BuffTimeline>,
 }
 impl<'a> CalculationInput<'a> {
     //stuff like mag size can use this, not reload, damage, etc.
@@ -68,6 +79,11 @@ impl<'a> CalculationInput<'a> {
             enemy_type: &EnemyType::BOSS,
             perk_value_map: _perk_value_map,
             has_overshield: false,
+            kill_events: false,
+            kills_total: 0.0,
+            kills_this_mag: 0.0,
+            time_since_last_kill: f64::INFINITY,
+            buffs: None,
         }
     }
     #[allow(clippy::too_many_arguments)]
@@ -105,6 +121,11 @@ impl<'a> CalculationInput<'a> {
             enemy_type: &EnemyType::PLAYER,
             perk_value_map: _perk_value_map,
             has_overshield: _has_overshield,
+            kill_events: false,
+            kills_total: 0.0,
+            kills_this_mag: 0.0,
+            time_since_last_kill: f64::INFINITY,
+            buffs: None,
         }
     }
     #[allow(clippy::too_many_arguments)]
@@ -131,8 +152,85 @@ impl<'a> CalculationInput<'a> {
             enemy_type: &EnemyType::ENCLAVE,
             perk_value_map: &weapon.perk_value_map,
             has_overshield: false,
+            kill_events: false,
+            kills_total: 0.0,
+            kills_this_mag: 0.0,
+            time_since_last_kill: f64::INFINITY,
+            buffs: None,
         }
     }
+    //stacks from the sim's buff timeline at time_total, None if there's no timeline,
+    //the perk doesn't have a buff rule or it needs kills the sim isn't tracking,
+    //then the perk should use its value instead
+    pub fn buff_stacks(&self, _perk: Perks) -> Option<u32> {
+        let hash: u32 = _perk.into();
+        let buffs = self.buffs?;
+        match buffs.get_trigger(hash)? {
+            BuffTrigger::Kill | BuffTrigger::KillReload if !self.kill_events => None,
+            _ => Some(buffs.stacks(hash, self.time_total)),
+        }
+    }
+}
+
+//enemies for the dps sim, they respawn at full health when killed
+#[derive(Debug, Clone, Default)]
+pub struct KillTracker {
+    pub kill_times: Vec<f64>,
+    pub kills_this_mag: u32,
+    max_health: f64,
+    health: f64,
+    //(time, damage) dealt but not landed yet, delayed hits can't kill before they land
+    pending: Vec<(f64, f64)>,
+}
+impl KillTracker {
+    //0 health means nothing ever dies
+    pub fn new(_health: f64) -> Self {
+        KillTracker {
+            max_health: _health,
+            health: _health,
+            ..Default::default()
+        }
+    }
+    pub fn queue_damage(&mut self, _time: f64, _dmg: f64) {
+        if self.max_health > 0.0 {
+            self.pending.push((_time, _dmg));
+        }
+    }
+    //lands everything queued up to _time in order, returns the number of kills
+    pub fn land_damage(&mut self, _time: f64) -> usize {
+        self.pending.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let landed = self
+            .pending
+            .iter()
+            .take_while(|(time, _)| *time <= _time)
+            .count();
+        let kills_before = self.kill_times.len();
+        for (time, dmg) in self.pending.drain(..landed) {
+            self.health -= dmg;
+            if self.health <= 0.0 {
+                self.kill_times.push(time);
+                self.kills_this_mag += 1;
+                self.health = self.max_health;
+            }
+        }
+        self.kill_times.len() - kills_before
+    }
+    pub fn on_reload(&mut self) {
+        self.kills_this_mag = 0;
+    }
+    pub fn kills_total(&self) -> f64 {
+        self.kill_times.len() as f64
+    }
+    pub fn time_since_last_kill(&self, _time: f64) -> f64 {
+        time_since_last_kill(&self.kill_times, _time)
+    }
+}
+
+//infinite before the first kill
+pub fn time_since_last_kill(_kill_times: &[f64], _time: f64) -> f64 {
+    _kill_times
+        .last()
+        .map_or(f64::INFINITY, |kill| _time - kill)
 }
 
 #[non_exhaustive]
@@ -189,6 +287,10 @@ pub struct ExtraDamageResponse {
     pub weapon_scale: bool,
     pub crit_scale: bool,
     pub combatant_scale: bool,
+    //meters around the target that also take the damage, 0 is single target
+    pub splash_radius: f64,
+    //only happens when the target dies, single target calcs will skip these
+    pub on_kill: bool,
 }
 impl Default for ExtraDamageResponse {
     fn default() -> Self {
@@ -202,6 +304,8 @@ impl Default for ExtraDamageResponse {
             weapon_scale: false,
             crit_scale: false,
             combatant_scale: false,
+            splash_radius: 0.0,
+            on_kill: false,
         }
     }
 }
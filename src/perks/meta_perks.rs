@@ -84,14 +84,13 @@ pub fn meta_perks() {
                 };
             }
 
-            if *_input.calc_data.weapon_type == WeaponType::LINEARFUSIONRIFLE
-                && _input.calc_data.intrinsic_hash < 1000
-            {
-                let charge_time = _input
-                    .calc_data
-                    .stats
-                    .get(&StatHashes::CHARGE_TIME.into())
-                    .unwrap();
+            //weapons without charge time set get no adjustment
+            let charge_time = _input.calc_data.stats.get(&StatHashes::CHARGE_TIME.into());
+            if let (WeaponType::LINEARFUSIONRIFLE, true, Some(charge_time)) = (
+                _input.calc_data.weapon_type,
+                _input.calc_data.intrinsic_hash < 1000,
+                charge_time,
+            ) {
                 //source: https://docs.google.com/spreadsheets/d/1QaUwtOW2_RJCTK1uaIGkbCoEXDa8UStvjDQSHSDxLOM/edit#gid=497378026
                 //damage value updated from harm and stardust during super DR testing
                 let total_damage = _input.calc_data.curr_firing_data.damage
@@ -114,16 +113,16 @@ pub fn meta_perks() {
             #[allow(unused_mut)]
             let mut delay_add = 0.0;
 
-            if matches!(
+            let charge_time = _input.calc_data.stats.get(&StatHashes::CHARGE_TIME.into());
+            if let (
+                WeaponType::FUSIONRIFLE | WeaponType::LINEARFUSIONRIFLE,
+                true,
+                Some(charge_time),
+            ) = (
                 _input.calc_data.weapon_type,
-                WeaponType::FUSIONRIFLE | WeaponType::LINEARFUSIONRIFLE
-            ) && _input.calc_data.intrinsic_hash < 1000
-            {
-                let charge_time = _input
-                    .calc_data
-                    .stats
-                    .get(&StatHashes::CHARGE_TIME.into())
-                    .unwrap();
+                _input.calc_data.intrinsic_hash < 1000,
+                charge_time,
+            ) {
                 let stat = (charge_time.perk_val() - charge_time.base_value) as f64;
                 delay_add -= match _input.calc_data.weapon_type {
                     WeaponType::FUSIONRIFLE => stat * 0.0040,
@@ -132,13 +131,8 @@ pub fn meta_perks() {
                 }
             }
 
-            if _input.calc_data.weapon_type == &WeaponType::BOW {
-                let draw_time = _input
-                    .calc_data
-                    .stats
-                    .get(&StatHashes::DRAW_TIME.into())
-                    .unwrap()
-                    .clone();
+            let draw_time = _input.calc_data.stats.get(&StatHashes::DRAW_TIME.into());
+            if let (WeaponType::BOW, Some(draw_time)) = (_input.calc_data.weapon_type, draw_time) {
                 delay_add += match _input.calc_data.intrinsic_hash {
                     //Lightweights, Wishender, Ticcus, Verglas
                     905 | 1470121888 | 3239299468 | 2636679416 => {
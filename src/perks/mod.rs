@@ -1,11 +1,14 @@
 pub mod buff_perks;
+pub mod buff_timeline;
 pub mod exotic_armor;
 pub mod exotic_perks;
 pub mod lib;
 pub mod meta_perks;
 pub mod origin_perks;
 pub mod other_perks;
+pub mod perk_coverage;
 pub mod perk_options_handler;
+pub mod perk_slots;
 pub mod year_1_perks;
 pub mod year_2_perks;
 pub mod year_3_perks;
@@ -19,9 +22,11 @@ use std::collections::HashMap;
 
 use num_enum::{FromPrimitive, IntoPrimitive};
 use serde::{Deserialize, Serialize};
+use strum::EnumIter;
 
 use crate::d2_enums::{BungieHash, StatBump, StatHashes, WeaponType};
 use crate::database;
+use crate::enemies::ChampionType;
 
 use self::{
     buff_perks::*,
@@ -37,6 +42,7 @@ use self::{
     meta_perks::*,
     origin_perks::*,
     other_perks::*,
+    perk_options_handler::ModifierCategory,
     year_1_perks::*,
     year_2_perks::*,
     year_3_perks::*,
@@ -74,7 +80,7 @@ pub struct Perk {
 pub fn enhanced_check(_hash: u32) -> (u32, bool) {
     let mut result = _hash;
     let mut found = false;
-    for (_, (h, r)) in database::ENHANCE_PERK_MAPPING.iter().enumerate() {
+    for (h, r) in database::ENHANCE_PERK_MAPPING.iter() {
         if _hash == *h {
             result = *r;
             found = true;
@@ -86,7 +92,7 @@ pub fn enhanced_check(_hash: u32) -> (u32, bool) {
 
 // all armor pekrs are for the future but wanted to started to compile them now
 
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, FromPrimitive, IntoPrimitive)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, FromPrimitive, IntoPrimitive, EnumIter)]
 #[repr(u32)]
 pub enum Perks {
     //Meta perks
@@ -116,6 +122,9 @@ pub enum Perks {
     UnflinchingMod = 1005,
     SurgeMod = 1006,
     InFlightCompensatorMod = 1007,
+    AntiBarrierMod = 1008,
+    OverloadMod = 1009,
+    UnstoppableMod = 1010,
     LucentBlades = 531057500,
     DragonShadow = 593361144,
     OphidianAspect = 1147638875,
@@ -226,6 +235,8 @@ pub enum Perks {
     MovingTarget = 588594999,
     AmbitiousAssassin = 2010801679,
     ClusterBomb = 1275731761,
+    ChainReaction = 2821430069,
+    Dragonfly = 2848615171,
     #[num_enum(alternatives = [2907129557, ])] //sunshot
     ExplosivePayload = 3038247973,
     FirmlyPlanted = 280464955,
@@ -397,6 +408,8 @@ pub enum Perks {
     KeepAway = 3619207468,
     ParacausalAffinity = 3215448563,
     EnviousAssassin = 968510818,
+    Incandescent = 4293542123,
+    Voltshot = 2173046394,
 
     //season 21 | year 6
     CollectiveAction = 3324494224,
@@ -601,6 +614,84 @@ pub fn map_perks() {
     }
 }
 
+pub fn get_modifier_categories(_perk: Perks) -> Vec<ModifierCategory> {
+    map_perks();
+    PERK_FUNC_MAP.with(|map| {
+        let map = map.borrow();
+        [
+            (map.sbr.contains_key(&_perk), ModifierCategory::STATS),
+            (map.dmr.contains_key(&_perk), ModifierCategory::DAMAGE),
+            (map.hmr.contains_key(&_perk), ModifierCategory::HANDLING),
+            (map.rmr.contains_key(&_perk), ModifierCategory::RANGE),
+            (map.rsmr.contains_key(&_perk), ModifierCategory::RELOAD),
+            (map.fmr.contains_key(&_perk), ModifierCategory::FIRING),
+            (map.flmr.contains_key(&_perk), ModifierCategory::FLINCH),
+            (map.edr.contains_key(&_perk), ModifierCategory::EXTRA_DAMAGE),
+            (map.rr.contains_key(&_perk), ModifierCategory::REFUND),
+            (map.vmr.contains_key(&_perk), ModifierCategory::VELOCITY),
+            (
+                map.epr.contains_key(&_perk),
+                ModifierCategory::EXPLOSIVE_PERCENT,
+            ),
+            (map.mmr.contains_key(&_perk), ModifierCategory::MAGAZINE),
+            (map.imr.contains_key(&_perk), ModifierCategory::INVENTORY),
+        ]
+        .into_iter()
+        .filter(|(has, _)| *has)
+        .map(|(_, category)| category)
+        .collect()
+    })
+}
+
+//runs the function with and without a value, cached data is fresh for every call
+//so the first run can't change the second
+fn value_changes_output<T: PartialEq>(
+    _map: &ModifierMap<T>,
+    _perk: Perks,
+    _calc_data: &CalculationInput,
+    _pvp: bool,
+) -> bool {
+    let func = match _map.get(&_perk) {
+        Some(func) => func,
+        None => return false,
+    };
+    let run = |value: u32| {
+        let mut cached_data = HashMap::new();
+        func(ModifierResponseInput {
+            calc_data: _calc_data,
+            value,
+            is_enhanced: false,
+            pvp: _pvp,
+            cached_data: &mut cached_data,
+        })
+    };
+    let base = run(0);
+    (1..=5).any(|value| run(value) != base)
+}
+
+//true if any of the perk's functions respond to its value in pve or pvp
+pub fn perk_reads_value(_perk: Perks, _calc_data: &CalculationInput) -> bool {
+    map_perks();
+    PERK_FUNC_MAP.with(|map| {
+        let map = map.borrow();
+        [false, true].into_iter().any(|pvp| {
+            value_changes_output(&map.sbr, _perk, _calc_data, pvp)
+                || value_changes_output(&map.dmr, _perk, _calc_data, pvp)
+                || value_changes_output(&map.hmr, _perk, _calc_data, pvp)
+                || value_changes_output(&map.rmr, _perk, _calc_data, pvp)
+                || value_changes_output(&map.rsmr, _perk, _calc_data, pvp)
+                || value_changes_output(&map.fmr, _perk, _calc_data, pvp)
+                || value_changes_output(&map.flmr, _perk, _calc_data, pvp)
+                || value_changes_output(&map.edr, _perk, _calc_data, pvp)
+                || value_changes_output(&map.rr, _perk, _calc_data, pvp)
+                || value_changes_output(&map.vmr, _perk, _calc_data, pvp)
+                || value_changes_output(&map.epr, _perk, _calc_data, pvp)
+                || value_changes_output(&map.mmr, _perk, _calc_data, pvp)
+                || value_changes_output(&map.imr, _perk, _calc_data, pvp)
+        })
+    })
+}
+
 impl PersistentModifierResponses {
     fn get_sbr(&self, perk: Perks, input: ModifierResponseInput) -> HashMap<BungieHash, StatBump> {
         if let Some(func) = self.sbr.get(&perk) {
@@ -1117,6 +1208,29 @@ pub fn get_velocity_modifier(
     velocity
 }
 
+//champion stuns don't go through the modifier maps, they only care if the perk is there.
+//only the anti-champion pseudo mods (1008-1010) and arbalest's intrinsic are mapped, other
+//exotic intrinsics and subclass verbs aren't perks here, add the matching pseudo mod for them
+pub fn get_anti_champion_mods(_perks: Vec<Perk>) -> Vec<ChampionType> {
+    let mut champion_types = vec![];
+    for perk in _perks {
+        let champion_type = match perk.hash.into() {
+            Perks::AntiBarrierMod => Some(ChampionType::BARRIER),
+            Perks::OverloadMod => Some(ChampionType::OVERLOAD),
+            Perks::UnstoppableMod => Some(ChampionType::UNSTOPPABLE),
+            //arbalest intrinsic
+            Perks::DisruptionBreak if perk.hash == 1683379515 => Some(ChampionType::BARRIER),
+            _ => None,
+        };
+        if let Some(champion_type) = champion_type {
+            if !champion_types.contains(&champion_type) {
+                champion_types.push(champion_type);
+            }
+        }
+    }
+    champion_types
+}
+
 impl Weapon {
     pub fn get_modifier_summary(
         &self,
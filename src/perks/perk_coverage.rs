@@ -0,0 +1,76 @@
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+use super::{
+    get_modifier_categories,
+    lib::CalculationInput,
+    perk_options_handler::{get_perk_options, ModifierCategory, PerkValueVariant},
+    perk_reads_value, Perks,
+};
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CoverageIssue {
+    //has modifier functions but no option data so the ui never shows it
+    NO_OPTIONS,
+    //has a toggle, slider or options but nothing registered to use them
+    NO_FUNCTIONS,
+    //functions respond to value but the options are static or missing
+    STATIC_READS_VALUE,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PerkCoverage {
+    pub perk: String,
+    pub hash: u32,
+    pub categories: Vec<ModifierCategory>,
+    #[serde(rename = "optionType")]
+    pub option_type: Option<PerkValueVariant>,
+    #[serde(rename = "readsValue")]
+    pub reads_value: bool,
+    //numbers are estimates, see PerkOptionData
+    pub approximate: bool,
+    pub issues: Vec<CoverageIssue>,
+}
+
+//value reads are found by running the functions against _calc_data, so perks
+//that only react on certain weapons can be missed with the wrong input
+pub fn get_perk_coverage(_calc_data: &CalculationInput) -> Vec<PerkCoverage> {
+    let mut coverage = Vec::new();
+    for perk in Perks::iter().filter(|perk| *perk != Perks::Ignore) {
+        let hash: u32 = perk.into();
+        let categories = get_modifier_categories(perk);
+        let options = get_perk_options(vec![hash]).remove(&hash);
+        let approximate = options
+            .as_ref()
+            .map_or(false, |options| options.approximate);
+        let option_type = options.map(|options| options.option_type);
+        let reads_value = perk_reads_value(perk, _calc_data);
+
+        let mut issues = Vec::new();
+        if option_type.is_none() && !categories.is_empty() {
+            issues.push(CoverageIssue::NO_OPTIONS);
+        }
+        let has_value = matches!(
+            option_type,
+            Some(PerkValueVariant::TOGGLE | PerkValueVariant::SLIDER | PerkValueVariant::OPTIONS)
+        );
+        if has_value && categories.is_empty() {
+            issues.push(CoverageIssue::NO_FUNCTIONS);
+        }
+        if reads_value && !has_value {
+            issues.push(CoverageIssue::STATIC_READS_VALUE);
+        }
+
+        coverage.push(PerkCoverage {
+            perk: format!("{:?}", perk),
+            hash,
+            categories,
+            option_type,
+            reads_value,
+            approximate,
+            issues,
+        });
+    }
+    coverage
+}
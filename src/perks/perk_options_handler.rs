@@ -2,9 +2,9 @@ use std::collections::HashMap;
 
 use serde::Serialize;
 
-use super::{enhanced_check, Perk, Perks};
+use super::{buff_timeline::get_buff_rule, enhanced_check, get_modifier_categories, Perk, Perks};
 
-#[derive(Debug, Clone, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Default, PartialEq)]
 pub enum PerkValueVariant {
     #[default]
     STATIC,
@@ -13,63 +13,128 @@ pub enum PerkValueVariant {
     OPTIONS,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Copy, Serialize, Default, PartialEq)]
+pub enum PerkAvailability {
+    #[default]
+    BOTH,
+    PVE,
+    PVP,
+}
+
+//which modifier maps a perk has functions in
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum ModifierCategory {
+    STATS,
+    DAMAGE,
+    HANDLING,
+    RANGE,
+    RELOAD,
+    FIRING,
+    FLINCH,
+    EXTRA_DAMAGE,
+    REFUND,
+    VELOCITY,
+    EXPLOSIVE_PERCENT,
+    MAGAZINE,
+    INVENTORY,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
 pub struct PerkOptionData {
-    stacks: (u32, u32),
-    options: Vec<String>,
+    pub stacks: (u32, u32),
+    pub options: Vec<String>,
     #[serde(rename = "optionType")]
-    option_type: PerkValueVariant,
+    pub option_type: PerkValueVariant,
+    //what the value counts, "kills", "stacks", etc
+    pub label: String,
+    //value the perk starts at when picked, the lowest stack unless a perk says otherwise
+    #[serde(rename = "defaultValue")]
+    pub default_value: u32,
+    pub availability: PerkAvailability,
+    //seconds the buff lasts once triggered, None if it isn't timed
+    pub duration: Option<f64>,
+    pub categories: Vec<ModifierCategory>,
+    //the perk's numbers are estimates that haven't been tested in game
+    pub approximate: bool,
 }
 impl PerkOptionData {
-    pub fn static_() -> PerkOptionData {
+    fn new(_stacks: (u32, u32), _options: Vec<String>, _option_type: PerkValueVariant) -> Self {
+        let label = match _option_type {
+            PerkValueVariant::STATIC => "",
+            PerkValueVariant::TOGGLE => "active",
+            PerkValueVariant::SLIDER => "stacks",
+            PerkValueVariant::OPTIONS => "option",
+        };
         PerkOptionData {
-            stacks: (0, 0),
-            options: vec![],
-            option_type: PerkValueVariant::STATIC,
+            stacks: _stacks,
+            options: _options,
+            option_type: _option_type,
+            label: label.to_string(),
+            default_value: _stacks.0,
+            availability: PerkAvailability::BOTH,
+            duration: None,
+            categories: Vec::new(),
+            approximate: false,
         }
     }
+    pub fn static_() -> PerkOptionData {
+        PerkOptionData::new((0, 0), vec![], PerkValueVariant::STATIC)
+    }
     pub fn toggle() -> PerkOptionData {
-        PerkOptionData {
-            stacks: (0, 1),
-            options: vec![],
-            option_type: PerkValueVariant::TOGGLE,
-        }
+        PerkOptionData::new((0, 1), vec![], PerkValueVariant::TOGGLE)
     }
     pub fn stacking(_stacks: u32) -> PerkOptionData {
-        PerkOptionData {
-            stacks: (0, _stacks),
-            options: vec![],
-            option_type: PerkValueVariant::SLIDER,
-        }
+        PerkOptionData::new((0, _stacks), vec![], PerkValueVariant::SLIDER)
     }
     pub fn stacking_min(_stacks: u32, _min_stacks: u32) -> PerkOptionData {
-        PerkOptionData {
-            stacks: (_min_stacks, _stacks),
-            options: vec![],
-            option_type: PerkValueVariant::SLIDER,
-        }
+        PerkOptionData::new((_min_stacks, _stacks), vec![], PerkValueVariant::SLIDER)
     }
     pub fn options(_options: Vec<&str>) -> PerkOptionData {
         let mut options = vec!["None".to_string()];
         for option in _options {
             options.push(option.to_string());
         }
-        PerkOptionData {
-            stacks: (0, options.len() as u32 - 1),
+        PerkOptionData::new(
+            (0, options.len() as u32 - 1),
             options,
-            option_type: PerkValueVariant::OPTIONS,
-        }
+            PerkValueVariant::OPTIONS,
+        )
     }
     pub fn options_raw(_options: Vec<&str>) -> PerkOptionData {
         let mut options = vec![];
         for option in _options {
             options.push(option.to_string());
         }
-        PerkOptionData {
-            stacks: (0, options.len() as u32 - 1),
+        PerkOptionData::new(
+            (0, options.len() as u32 - 1),
             options,
-            option_type: PerkValueVariant::OPTIONS,
-        }
+            PerkValueVariant::OPTIONS,
+        )
+    }
+    pub fn label(mut self, _label: &str) -> PerkOptionData {
+        self.label = _label.to_string();
+        self
+    }
+    pub fn pve_only(mut self) -> PerkOptionData {
+        self.availability = PerkAvailability::PVE;
+        self
+    }
+    pub fn pvp_only(mut self) -> PerkOptionData {
+        self.availability = PerkAvailability::PVP;
+        self
+    }
+    pub fn default_value(mut self, _value: u32) -> PerkOptionData {
+        self.default_value = _value.clamp(self.stacks.0, self.stacks.1);
+        self
+    }
+    pub fn duration(mut self, _seconds: f64) -> PerkOptionData {
+        self.duration = Some(_seconds);
+        self
+    }
+    pub fn approximate(mut self) -> PerkOptionData {
+        self.approximate = true;
+        self
     }
 }
 
@@ -108,11 +173,11 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
 
         //mods
         Perks::QuickAccessSling => Some(PerkOptionData::static_()),
-        Perks::BossSpec => Some(PerkOptionData::static_()),
-        Perks::MajorSpec => Some(PerkOptionData::static_()),
-        Perks::MinorSpec => Some(PerkOptionData::static_()),
-        Perks::BigOnesSpec => Some(PerkOptionData::static_()),
-        Perks::TakenSpec => Some(PerkOptionData::toggle()),
+        Perks::BossSpec => Some(PerkOptionData::static_().pve_only()),
+        Perks::MajorSpec => Some(PerkOptionData::static_().pve_only()),
+        Perks::MinorSpec => Some(PerkOptionData::static_().pve_only()),
+        Perks::BigOnesSpec => Some(PerkOptionData::static_().pve_only()),
+        Perks::TakenSpec => Some(PerkOptionData::toggle().pve_only()),
         Perks::FreehandGrip => Some(PerkOptionData::static_()),
 
         //origin | year 5+
@@ -132,19 +197,21 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
         Perks::TexBalancedStock => Some(PerkOptionData::toggle()),
         Perks::SearchParty => Some(PerkOptionData::toggle()),
         Perks::HarmonicResonance => Some(PerkOptionData::stacking(2)),
-        Perks::FieldTested => Some(PerkOptionData::stacking(5)),
+        Perks::FieldTested => Some(PerkOptionData::stacking(5).label("kills")),
         Perks::NobleDeeds => Some(PerkOptionData::toggle()),
 
         //season 1 | year 1
         Perks::KillClip => Some(PerkOptionData::toggle()),
         Perks::Outlaw => Some(PerkOptionData::toggle()),
-        Perks::BackupPlan => Some(PerkOptionData::toggle()),
+        Perks::BackupPlan => Some(PerkOptionData::toggle().duration(2.0)),
         Perks::FieldPrep => Some(PerkOptionData::toggle()),
-        Perks::Rampage => Some(PerkOptionData::stacking(3)),
-        Perks::OpeningShot => Some(PerkOptionData::toggle()),
+        Perks::Rampage => Some(PerkOptionData::stacking(3).label("kills")),
+        Perks::OpeningShot => Some(PerkOptionData::toggle().default_value(1)),
         Perks::MovingTarget => Some(PerkOptionData::toggle()),
-        Perks::AmbitiousAssassin => Some(PerkOptionData::stacking(15)),
+        Perks::AmbitiousAssassin => Some(PerkOptionData::stacking(15).label("kills before reload")),
         Perks::ClusterBomb => Some(PerkOptionData::static_()),
+        Perks::ChainReaction => Some(PerkOptionData::static_().approximate()),
+        Perks::Dragonfly => Some(PerkOptionData::static_().approximate()),
         Perks::ExplosivePayload => Some(PerkOptionData::static_()),
         Perks::FirmlyPlanted => Some(PerkOptionData::toggle()),
         Perks::FullAutoTrigger => Some(PerkOptionData::static_()),
@@ -156,7 +223,7 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
         Perks::SlideWays => Some(PerkOptionData::toggle()),
         Perks::QuickDraw => Some(PerkOptionData::toggle()),
         Perks::TimedPayload => Some(PerkOptionData::static_()),
-        Perks::ThreatDetector => Some(PerkOptionData::stacking(2)),
+        Perks::ThreatDetector => Some(PerkOptionData::stacking(2).label("enemies nearby")),
         Perks::SlideShot => Some(PerkOptionData::toggle()),
         Perks::TripleTap => Some(PerkOptionData::static_()),
         Perks::UnderPressure => Some(PerkOptionData::toggle()),
@@ -167,7 +234,7 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
 
         //season 3 | year 1
         Perks::RangeFinder => Some(PerkOptionData::static_()),
-        Perks::DisruptionBreak => Some(PerkOptionData::toggle()),
+        Perks::DisruptionBreak => Some(PerkOptionData::toggle().duration(4.0)),
         Perks::TrenchBarrel => Some(PerkOptionData::toggle()),
         Perks::Desperado => Some(PerkOptionData::toggle()),
         Perks::BoxBreathing => Some(PerkOptionData::toggle()),
@@ -175,9 +242,9 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
         //season 4 | year 2
         Perks::ArchersTempo => Some(PerkOptionData::toggle()),
         Perks::ExplosiveHead => Some(PerkOptionData::static_()),
-        Perks::FeedingFrenzy => Some(PerkOptionData::stacking(5)),
+        Perks::FeedingFrenzy => Some(PerkOptionData::stacking(5).label("kills")),
         Perks::FourthTimesTheCharm => Some(PerkOptionData::static_()),
-        Perks::RapidHit => Some(PerkOptionData::stacking(5)),
+        Perks::RapidHit => Some(PerkOptionData::stacking(5).label("precision hits")),
 
         //season 5 | year 2
         Perks::ResevoirBurst => Some(PerkOptionData::static_()),
@@ -187,10 +254,10 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
         //season 6 | year 2
         Perks::FiringLine => Some(PerkOptionData::toggle()),
         Perks::FullCourt => Some(PerkOptionData::toggle()),
-        Perks::KillingTally => Some(PerkOptionData::stacking(3)),
+        Perks::KillingTally => Some(PerkOptionData::stacking(3).label("kills")),
         // Perks::Demolitionist => Some(PerkOptionData::options(vec!["Once", "Every 3s"])),
-        Perks::MultikillClip => Some(PerkOptionData::stacking(3)),
-        Perks::Swashbuckler => Some(PerkOptionData::stacking(5)),
+        Perks::MultikillClip => Some(PerkOptionData::stacking(3).label("kills before reload")),
+        Perks::Swashbuckler => Some(PerkOptionData::stacking(5).label("kills").duration(4.5)),
         Perks::OverFlow => Some(PerkOptionData::toggle()),
 
         //season 7 | year 2
@@ -217,34 +284,34 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
 
         //season 12 | year 4
         Perks::DualLoader => Some(PerkOptionData::static_()),
-        Perks::OneForAll => Some(PerkOptionData::toggle()),
-        Perks::Recombination => Some(PerkOptionData::stacking(10)),
+        Perks::OneForAll => Some(PerkOptionData::toggle().duration(10.0)),
+        Perks::Recombination => Some(PerkOptionData::stacking(10).label("elemental kills")),
         Perks::Reconstruction => Some(PerkOptionData::toggle()),
-        Perks::Surplus => Some(PerkOptionData::stacking(3)),
+        Perks::Surplus => Some(PerkOptionData::stacking(3).label("charged abilities")),
 
         //season 13 | year 4
         Perks::ImpulseAmplifier => Some(PerkOptionData::static_()),
         Perks::Frenzy => Some(PerkOptionData::toggle()),
         Perks::LastingImpression => Some(PerkOptionData::static_()),
-        Perks::KickStart => Some(PerkOptionData::toggle()),
+        Perks::KickStart => Some(PerkOptionData::toggle().duration(1.0)),
 
         //season 14 | year 4
         Perks::Cornered => Some(PerkOptionData::toggle()),
-        Perks::AdrenalineJunkie => Some(PerkOptionData::stacking(5)),
+        Perks::AdrenalineJunkie => Some(PerkOptionData::stacking(5).label("kills")),
         Perks::RewindRounds => Some(PerkOptionData::static_()),
-        Perks::HeatingUp => Some(PerkOptionData::stacking(2)),
-        Perks::FireFly => Some(PerkOptionData::toggle()),
+        Perks::HeatingUp => Some(PerkOptionData::stacking(2).label("kills")),
+        Perks::FireFly => Some(PerkOptionData::toggle().approximate()),
         Perks::DangerZone => Some(PerkOptionData::toggle()),
         Perks::TunnelVision => Some(PerkOptionData::toggle()),
 
         //season 15 | year 4
-        Perks::Encore => Some(PerkOptionData::stacking(4)),
+        Perks::Encore => Some(PerkOptionData::stacking(4).label("precision kills")),
         Perks::Ensemble => Some(PerkOptionData::toggle()),
         Perks::GoldenTricorn => Some(PerkOptionData::stacking(2)),
-        Perks::Harmony => Some(PerkOptionData::toggle()),
+        Perks::Harmony => Some(PerkOptionData::toggle().duration(7.0)),
         Perks::PerpetualMotion => Some(PerkOptionData::stacking(2)),
-        Perks::Adagio => Some(PerkOptionData::toggle()),
-        Perks::BluntExecutionRounds => Some(PerkOptionData::toggle()),
+        Perks::Adagio => Some(PerkOptionData::toggle().default_value(1)),
+        Perks::BluntExecutionRounds => Some(PerkOptionData::toggle().duration(10.0)),
 
         //season 16 | year 5
         Perks::BaitAndSwitch => Some(PerkOptionData::toggle()),
@@ -278,7 +345,9 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
         //season 20 | year 6
         Perks::KeepAway => Some(PerkOptionData::toggle()),
         Perks::ParacausalAffinity => Some(PerkOptionData::toggle()),
-        Perks::EnviousAssassin => Some(PerkOptionData::stacking(20)),
+        Perks::EnviousAssassin => Some(PerkOptionData::stacking(20).label("kills before reload")),
+        Perks::Incandescent => Some(PerkOptionData::static_().approximate()),
+        Perks::Voltshot => Some(PerkOptionData::toggle().label("charged").approximate()),
 
         //season 21 | year 6
         Perks::CollectiveAction => Some(PerkOptionData::toggle()),
@@ -290,15 +359,15 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
         Perks::EddyCurrent => Some(PerkOptionData::options(["Base", "Amplified"].to_vec())),
 
         //season 22 | year 6
-        Perks::PrecisionInstrument => Some(PerkOptionData::stacking(6)),
+        Perks::PrecisionInstrument => Some(PerkOptionData::stacking(6).label("precision hits")),
         Perks::LooseChange => Some(PerkOptionData::toggle()),
         Perks::HighGround => Some(PerkOptionData::stacking(3)),
         Perks::HeadRush => Some(PerkOptionData::toggle()),
         Perks::EnlightendAction => Some(PerkOptionData::stacking(12)),
-        Perks::SwordLogic => Some(PerkOptionData::stacking(4)),
+        Perks::SwordLogic => Some(PerkOptionData::stacking(4).label("kills")),
         //season 23 | year 6
-        Perks::Onslaught => Some(PerkOptionData::stacking(3)),
-        Perks::DesperateMeasures => Some(PerkOptionData::stacking(3)),
+        Perks::Onslaught => Some(PerkOptionData::stacking(3).label("kills")),
+        Perks::DesperateMeasures => Some(PerkOptionData::stacking(3).label("kills")),
         Perks::MasterOfArms => Some(PerkOptionData::stacking(2)),
 
         //episode 1 | year 7
@@ -312,7 +381,7 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
         Perks::SplicerSurge => Some(PerkOptionData::stacking(3)),
 
         //episode 3 | year 7
-        Perks::ElementalHoning => Some(PerkOptionData::stacking(5)),
+        Perks::ElementalHoning => Some(PerkOptionData::stacking(5).label("elements")),
         Perks::TimelostMagazine => Some(PerkOptionData::toggle()),
 
         //exotics
@@ -324,7 +393,7 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
         Perks::WormsHunger => Some(PerkOptionData::stacking(20)),
         Perks::WormByproduct => Some(PerkOptionData::toggle()),
         Perks::RocketTracers => Some(PerkOptionData::static_()),
-        Perks::ParacausalShot => Some(PerkOptionData::stacking(7)),
+        Perks::ParacausalShot => Some(PerkOptionData::stacking(7).label("hits")),
         Perks::CorruptionSpreads => Some(PerkOptionData::static_()),
         Perks::TimeSlip => Some(PerkOptionData::toggle()),
         Perks::ToM => Some(PerkOptionData::toggle()),
@@ -350,12 +419,12 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
         Perks::Fundamentals => Some(PerkOptionData::toggle()),
         Perks::ThinTheHerd => Some(PerkOptionData::toggle()),
         Perks::Chimera => Some(PerkOptionData::toggle()),
-        Perks::FateOfAllFools => Some(PerkOptionData::stacking(3)),
-        Perks::HonedEdge => Some(PerkOptionData::stacking_min(4, 1)),
+        Perks::FateOfAllFools => Some(PerkOptionData::stacking(3).label("bodyshots")),
+        Perks::HonedEdge => Some(PerkOptionData::stacking_min(4, 1).label("charge level")),
         Perks::TakenPredator => Some(PerkOptionData::options(
             ["Taken", "Witherhoard", "Both"].to_vec(),
         )),
-        Perks::MarkovChain => Some(PerkOptionData::stacking(5)),
+        Perks::MarkovChain => Some(PerkOptionData::stacking(5).label("kills")),
         Perks::StormAndStress => Some(PerkOptionData::toggle()),
         Perks::DualSpeedReceiver => Some(PerkOptionData::toggle()),
         Perks::ExplosiveShadow => Some(PerkOptionData::static_()),
@@ -363,26 +432,29 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
         Perks::SpinningUp => Some(PerkOptionData::stacking(2)),
         Perks::DarkDescent => Some(PerkOptionData::toggle()),
         Perks::TargetAquired => Some(PerkOptionData::toggle()),
-        Perks::RatPack => Some(PerkOptionData::stacking_min(5, 1)),
+        Perks::RatPack => Some(PerkOptionData::stacking_min(5, 1).label("allies nearby")),
         Perks::HuntersTrance => Some(PerkOptionData::static_()),
         Perks::RideTheBull => Some(PerkOptionData::stacking(2)),
         Perks::NobleRounds => Some(PerkOptionData::toggle()),
         Perks::MementoMori => Some(PerkOptionData::toggle()),
         Perks::TractorCannon => Some(PerkOptionData::static_()),
-        Perks::HarmonicLaser => Some(PerkOptionData::stacking(2)),
+        Perks::HarmonicLaser => Some(PerkOptionData::stacking(2).label("precision hits")),
         Perks::AgersScepterCatalyst => Some(PerkOptionData::toggle()),
         Perks::ColdFusion => Some(PerkOptionData::toggle()),
         Perks::BlackHole => Some(PerkOptionData::static_()),
         Perks::TemporalUnlimiter => Some(PerkOptionData::toggle()),
         Perks::TempestCascade => Some(PerkOptionData::toggle()),
         Perks::MarksmanSights => Some(PerkOptionData::static_()),
-        Perks::DexterityMod => Some(PerkOptionData::stacking(3)),
-        Perks::ReserveMod => Some(PerkOptionData::stacking(3)),
-        Perks::LoaderMod => Some(PerkOptionData::stacking(3)),
-        Perks::TargetingMod => Some(PerkOptionData::stacking(3)),
-        Perks::UnflinchingMod => Some(PerkOptionData::stacking(3)),
-        Perks::SurgeMod => Some(PerkOptionData::stacking(3)),
-        Perks::InFlightCompensatorMod => Some(PerkOptionData::stacking(3)),
+        Perks::DexterityMod => Some(PerkOptionData::stacking(3).label("mods")),
+        Perks::ReserveMod => Some(PerkOptionData::stacking(3).label("mods")),
+        Perks::LoaderMod => Some(PerkOptionData::stacking(3).label("mods")),
+        Perks::TargetingMod => Some(PerkOptionData::stacking(3).label("mods")),
+        Perks::UnflinchingMod => Some(PerkOptionData::stacking(3).label("mods")),
+        Perks::SurgeMod => Some(PerkOptionData::stacking(3).label("mods")),
+        Perks::InFlightCompensatorMod => Some(PerkOptionData::stacking(3).label("mods")),
+        Perks::AntiBarrierMod => Some(PerkOptionData::static_().pve_only()),
+        Perks::OverloadMod => Some(PerkOptionData::static_().pve_only()),
+        Perks::UnstoppableMod => Some(PerkOptionData::static_().pve_only()),
         Perks::LucentBlades => Some(PerkOptionData::stacking(3)),
         Perks::OnYourMark => Some(PerkOptionData::stacking(3)),
         Perks::Frequency => Some(PerkOptionData::toggle()),
@@ -402,20 +474,20 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
         Perks::MoebiusQuiver => Some(PerkOptionData::static_()),
         Perks::Broadhead => Some(PerkOptionData::static_()),
         Perks::HuntersTrace => Some(PerkOptionData::toggle()),
-        Perks::Desperation => Some(PerkOptionData::toggle()),
+        Perks::Desperation => Some(PerkOptionData::toggle().duration(7.0)),
         Perks::IonicReturn => Some(PerkOptionData::toggle()),
         Perks::Unrepentant => Some(PerkOptionData::toggle()),
         Perks::ArcConductor => Some(PerkOptionData::toggle()),
-        Perks::VoidLeech => Some(PerkOptionData::toggle()),
+        Perks::VoidLeech => Some(PerkOptionData::toggle().pve_only()),
         Perks::InverseRelationship => Some(PerkOptionData::stacking(3)),
-        Perks::Spindle => Some(PerkOptionData::stacking(25)),
+        Perks::Spindle => Some(PerkOptionData::stacking(25).label("precision hits")),
         Perks::TheRightChoice => Some(PerkOptionData::static_()),
 
         Perks::DragonShadow => Some(PerkOptionData::toggle()),
         Perks::OphidianAspect => Some(PerkOptionData::static_()),
         Perks::TomeOfDawn => Some(PerkOptionData::toggle()),
         Perks::PathOfTheBurningSteps => Some(PerkOptionData::stacking(4)),
-        Perks::MantleOfBattleHarmony => Some(PerkOptionData::static_()),
+        Perks::MantleOfBattleHarmony => Some(PerkOptionData::toggle()),
         Perks::MaskOfBakris => Some(PerkOptionData::toggle()),
         Perks::BallindorseWrathweavers => Some(PerkOptionData::toggle()),
         Perks::LunaFaction => Some(PerkOptionData::options(
@@ -456,7 +528,7 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
         Perks::UmbralSharpening => Some(PerkOptionData::stacking(5)),
         Perks::EnhancedScannerAugment => Some(PerkOptionData::toggle()),
         Perks::Demolitionist => Some(PerkOptionData::static_()),
-        Perks::FullStop => Some(PerkOptionData::static_()),
+        Perks::FullStop => Some(PerkOptionData::static_().pve_only()),
         Perks::HakkeHeavyBurst => Some(PerkOptionData::static_()),
         Perks::EternalWarrior => Some(PerkOptionData::stacking(4)),
 
@@ -467,8 +539,15 @@ fn hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
 pub fn enh_hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
     let perk: Perks = enhanced_check(_hash).0.into();
     match perk {
-        Perks::Recombination => Some(PerkOptionData::stacking(8)),
+        //enhanced durations are the same ones the perks' modifier functions check against
+        Perks::Recombination => Some(PerkOptionData::stacking(8).label("elemental kills")),
         Perks::ExplosiveLight => Some(PerkOptionData::stacking(7)),
+        Perks::BackupPlan => Some(PerkOptionData::toggle().duration(2.2)),
+        Perks::DisruptionBreak => Some(PerkOptionData::toggle().duration(5.0)),
+        Perks::Swashbuckler => Some(PerkOptionData::stacking(5).label("kills").duration(6.0)),
+        Perks::OneForAll => Some(PerkOptionData::toggle().duration(11.0)),
+        Perks::Harmony => Some(PerkOptionData::toggle().duration(8.0)),
+        Perks::BluntExecutionRounds => Some(PerkOptionData::toggle().duration(11.0)),
         _ => hash_to_perk_option_data(_hash),
     }
 }
@@ -476,9 +555,23 @@ pub fn enh_hash_to_perk_option_data(_hash: u32) -> Option<PerkOptionData> {
 pub fn get_perk_options(_perks: Vec<u32>) -> HashMap<u32, PerkOptionData> {
     let mut options = HashMap::new();
     for perk in _perks {
-        // let data = if  _input._is_enhanced {enh_hash_to_perk_option_data(perk)} else {hash_to_perk_option_data(perk)};
-        let data = hash_to_perk_option_data(perk);
-        if let Some(value) = data {
+        let (hash, is_enhanced) = enhanced_check(perk);
+        let data = if is_enhanced {
+            enh_hash_to_perk_option_data(perk)
+        } else {
+            hash_to_perk_option_data(perk)
+        };
+        if let Some(mut value) = data {
+            //timed perks the sims know about already have their duration
+            let rule = get_buff_rule(&Perk {
+                hash,
+                enhanced: is_enhanced,
+                ..Default::default()
+            });
+            if let (None, Some(rule)) = (value.duration, rule) {
+                value.duration = Some(rule.duration).filter(|d| d.is_finite());
+            }
+            value.categories = get_modifier_categories(hash.into());
             options.insert(perk, value);
         }
     }
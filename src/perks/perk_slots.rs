@@ -0,0 +1,503 @@
+use serde::Serialize;
+
+use crate::d2_enums::WeaponType;
+
+use super::{Perk, Perks};
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum PerkSlot {
+    //abilities and buffs from outside the weapon, any number can be active
+    BUFF,
+    INTRINSIC,
+    BARREL,
+    MAGAZINE,
+    TRAIT,
+    ORIGIN,
+    MASTERWORK,
+    MOD,
+    EXOTIC,
+    ARMOR_MOD,
+    EXOTIC_ARMOR,
+    SUBCLASS,
+}
+impl PerkSlot {
+    //None means the slot doesn't limit how many perks can be in it
+    pub fn max_perks(&self) -> Option<usize> {
+        match self {
+            PerkSlot::INTRINSIC
+            | PerkSlot::BARREL
+            | PerkSlot::MAGAZINE
+            | PerkSlot::ORIGIN
+            | PerkSlot::MASTERWORK
+            | PerkSlot::MOD
+            | PerkSlot::EXOTIC_ARMOR => Some(1),
+            PerkSlot::TRAIT => Some(2),
+            PerkSlot::BUFF | PerkSlot::EXOTIC | PerkSlot::ARMOR_MOD | PerkSlot::SUBCLASS => None,
+        }
+    }
+}
+
+//no catch all so new perks have to pick a slot, unknown hashes come through as
+//Perks::Ignore which isn't capped
+pub fn get_perk_slot(_perk: Perks) -> PerkSlot {
+    match _perk {
+        //Meta perks
+        Perks::BuiltIn
+        | Perks::RallyBarricade
+        | Perks::EmpRift
+        | Perks::Radiant
+        | Perks::Weaken
+        | Perks::Sever
+        | Perks::WellOfRadiance
+        | Perks::WardOfDawn
+        | Perks::BannerShield
+        | Perks::DeadFall
+        | Perks::MoebiusQuiver
+        | Perks::Ignore => PerkSlot::BUFF,
+
+        //intrinsics
+        Perks::RapidFireFrame | Perks::PrecisionFrame | Perks::SupportFrame => PerkSlot::INTRINSIC,
+
+        //armor
+        Perks::DexterityMod
+        | Perks::TargetingMod
+        | Perks::ReserveMod
+        | Perks::LoaderMod
+        | Perks::UnflinchingMod
+        | Perks::SurgeMod
+        | Perks::InFlightCompensatorMod
+        | Perks::AntiBarrierMod
+        | Perks::OverloadMod
+        | Perks::UnstoppableMod => PerkSlot::ARMOR_MOD,
+        Perks::LucentBlades
+        | Perks::DragonShadow
+        | Perks::OphidianAspect
+        | Perks::LunaFaction
+        | Perks::TomeOfDawn
+        | Perks::PathOfTheBurningSteps
+        | Perks::KnuckleheadRadar
+        | Perks::Foetracers
+        | Perks::MechaneersTricksleeves
+        | Perks::Oathkeeper
+        | Perks::SealedAhamkaraGrasps
+        | Perks::LuckyPants
+        | Perks::NoBackupPlans
+        | Perks::ActiumWarRig
+        | Perks::HallowfireHeart
+        | Perks::LionRampart
+        | Perks::Peacekeepers
+        | Perks::PeregrineGreaves
+        | Perks::EyeOfAnotherWorld
+        | Perks::AstrocyteVerse
+        | Perks::NecroticGrips
+        | Perks::BootsOfTheAssembler
+        | Perks::RainOfFire
+        | Perks::SpeedloaderSlacks
+        | Perks::MantleOfBattleHarmony
+        | Perks::MaskOfBakris
+        | Perks::BallindorseWrathweavers
+        | Perks::Gyrfalcon
+        | Perks::AeonInsight
+        | Perks::AeonForce
+        | Perks::Felwinters
+        | Perks::SanguineAlchemy
+        | Perks::EternalWarrior
+        | Perks::TritonVice
+        | Perks::GlacialGuard
+        | Perks::DoomFang
+        | Perks::BurningFists => PerkSlot::EXOTIC_ARMOR,
+
+        //parts
+        Perks::FullChoke | Perks::LiquidCoils | Perks::AcceleratedCoils => PerkSlot::BARREL,
+        Perks::ImpactCasing
+        | Perks::SwapMag
+        | Perks::SpikeGrenades
+        | Perks::AlloyMag
+        | Perks::DisorientingGrenades
+        | Perks::AssaultMag
+        | Perks::PhaseMag => PerkSlot::MAGAZINE,
+        Perks::ChargetimeMW => PerkSlot::MASTERWORK,
+
+        //bow strings
+        Perks::SlowerStringT1 | Perks::FasterStringT2 | Perks::FasterStringT1 => PerkSlot::BARREL,
+
+        //mods
+        Perks::QuickAccessSling
+        | Perks::BossSpec
+        | Perks::MajorSpec
+        | Perks::MinorSpec
+        | Perks::BigOnesSpec
+        | Perks::TakenSpec
+        | Perks::AdeptChargeTime
+        | Perks::FreehandGrip => PerkSlot::MOD,
+        Perks::UmbralSharpening | Perks::EnhancedScannerAugment => PerkSlot::BUFF,
+
+        //origin | year 5+
+        Perks::VeistStinger
+        | Perks::HakkeBreach
+        | Perks::Alacrity
+        | Perks::FluidDynamics
+        | Perks::QuietMoment
+        | Perks::SurosSynergy
+        | Perks::BitterSpite
+        | Perks::RunnethOver
+        | Perks::HotSwap
+        | Perks::RightHook
+        | Perks::Ambush
+        | Perks::TexBalancedStock
+        | Perks::SearchParty
+        | Perks::FieldTested
+        | Perks::HarmonicResonance
+        | Perks::NobleDeeds => PerkSlot::ORIGIN,
+
+        //subclass
+        Perks::OnYourMark
+        | Perks::Hedrons
+        | Perks::FlowState
+        | Perks::Frequency
+        | Perks::HeatRises
+        | Perks::Tempering
+        | Perks::ThreadOfAscent
+        | Perks::Amplified => PerkSlot::SUBCLASS,
+
+        //kinetic exotic
+        Perks::CranialSpike
+        | Perks::DarkForgedTrigger
+        | Perks::AgersCall
+        | Perks::RatPack
+        | Perks::StringofCurses
+        | Perks::RocketTracers
+        | Perks::ParacausalShot
+        | Perks::TimeSlip
+        | Perks::ToM
+        | Perks::CorruptionSpreads
+        | Perks::HonedEdge
+        | Perks::HakkeHeavyBurst
+        | Perks::FateOfAllFools
+        | Perks::ExplosiveShadow
+        | Perks::TakenPredator
+        | Perks::SurosLegacy
+        | Perks::SpinningUp
+        | Perks::DualSpeedReceiver
+        | Perks::FullStop
+        | Perks::RideTheBull
+        | Perks::HuntersTrance
+        | Perks::NobleRounds
+        | Perks::StormAndStress
+        | Perks::Roadborn
+        | Perks::MarkovChain
+        | Perks::MementoMori
+        | Perks::AgersScepterCatalyst
+        | Perks::Broadhead
+        | Perks::HuntersTrace
+        | Perks::Desperation
+        | Perks::PickYourPoison
+        | Perks::StringTheory => PerkSlot::EXOTIC,
+
+        //energy exotic
+        Perks::LagragianSight
+        | Perks::IgnitionTrigger
+        | Perks::GuidanceRing
+        | Perks::FirstGlance
+        | Perks::ConserveMomentum
+        | Perks::Broadside
+        | Perks::FourthHorsemanCatalyst
+        | Perks::Impetus
+        | Perks::Stormbringer
+        | Perks::PerfectFith
+        | Perks::PrismaticInferno
+        | Perks::CalculatedBalance
+        | Perks::Chimera
+        | Perks::ThinTheHerd
+        | Perks::RavenousBeast
+        | Perks::LordOfWolvesCatalyst
+        | Perks::ReleaseTheWolves
+        | Perks::SwoopingTalons
+        | Perks::Fundamentals
+        | Perks::HarmonicLaser
+        | Perks::ColdFusion
+        | Perks::BlackHole
+        | Perks::TemporalUnlimiter
+        | Perks::IonicReturn
+        | Perks::Unrepentant
+        | Perks::ArcConductor
+        | Perks::VoidLeech
+        | Perks::InverseRelationship
+        | Perks::Spindle
+        | Perks::TheRightChoice
+        | Perks::Judgment
+        | Perks::TempestCascade => PerkSlot::EXOTIC,
+
+        //heavy exotic
+        Perks::ReignHavoc
+        | Perks::WormsHunger
+        | Perks::WormByproduct
+        | Perks::WhiteNail
+        | Perks::WhisperedBreathing
+        | Perks::DarkDescent
+        | Perks::TargetAquired
+        | Perks::TractorCannon
+        | Perks::MarksmanSights => PerkSlot::EXOTIC,
+
+        //traits | year 1
+        Perks::KillClip
+        | Perks::Outlaw
+        | Perks::BackupPlan
+        | Perks::FieldPrep
+        | Perks::Rampage
+        | Perks::OpeningShot
+        | Perks::MovingTarget
+        | Perks::AmbitiousAssassin
+        | Perks::ClusterBomb
+        | Perks::ChainReaction
+        | Perks::Dragonfly
+        | Perks::ExplosivePayload
+        | Perks::FirmlyPlanted
+        | Perks::FullAutoTrigger
+        | Perks::HeadSeeker
+        | Perks::HighImpactReserves
+        | Perks::HipFireGrip
+        | Perks::Snapshot
+        | Perks::TapTheTrigger
+        | Perks::SlideWays
+        | Perks::QuickDraw
+        | Perks::TimedPayload
+        | Perks::ThreatDetector
+        | Perks::SlideShot
+        | Perks::TripleTap
+        | Perks::UnderPressure
+        | Perks::PulseMonitor
+        | Perks::RangeFinder
+        | Perks::DisruptionBreak
+        | Perks::TrenchBarrel
+        | Perks::Desperado
+        | Perks::BoxBreathing => PerkSlot::TRAIT,
+
+        //traits | year 2
+        Perks::ArchersTempo
+        | Perks::ExplosiveHead
+        | Perks::FeedingFrenzy
+        | Perks::FourthTimesTheCharm
+        | Perks::RapidHit
+        | Perks::ResevoirBurst
+        | Perks::Surrounded
+        | Perks::AirAssault
+        | Perks::OverFlow
+        | Perks::FiringLine
+        | Perks::FullCourt
+        | Perks::KillingTally
+        | Perks::Demolitionist
+        | Perks::MultikillClip
+        | Perks::Swashbuckler
+        | Perks::ExplosiveLight
+        | Perks::EyeOfTheStorm
+        | Perks::NoDistractions
+        | Perks::ArchersGambit => PerkSlot::TRAIT,
+
+        //traits | year 3
+        Perks::ClownCartridge | Perks::ElementalCapacitor | Perks::Vorpal | Perks::KillingWind => {
+            PerkSlot::TRAIT
+        }
+
+        //traits | year 4
+        Perks::DualLoader
+        | Perks::OneForAll
+        | Perks::Recombination
+        | Perks::Reconstruction
+        | Perks::Surplus
+        | Perks::ImpulseAmplifier
+        | Perks::Frenzy
+        | Perks::LastingImpression
+        | Perks::KickStart
+        | Perks::Cornered
+        | Perks::AdrenalineJunkie
+        | Perks::RewindRounds
+        | Perks::HeatingUp
+        | Perks::FireFly
+        | Perks::DangerZone
+        | Perks::TunnelVision
+        | Perks::Encore
+        | Perks::Ensemble
+        | Perks::GoldenTricorn
+        | Perks::Harmony
+        | Perks::PerpetualMotion
+        | Perks::Adagio
+        | Perks::BluntExecutionRounds => PerkSlot::TRAIT,
+
+        //traits | year 5
+        Perks::BaitAndSwitch
+        | Perks::CompulsiveReloader
+        | Perks::FocusedFury
+        | Perks::ChillClip
+        | Perks::SleightOfHand
+        | Perks::StatsForAll
+        | Perks::SteadyHands
+        | Perks::SuccesfulWarmup
+        | Perks::UnstoppableForce
+        | Perks::FragileFocus
+        | Perks::WellRounded
+        | Perks::GutShot
+        | Perks::Pugilist
+        | Perks::Slickdraw
+        | Perks::UnderOver
+        | Perks::CascadePoint
+        | Perks::CloseToMelee
+        | Perks::OffhandStrike
+        | Perks::PerfectFloat
+        | Perks::ShotSwap
+        | Perks::TargetLock => PerkSlot::TRAIT,
+
+        //traits | year 6
+        Perks::KeepAway
+        | Perks::ParacausalAffinity
+        | Perks::EnviousAssassin
+        | Perks::Incandescent
+        | Perks::Voltshot
+        | Perks::CollectiveAction
+        | Perks::Bipod
+        | Perks::ControlledBurst
+        | Perks::InvisibleHand
+        | Perks::UnsatedHunger
+        | Perks::Discord
+        | Perks::EddyCurrent
+        | Perks::PrecisionInstrument
+        | Perks::LooseChange
+        | Perks::HighGround
+        | Perks::HeadRush
+        | Perks::EnlightendAction
+        | Perks::SwordLogic
+        | Perks::Onslaught
+        | Perks::DesperateMeasures
+        | Perks::MasterOfArms => PerkSlot::TRAIT,
+
+        //traits | year 7
+        Perks::ChaosReshaped
+        | Perks::CircleOfLife
+        | Perks::AirTrigger
+        | Perks::ClosingTime
+        | Perks::LoneWolf
+        | Perks::SplicerSurge
+        | Perks::ElementalHoning
+        | Perks::TimelostMagazine => PerkSlot::TRAIT,
+    }
+}
+
+//trait hashes that aren't the perk's own are exotic catalysts or weapon specific
+//versions reusing its functions, they don't take up a trait column
+pub fn get_hash_slot(_hash: u32) -> PerkSlot {
+    let perk = Perks::from(_hash);
+    match get_perk_slot(perk) {
+        PerkSlot::TRAIT if u32::from(perk) != _hash => PerkSlot::EXOTIC,
+        slot => slot,
+    }
+}
+
+//None means the perk isn't limited to any weapon type
+pub fn get_allowed_weapon_types(_perk: Perks) -> Option<&'static [WeaponType]> {
+    const FUSIONS: &[WeaponType] = &[WeaponType::FUSIONRIFLE, WeaponType::LINEARFUSIONRIFLE];
+    match _perk {
+        Perks::FullChoke => Some(&[WeaponType::SHOTGUN]),
+        Perks::SpikeGrenades | Perks::DisorientingGrenades => Some(&[WeaponType::GRENADELAUNCHER]),
+        Perks::LiquidCoils
+        | Perks::AcceleratedCoils
+        | Perks::ChargetimeMW
+        | Perks::AdeptChargeTime => Some(FUSIONS),
+        Perks::SlowerStringT1 | Perks::FasterStringT2 | Perks::FasterStringT1 => {
+            Some(&[WeaponType::BOW])
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PerkConflict {
+    WeaponType {
+        perk: Perks,
+        weapon_type: WeaponType,
+    },
+    SlotFull {
+        perk: Perks,
+        slot: PerkSlot,
+        //sorted by hash so the message is stable
+        taken_by: Vec<u32>,
+    },
+}
+impl PerkConflict {
+    //single perk slots can be swapped over, with two traits it's up to the caller which one goes
+    pub fn replaceable(&self) -> Option<&[u32]> {
+        match self {
+            PerkConflict::SlotFull { slot, taken_by, .. } if slot.max_perks() == Some(1) => {
+                Some(taken_by)
+            }
+            _ => None,
+        }
+    }
+}
+impl std::fmt::Display for PerkConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PerkConflict::WeaponType { perk, weapon_type } => write!(
+                f,
+                "{:?} can't be used on a {:?}, it needs one of {:?}",
+                perk,
+                weapon_type,
+                get_allowed_weapon_types(*perk).unwrap_or_default()
+            ),
+            PerkConflict::SlotFull {
+                perk,
+                slot,
+                taken_by,
+            } => {
+                let names: Vec<String> = taken_by
+                    .iter()
+                    .map(|hash| format!("{:?}", Perks::from(*hash)))
+                    .collect();
+                write!(
+                    f,
+                    "{:?} conflicts with {} in the {:?} slot",
+                    perk,
+                    names.join(" and "),
+                    slot
+                )
+            }
+        }
+    }
+}
+
+//checks _perk against the perks already on a weapon of _weapon_type
+pub fn check_perk_conflicts(
+    _perk: &Perk,
+    _current: &[Perk],
+    _weapon_type: &WeaponType,
+) -> Result<(), PerkConflict> {
+    let perk = Perks::from(_perk.hash);
+    if let Some(types) = get_allowed_weapon_types(perk) {
+        if *_weapon_type != WeaponType::UNKNOWN && !types.contains(_weapon_type) {
+            return Err(PerkConflict::WeaponType {
+                perk,
+                weapon_type: *_weapon_type,
+            });
+        }
+    }
+    let slot = get_hash_slot(_perk.hash);
+    let max = match slot.max_perks() {
+        Some(max) => max,
+        None => return Ok(()),
+    };
+    let mut taken_by: Vec<u32> = _current
+        .iter()
+        .filter(|other| other.hash != _perk.hash)
+        .filter(|other| get_hash_slot(other.hash) == slot)
+        .map(|other| other.hash)
+        .collect();
+    if taken_by.len() < max {
+        return Ok(());
+    }
+    taken_by.sort();
+    Err(PerkConflict::SlotFull {
+        perk,
+        slot,
+        taken_by,
+    })
+}
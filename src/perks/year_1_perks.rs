@@ -591,12 +591,14 @@ pub fn year_1_perks() {
     add_dmr(
         Perks::Rampage,
         Box::new(|_input: ModifierResponseInput| -> DamageModifierResponse {
-            let val = clamp(_input.value, 0, 3);
-            let mut damage_mult = 1.1_f64.powi(val as i32) - 1.0;
             let duration = if _input.is_enhanced { 5.0 } else { 4.0 };
-            if _input.calc_data.time_total > duration {
-                damage_mult = 0.0;
+            let mut val = clamp(_input.value, 0, 3);
+            if let Some(stacks) = _input.calc_data.buff_stacks(Perks::Rampage) {
+                val = clamp(stacks, 0, 3);
+            } else if _input.calc_data.time_total > duration {
+                val = 0;
             };
+            let mut damage_mult = 1.1_f64.powi(val as i32) - 1.0;
             if _input.calc_data.perk_value_map.contains_key(&630329983) && !_input.pvp {
                 //huckleberry
                 damage_mult *= 2.0;
@@ -614,7 +616,9 @@ pub fn year_1_perks() {
         Box::new(|_input: ModifierResponseInput| -> DamageModifierResponse {
             let mut damage_mult = if _input.value > 0 { 0.25 } else { 0.0 };
             let duration = if _input.is_enhanced { 5.0 } else { 4.0 };
-            if _input.calc_data.time_total > duration {
+            if let Some(stacks) = _input.calc_data.buff_stacks(Perks::KillClip) {
+                damage_mult = if stacks > 0 { 0.25 } else { 0.0 };
+            } else if _input.calc_data.time_total > duration {
                 damage_mult = 0.0;
             };
             DamageModifierResponse {
@@ -704,6 +708,52 @@ pub fn year_1_perks() {
                 weapon_scale: true,
                 hit_at_same_time: true,
                 is_dot: false,
+                splash_radius: 3.0,
+                on_kill: false,
+            }
+        }),
+    );
+
+    add_edr(
+        Perks::ChainReaction,
+        Box::new(|_input: ModifierResponseInput| -> ExtraDamageResponse {
+            //not measured, there's no tested value for the explosion yet. 0.75 of base
+            //damage in a 5m radius is a placeholder, the perk is flagged approximate
+            ExtraDamageResponse {
+                additive_damage: _input.calc_data.curr_firing_data.damage * 0.75,
+                combatant_scale: true,
+                crit_scale: false,
+                increment_total_time: false,
+                time_for_additive_damage: 0.0,
+                times_to_hit: 1,
+                weapon_scale: true,
+                hit_at_same_time: true,
+                is_dot: false,
+                splash_radius: 5.0,
+                on_kill: true,
+            }
+        }),
+    );
+
+    add_edr(
+        Perks::Dragonfly,
+        Box::new(|_input: ModifierResponseInput| -> ExtraDamageResponse {
+            //only procs on precision kills, dps calc assumes every hit is one anyway.
+            //not measured, 0.5 of base damage (0.55 enhanced) in a 4m radius is a
+            //placeholder, the perk is flagged approximate
+            let mult = if _input.is_enhanced { 0.55 } else { 0.5 };
+            ExtraDamageResponse {
+                additive_damage: _input.calc_data.curr_firing_data.damage * mult,
+                combatant_scale: true,
+                crit_scale: false,
+                increment_total_time: false,
+                time_for_additive_damage: 0.0,
+                times_to_hit: 1,
+                weapon_scale: true,
+                hit_at_same_time: true,
+                is_dot: false,
+                splash_radius: 4.0,
+                on_kill: true,
             }
         }),
     );
@@ -814,13 +864,15 @@ pub fn year_1_perks() {
         Perks::PhaseMag,
         Box::new(|_input: ModifierResponseInput| -> DamageModifierResponse {
             //set up precision smg to get damage values from
-            let precision = Weapon::generate_weapon(
+            //without formula data for it there's nothing to scale to
+            let Some(precision) = Weapon::generate_weapon(
                 0, 24,         //smg
                 1636108362, //precision
                 1,          //primary
                 3949783978, //strand
-            )
-            .unwrap();
+            ) else {
+                return DamageModifierResponse::default();
+            };
 
             let p_data = precision.get_damage_profile(_input.pvp);
 
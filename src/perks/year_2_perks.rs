@@ -73,8 +73,9 @@ pub fn year_2_perks() {
     add_rsmr(
         Perks::FeedingFrenzy,
         Box::new(|_input: ModifierResponseInput| -> ReloadModifierResponse {
-            let val = clamp(_input.value, 0, 5);
             let duration = 3.5;
+            let buff_stacks = _input.calc_data.buff_stacks(Perks::FeedingFrenzy);
+            let val = clamp(buff_stacks.unwrap_or(_input.value), 0, 5);
             let mut reload_mult = 1.0;
             let mut reload = 0;
             if val == 1 {
@@ -93,7 +94,7 @@ pub fn year_2_perks() {
                 reload = 100;
                 reload_mult = 0.8;
             };
-            if _input.calc_data.time_total > duration {
+            if _input.calc_data.time_total > duration && buff_stacks.is_none() {
                 reload = 0;
                 reload_mult = 1.0;
             };
@@ -108,8 +109,9 @@ pub fn year_2_perks() {
         Perks::FeedingFrenzy,
         Box::new(|_input: ModifierResponseInput| -> HashMap<u32, i32> {
             let mut stats = HashMap::new();
-            let val = clamp(_input.value, 0, 5);
             let duration = 3.5;
+            let buff_stacks = _input.calc_data.buff_stacks(Perks::FeedingFrenzy);
+            let val = clamp(buff_stacks.unwrap_or(_input.value), 0, 5);
             let mut reload = 0;
             if val == 1 {
                 reload = 8;
@@ -122,7 +124,7 @@ pub fn year_2_perks() {
             } else if val == 5 {
                 reload = 100;
             };
-            if _input.calc_data.time_total > duration {
+            if _input.calc_data.time_total > duration && buff_stacks.is_none() {
                 reload = 0;
             };
             stats.insert(StatHashes::RELOAD.into(), reload);
@@ -310,11 +312,13 @@ pub fn year_2_perks() {
     add_dmr(
         Perks::MultikillClip,
         Box::new(|_input: ModifierResponseInput| -> DamageModifierResponse {
-            let val = clamp(_input.value, 0, 5);
-            let mut damage_mult = (1.0 / 6.0) * val as f64;
-            if _input.calc_data.num_reloads > 0.0 {
-                damage_mult = 0.0;
+            let mut val = clamp(_input.value, 0, 5);
+            if let Some(stacks) = _input.calc_data.buff_stacks(Perks::MultikillClip) {
+                val = clamp(stacks, 0, 5);
+            } else if _input.calc_data.num_reloads > 0.0 {
+                val = 0;
             };
+            let damage_mult = (1.0 / 6.0) * val as f64;
             DamageModifierResponse {
                 impact_dmg_scale: 1.0 + damage_mult,
                 explosive_dmg_scale: 1.0 + damage_mult,
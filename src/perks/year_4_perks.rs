@@ -3,8 +3,8 @@ use std::collections::HashMap;
 use crate::d2_enums::{StatHashes, WeaponType};
 
 use super::{
-    add_dmr, add_epr, add_flmr, add_fmr, add_hmr, add_mmr, add_rmr, add_rsmr, add_sbr, add_vmr,
-    clamp,
+    add_dmr, add_edr, add_epr, add_flmr, add_fmr, add_hmr, add_mmr, add_rmr, add_rsmr, add_sbr,
+    add_vmr, clamp,
     lib::{
         CalculationInput, DamageModifierResponse, ExtraDamageResponse, FiringModifierResponse,
         FlinchModifierResponse, HandlingModifierResponse, MagazineModifierResponse,
@@ -24,7 +24,11 @@ pub fn year_4_perks() {
             {
                 dmg_boost = 0.2;
             };
-            if _input.calc_data.time_total > duration || _input.value == 0 {
+            let active = match _input.calc_data.buff_stacks(Perks::Adagio) {
+                Some(stacks) => stacks > 0,
+                None => _input.calc_data.time_total <= duration && _input.value > 0,
+            };
+            if !active {
                 dmg_boost = 0.0;
             };
             DamageModifierResponse {
@@ -40,7 +44,11 @@ pub fn year_4_perks() {
         Box::new(|_input: ModifierResponseInput| -> FiringModifierResponse {
             let duration = if _input.is_enhanced { 8.0 } else { 7.0 };
             let mut firing_slow = 1.2;
-            if _input.calc_data.time_total > duration || _input.value == 0 {
+            let active = match _input.calc_data.buff_stacks(Perks::Adagio) {
+                Some(stacks) => stacks > 0,
+                None => _input.calc_data.time_total <= duration && _input.value > 0,
+            };
+            if !active {
                 firing_slow = 1.0;
             };
             FiringModifierResponse {
@@ -57,7 +65,11 @@ pub fn year_4_perks() {
         Box::new(|_input: ModifierResponseInput| -> HashMap<u32, i32> {
             let mut map = HashMap::new();
             let duration = if _input.is_enhanced { 8.0 } else { 7.0 };
-            if _input.calc_data.time_total <= duration && _input.value > 0 {
+            let active = match _input.calc_data.buff_stacks(Perks::Adagio) {
+                Some(stacks) => stacks > 0,
+                None => _input.calc_data.time_total <= duration && _input.value > 0,
+            };
+            if active {
                 map.insert(StatHashes::RANGE.into(), 10);
             }
             map
@@ -81,12 +93,14 @@ pub fn year_4_perks() {
     add_dmr(
         Perks::AdrenalineJunkie,
         Box::new(|_input: ModifierResponseInput| -> DamageModifierResponse {
-            let val = clamp(_input.value, 0, 5);
             let duration = if _input.is_enhanced { 6.0 } else { 4.5 };
-            let mut dmg_boost = 0.067 * val as f64;
-            if _input.calc_data.time_total > duration {
-                dmg_boost = 0.0;
+            let mut val = clamp(_input.value, 0, 5);
+            if let Some(stacks) = _input.calc_data.buff_stacks(Perks::AdrenalineJunkie) {
+                val = clamp(stacks, 0, 5);
+            } else if _input.calc_data.time_total > duration {
+                val = 0;
             };
+            let dmg_boost = 0.067 * val as f64;
             DamageModifierResponse {
                 impact_dmg_scale: 1.0 + dmg_boost,
                 explosive_dmg_scale: 1.0 + dmg_boost,
@@ -100,7 +114,11 @@ pub fn year_4_perks() {
         Box::new(|_input: ModifierResponseInput| -> HashMap<u32, i32> {
             let duration = if _input.is_enhanced { 6.0 } else { 4.5 };
             let mut handling = 0;
-            if _input.calc_data.time_total <= duration && _input.value > 0 {
+            if let Some(stacks) = _input.calc_data.buff_stacks(Perks::AdrenalineJunkie) {
+                if stacks > 0 {
+                    handling = 20;
+                }
+            } else if _input.calc_data.time_total <= duration && _input.value > 0 {
                 handling = 20;
             };
             let mut out = HashMap::new();
@@ -744,4 +762,25 @@ pub fn year_4_perks() {
             }
         }),
     );
+
+    add_edr(
+        Perks::FireFly,
+        Box::new(|_input: ModifierResponseInput| -> ExtraDamageResponse {
+            //precision kills only, same as dragonfly. not measured either, 0.5 of base
+            //damage in a 3.5m radius is a placeholder, the perk is flagged approximate
+            ExtraDamageResponse {
+                additive_damage: _input.calc_data.curr_firing_data.damage * 0.5,
+                combatant_scale: true,
+                crit_scale: false,
+                increment_total_time: false,
+                time_for_additive_damage: 0.0,
+                times_to_hit: 1,
+                weapon_scale: true,
+                hit_at_same_time: true,
+                is_dot: false,
+                splash_radius: 3.5,
+                on_kill: true,
+            }
+        }),
+    );
 }
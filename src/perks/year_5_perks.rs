@@ -17,8 +17,14 @@ pub fn year_5_perks() {
         Perks::CascadePoint,
         Box::new(|_input: ModifierResponseInput| -> FiringModifierResponse {
             let duration = if _input.is_enhanced { 3.0 } else { 2.5 };
+            //sims that track kills run it off the last kill instead of the fight start
+            let active = if _input.calc_data.kill_events {
+                _input.calc_data.time_since_last_kill < duration
+            } else {
+                _input.calc_data.time_total < duration && _input.value > 0
+            };
             let mut delay_mult = 1.0;
-            if _input.calc_data.time_total < duration && _input.value > 0 {
+            if active {
                 if *_input.calc_data.weapon_type == WeaponType::MACHINEGUN
                     || *_input.calc_data.weapon_type == WeaponType::SUBMACHINEGUN
                 {
@@ -3,8 +3,8 @@ use std::{collections::HashMap, default, hash::Hash};
 use crate::d2_enums::{AmmoType, BungieHash, DamageType, StatBump, StatHashes, WeaponType};
 
 use super::{
-    add_dmr, add_epr, add_fmr, add_hmr, add_imr, add_mmr, add_rmr, add_rsmr, add_sbr, add_vmr,
-    clamp,
+    add_dmr, add_edr, add_epr, add_fmr, add_hmr, add_imr, add_mmr, add_rmr, add_rsmr, add_sbr,
+    add_vmr, clamp,
     lib::{
         CalculationInput, DamageModifierResponse, ExplosivePercentResponse, ExtraDamageResponse,
         FiringModifierResponse, HandlingModifierResponse, InventoryModifierResponse,
@@ -728,5 +728,62 @@ pub fn year_6_perks() {
                 ..Default::default()
             }
         }),
-    )
+    );
+
+    add_edr(
+        Perks::Incandescent,
+        Box::new(|_input: ModifierResponseInput| -> ExtraDamageResponse {
+            //scorch spread, ticks over a second and a half. the game applies scorch
+            //stacks rather than weapon damage, 0.12 of base damage per tick is a
+            //placeholder until the scorch damage is measured, flagged approximate
+            ExtraDamageResponse {
+                additive_damage: _input.calc_data.curr_firing_data.damage * 0.12,
+                combatant_scale: true,
+                crit_scale: false,
+                increment_total_time: false,
+                time_for_additive_damage: 0.5,
+                times_to_hit: 3,
+                weapon_scale: false,
+                hit_at_same_time: false,
+                is_dot: true,
+                splash_radius: 8.0,
+                on_kill: true,
+            }
+        }),
+    );
+
+    add_edr(
+        Perks::Voltshot,
+        Box::new(|_input: ModifierResponseInput| -> ExtraDamageResponse {
+            //a kill + reload charges the weapon, the next shot jolts the target and
+            //it arcs to nearby targets. without kills the toggle means the fight
+            //starts charged
+            let charged = match _input.calc_data.buff_stacks(Perks::Voltshot) {
+                Some(stacks) => stacks > 0,
+                None => {
+                    _input.value > 0
+                        && _input.calc_data.num_reloads == 0.0
+                        && _input.calc_data.shots_fired_this_mag <= 1.0
+                }
+            };
+            if !charged {
+                return ExtraDamageResponse::default();
+            }
+            //not measured, a flat 40 per jolt arc over 4 arcs is a placeholder until
+            //jolt damage is tested, flagged approximate
+            ExtraDamageResponse {
+                additive_damage: 40.0,
+                combatant_scale: true,
+                crit_scale: false,
+                increment_total_time: false,
+                time_for_additive_damage: 0.25,
+                times_to_hit: 4,
+                weapon_scale: false,
+                hit_at_same_time: false,
+                is_dot: false,
+                splash_radius: 15.0,
+                on_kill: false,
+            }
+        }),
+    );
 }
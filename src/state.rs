@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::activity::{damage_calc::DifficultyOptions, Activity, Player};
+use crate::d2_enums::{AmmoType, DamageType};
+use crate::enemies::{BossEncounter, ChampionProfile, Enemy, EnemyType};
+use crate::error::OracleError;
+use crate::perks::{enhanced_check, Perk};
+use crate::weapons::{masterwork::MasterworkData, sockets::SocketLayout, Stat, Weapon};
+use crate::PersistentData;
+
+//bump when a saved field changes meaning, other versions are refused on import
+pub const STATE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerkState {
+    //the hash addTrait got, enhanced perks keep their enhanced hash
+    pub hash: u32,
+    #[serde(default)]
+    pub value: u32,
+    #[serde(default)]
+    pub stats: BTreeMap<u32, i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeaponState {
+    pub hash: u32,
+    #[serde(rename = "weaponTypeId")]
+    pub weapon_type_id: u8,
+    #[serde(rename = "intrinsicHash")]
+    pub intrinsic_hash: u32,
+    #[serde(rename = "ammoType")]
+    pub ammo_type: AmmoType,
+    #[serde(rename = "damageType")]
+    pub damage_type: DamageType,
+    //base values, trait and masterwork bumps get re-applied
+    #[serde(default)]
+    pub stats: BTreeMap<u32, i32>,
+    #[serde(default)]
+    pub perks: Vec<PerkState>,
+    #[serde(default)]
+    pub masterwork: MasterworkData,
+    #[serde(default)]
+    pub sockets: SocketLayout,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncounterState {
+    #[serde(rename = "recommendedPl")]
+    pub recommended_pl: u32,
+    #[serde(rename = "overrideCap")]
+    pub override_cap: i32,
+    pub difficulty: DifficultyOptions,
+    #[serde(rename = "playerPl")]
+    pub player_pl: u32,
+    #[serde(rename = "enemyType")]
+    pub enemy_type: EnemyType,
+    #[serde(rename = "enemyHealth")]
+    pub enemy_health: f64,
+    #[serde(default)]
+    pub champion: Option<ChampionProfile>,
+    #[serde(default, rename = "bossEncounter")]
+    pub boss_encounter: BossEncounter,
+}
+
+//everything the setters put into PersistentData, formulas come from the active
+//database on import so a state keeps working across formula updates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedState {
+    pub version: u32,
+    pub weapon: WeaponState,
+    pub encounter: EncounterState,
+}
+impl SavedState {
+    pub fn to_json(&self) -> Result<String, OracleError> {
+        serde_json::to_string(self)
+            .map_err(|err| OracleError::Data(format!("Could not write state: {}", err)))
+    }
+
+    pub fn from_json(_json: &str) -> Result<SavedState, OracleError> {
+        //read the version first so newer states get a useful error instead of a
+        //missing field
+        #[derive(Deserialize)]
+        struct Version {
+            version: u32,
+        }
+        let version: Version = serde_json::from_str(_json)
+            .map_err(|err| OracleError::InvalidInput(format!("Invalid state: {}", err)))?;
+        if version.version != STATE_VERSION {
+            return Err(OracleError::InvalidInput(format!(
+                "State version {} isn't supported, expected {}",
+                version.version, STATE_VERSION
+            )));
+        }
+        serde_json::from_str(_json)
+            .map_err(|err| OracleError::InvalidInput(format!("Invalid state: {}", err)))
+    }
+}
+
+impl PersistentData {
+    pub fn export_state(&self) -> SavedState {
+        let weapon = &self.weapon;
+        let mut perks: Vec<PerkState> = weapon
+            .perks
+            .values()
+            .map(|perk| PerkState {
+                hash: perk.raw_hash,
+                value: perk.value,
+                stats: perk.stat_buffs.clone().into_iter().collect(),
+            })
+            .collect();
+        perks.sort_by_key(|perk| perk.hash);
+        SavedState {
+            version: STATE_VERSION,
+            weapon: WeaponState {
+                hash: weapon.hash,
+                weapon_type_id: weapon.weapon_type as u8,
+                intrinsic_hash: weapon.raw_intrinsic_hash(),
+                ammo_type: weapon.ammo_type,
+                damage_type: weapon.damage_type,
+                stats: weapon
+                    .stats
+                    .iter()
+                    .map(|(hash, stat)| (*hash, stat.base_value))
+                    .collect(),
+                perks,
+                masterwork: weapon.masterwork.clone(),
+                sockets: self.sockets.clone(),
+            },
+            encounter: EncounterState {
+                recommended_pl: self.activity.rpl,
+                override_cap: self.activity.cap,
+                difficulty: self.activity.difficulty.clone(),
+                player_pl: self.activity.player.power,
+                enemy_type: self.enemy.type_,
+                enemy_health: self.enemy.health,
+                champion: self.enemy.champion.clone(),
+                boss_encounter: self.enemy.encounter.clone(),
+            },
+        }
+    }
+
+    //goes through the same checks as the setters, nothing changes unless the whole
+    //state is valid
+    pub fn import_state(&mut self, _state: SavedState) -> Result<(), OracleError> {
+        let saved = _state.weapon;
+        let mut weapon = Weapon::try_generate_weapon(
+            saved.hash,
+            saved.weapon_type_id,
+            saved.intrinsic_hash,
+            0,
+            0,
+        )?;
+        weapon.ammo_type = saved.ammo_type;
+        weapon.damage_type = saved.damage_type;
+        //the saved perks include the intrinsic unless it was reset away
+        weapon.perks.clear();
+        for perk in saved.perks {
+            let (hash, enhanced) = enhanced_check(perk.hash);
+            weapon.try_add_perk(
+                Perk {
+                    stat_buffs: perk.stats.into_iter().collect(),
+                    enhanced,
+                    value: perk.value,
+                    hash,
+                    raw_hash: perk.hash,
+                },
+                false,
+            )?;
+        }
+        weapon.set_masterwork(saved.masterwork)?;
+        weapon.set_stats(
+            saved
+                .stats
+                .into_iter()
+                .map(|(hash, value)| (hash, Stat::from(value)))
+                .collect(),
+        );
+        saved.sockets.validate(&weapon.weapon_type)?;
+
+        let encounter = _state.encounter;
+        if !encounter.enemy_health.is_finite() || encounter.enemy_health < 0.0 {
+            return Err(OracleError::InvalidInput(
+                "Enemy health can't be negative".to_string(),
+            ));
+        }
+        encounter.boss_encounter.validate()?;
+        if let Some(champion) = &encounter.champion {
+            champion.validate()?;
+        }
+        let activity = Activity {
+            rpl: encounter.recommended_pl,
+            cap: encounter.override_cap,
+            difficulty: encounter.difficulty,
+            player: Player {
+                power: encounter.player_pl,
+                ..self.activity.player.clone()
+            },
+            ..self.activity.clone()
+        };
+        let enemy = Enemy {
+            health: encounter.enemy_health,
+            type_: encounter.enemy_type,
+            champion: encounter.champion,
+            encounter: encounter.boss_encounter,
+            ..self.enemy.clone()
+        };
+
+        self.weapon = weapon;
+        self.sockets = saved.sockets;
+        self.activity = activity;
+        self.enemy = enemy;
+        Ok(())
+    }
+}
@@ -3,9 +3,46 @@ use std::collections::HashMap;
 use num_traits::{Float, Zero};
 
 use crate::{
+    clear_champion,
     d2_enums::{AmmoType, DamageType, StatHashes, WeaponType},
-    perks::{map_perks, Perk, Perks},
-    weapons::{Stat, Weapon},
+    enemies::{BossEncounter, ChampionProfile, ChampionType, Enemy, EnemyType},
+    error::OracleError,
+    logging::LogLevel,
+    perks::{
+        buff_timeline::{BuffTimeline, BuffTrigger},
+        get_firing_modifier,
+        lib::{CalculationInput, KillTracker},
+        map_perks,
+        perk_coverage::{get_perk_coverage, CoverageIssue},
+        perk_options_handler::{
+            get_perk_options, ModifierCategory, PerkAvailability, PerkOptionData,
+        },
+        perk_slots::{get_hash_slot, get_perk_slot, PerkSlot},
+        Perk, Perks,
+    },
+    set_champion, set_champion_timings,
+    types::{
+        js_types::JsChampionType,
+        rs_types::{ReserveFormula, WeaponFlags},
+    },
+    weapons::{
+        add_clear_calc::{AddWave, MAX_ADD_WAVE_SIZE},
+        breakpoints::{Breakpoint, BreakpointOutput},
+        formula_db::{
+            add_formula_snapshot, list_formula_snapshots, remove_formula_snapshot,
+            reset_formula_db, set_formula_db, use_formula_snapshot, FormulaDatabase, FormulaSource,
+            BUILT_IN_SNAPSHOT,
+        },
+        formula_diff::diff_formula_snapshots,
+        masterwork::MasterworkData,
+        roll_optimizer::{
+            optimize_rolls, RollConstraint, RollMetric, RollObjective, MAX_OPTIMIZER_ROLLS,
+        },
+        sockets::{Plug, SocketLayout},
+        stat_breakdown::StatSource,
+        stat_curves::{get_stat_curve_point, get_stat_curves, CurveScalars},
+        Stat, Weapon,
+    },
     PERS_DATA,
 };
 
@@ -345,5 +382,1388 @@ fn test_phase_mag() {
     let p_data = precision.calc_firing_data(Some(precision.static_calc_input()), None, true);
     assert_eq!(lw_data.burst_delay, p_data.burst_delay);
     assert_eq!(lw_data.pvp_crit_mult, p_data.pvp_crit_mult);
-    assert_eq!(f64::trunc(lw_data.pvp_impact_damage*1000.0)/1000.0, p_data.pvp_impact_damage);
+    assert_eq!(
+        f64::trunc(lw_data.pvp_impact_damage * 1000.0) / 1000.0,
+        p_data.pvp_impact_damage
+    );
+}
+
+#[test]
+fn test_champion_barrier_stun() {
+    setup_pulse();
+    map_perks();
+    let mut weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    let champion = ChampionProfile::new(ChampionType::BARRIER, 600.0);
+
+    let no_mod = weapon.calc_champion_ttk(&Enemy::default(), &champion, 1.0, 1.0);
+    assert!(!no_mod.can_stun);
+    assert_eq!(no_mod.stuns, 0);
+    assert!(
+        no_mod.time_immune > 0.0,
+        "time immune: {}",
+        no_mod.time_immune
+    );
+
+    weapon.add_perk(Perk {
+        hash: Perks::AntiBarrierMod.into(),
+        raw_hash: Perks::AntiBarrierMod.into(),
+        ..Default::default()
+    });
+    let with_mod = weapon.calc_champion_ttk(&Enemy::default(), &champion, 1.0, 1.0);
+    assert!(with_mod.can_stun);
+    assert!(with_mod.killed);
+    assert_eq!(with_mod.stuns, 3);
+    assert!(
+        with_mod.time_to_kill < no_mod.time_to_kill,
+        "with mod: {}, without: {}",
+        with_mod.time_to_kill,
+        no_mod.time_to_kill
+    );
+}
+
+#[test]
+fn test_champion_profile_validation() {
+    setup_pulse();
+    map_perks();
+    let champion = ChampionProfile::new(ChampionType::BARRIER, 600.0);
+    assert!(champion.validate().is_ok());
+    let bad_profiles = [
+        ChampionProfile {
+            health: f64::NAN,
+            ..champion.clone()
+        },
+        ChampionProfile {
+            immune_thresholds: vec![0.75, f64::NAN],
+            ..champion.clone()
+        },
+        ChampionProfile {
+            immune_thresholds: vec![1.5],
+            ..champion.clone()
+        },
+        ChampionProfile {
+            stun_cooldown: f64::INFINITY,
+            ..champion.clone()
+        },
+        ChampionProfile {
+            regen_delay: -1.0,
+            ..champion.clone()
+        },
+    ];
+    for profile in bad_profiles {
+        assert!(profile.validate().is_err(), "{:?}", profile);
+    }
+
+    //radiant is an artifact buff, it doesn't stun anything on its own
+    let mut weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    weapon.add_perk(Perk {
+        hash: Perks::Radiant.into(),
+        raw_hash: Perks::Radiant.into(),
+        ..Default::default()
+    });
+    assert!(
+        !weapon
+            .calc_champion_ttk(&Enemy::default(), &champion, 1.0, 1.0)
+            .can_stun
+    );
+}
+
+#[test]
+fn test_boss_encounter_phases() {
+    setup_pulse();
+    let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    let mut boss = Enemy {
+        type_: EnemyType::BOSS,
+        ..Default::default()
+    };
+    let all_crits = weapon.calc_dps(boss.clone(), 1.0);
+
+    boss.encounter.precision_ratio = 0.0;
+    let no_crits = weapon.calc_dps(boss.clone(), 1.0);
+    assert!(
+        cmp_floats(all_crits.total_damage, no_crits.total_damage * 2.0),
+        "all crits: {}, no crits: {}",
+        all_crits.total_damage,
+        no_crits.total_damage
+    );
+
+    boss.encounter = BossEncounter {
+        dr_phases: vec![(0.0, f64::INFINITY, 0.5)],
+        immune_periods: vec![(0.0, 1.0)],
+        ..Default::default()
+    };
+    assert!(boss.encounter.validate().is_ok());
+    for bad in [
+        BossEncounter {
+            precision_ratio: f64::NAN,
+            ..Default::default()
+        },
+        BossEncounter {
+            dr_phases: vec![(0.0, 5.0, f64::NAN)],
+            ..Default::default()
+        },
+        BossEncounter {
+            dr_phases: vec![(0.0, 5.0, -0.5)],
+            ..Default::default()
+        },
+        BossEncounter {
+            crit_windows: vec![(5.0, 2.0)],
+            ..Default::default()
+        },
+        BossEncounter {
+            crit_windows: vec![(f64::NAN, 2.0)],
+            ..Default::default()
+        },
+        BossEncounter {
+            immune_periods: vec![(0.0, 3.0), (2.0, 4.0)],
+            ..Default::default()
+        },
+    ] {
+        assert!(bad.validate().is_err(), "{:?}", bad);
+    }
+    let phased = weapon.calc_dps(boss, 1.0);
+    let immune_hits = phased
+        .time_damage_data
+        .iter()
+        .filter(|(time, _)| *time < 1.0)
+        .count();
+    assert!(immune_hits > 0);
+    assert!(phased
+        .time_damage_data
+        .iter()
+        .all(|(time, dmg)| (*time < 1.0) == (*dmg == 0.0)));
+    assert!(phased.total_damage < all_crits.total_damage * 0.5);
+}
+
+#[test]
+fn test_add_clear_kill_perks() {
+    setup_pulse();
+    map_perks();
+    let mut weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    weapon.add_perk(Perk {
+        hash: Perks::Rampage.into(),
+        raw_hash: Perks::Rampage.into(),
+        value: 0,
+        ..Default::default()
+    });
+    let wave = AddWave {
+        count: 8,
+        health: 45.0,
+        spacing: 2.0,
+        enemy_type: EnemyType::MINOR,
+        precision_ratio: 1.0,
+    };
+    assert!(wave.validate().is_ok());
+    for bad_wave in [
+        AddWave {
+            count: MAX_ADD_WAVE_SIZE + 1,
+            ..wave.clone()
+        },
+        AddWave {
+            health: f64::NAN,
+            ..wave.clone()
+        },
+        AddWave {
+            health: -1.0,
+            ..wave.clone()
+        },
+        AddWave {
+            spacing: -2.0,
+            ..wave.clone()
+        },
+    ] {
+        assert!(bad_wave.validate().is_err(), "{:?}", bad_wave);
+    }
+    let response = weapon.calc_add_clear(&wave, 1.0);
+    assert!(response.cleared);
+    assert_eq!(response.total_kills, 8);
+    assert_eq!(response.splash_kills, 0);
+    assert_eq!(response.kills_per_mag.iter().sum::<u32>(), 8);
+    let rampage_uptime = response.perk_uptime[&Perks::Rampage.into()];
+    assert!(
+        rampage_uptime > 0.0 && rampage_uptime <= 1.0,
+        "rampage uptime: {}",
+        rampage_uptime
+    );
+
+    let weak_wave = AddWave {
+        health: 6.0,
+        ..wave.clone()
+    };
+    let unchained = weapon.calc_add_clear(&weak_wave, 1.0);
+    weapon.add_perk(Perk {
+        hash: Perks::ChainReaction.into(),
+        raw_hash: Perks::ChainReaction.into(),
+        ..Default::default()
+    });
+    let chained = weapon.calc_add_clear(&weak_wave, 1.0);
+    assert!(chained.cleared);
+    assert!(chained.splash_kills > 0);
+    assert!(
+        chained.shots_fired < unchained.shots_fired,
+        "chain reaction: {}, without: {}",
+        chained.shots_fired,
+        unchained.shots_fired
+    );
+
+    //incandescent ticks half a second after the kill, the gun gets there first
+    //on the closest adds and the first tick takes the rest
+    let mut weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    weapon.add_perk(Perk {
+        hash: Perks::Incandescent.into(),
+        raw_hash: Perks::Incandescent.into(),
+        ..Default::default()
+    });
+    let packed_wave = AddWave {
+        count: 12,
+        health: 1.0,
+        spacing: 0.5,
+        enemy_type: EnemyType::MINOR,
+        precision_ratio: 1.0,
+    };
+    let body_shots = weapon.calc_add_clear(
+        &AddWave {
+            precision_ratio: 0.0,
+            ..wave.clone()
+        },
+        1.0,
+    );
+    let crits = weapon.calc_add_clear(&wave, 1.0);
+    assert!(
+        body_shots.shots_fired > crits.shots_fired,
+        "body shots: {}, crits: {}",
+        body_shots.shots_fired,
+        crits.shots_fired
+    );
+    let scorched = weapon.calc_add_clear(&packed_wave, 1.0);
+    assert!(scorched.cleared);
+    assert_eq!(scorched.shots_fired, 8);
+    assert_eq!(scorched.splash_kills, 4);
+    assert!(
+        cmp_floats(scorched.time_to_clear, 0.5),
+        "time to clear: {}",
+        scorched.time_to_clear
+    );
+}
+
+#[test]
+fn test_dps_kill_events() {
+    setup_pulse();
+    map_perks();
+    let mut weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    weapon.add_perk(Perk {
+        hash: Perks::Rampage.into(),
+        raw_hash: Perks::Rampage.into(),
+        value: 0,
+        ..Default::default()
+    });
+    let mut enemy = Enemy {
+        type_: EnemyType::MINOR,
+        ..Default::default()
+    };
+    let no_kills = weapon.calc_dps(enemy.clone(), 1.0);
+    assert!(no_kills.kill_times.is_empty());
+
+    //dies to every third shot
+    enemy.health = no_kills.time_damage_data[0].1 * 2.5;
+    let kills = weapon.calc_dps(enemy, 1.0);
+    assert!(!kills.kill_times.is_empty());
+    assert!(
+        kills.total_damage > no_kills.total_damage,
+        "with kills: {}, without: {}",
+        kills.total_damage,
+        no_kills.total_damage
+    );
+}
+
+#[test]
+fn test_kill_inputs() {
+    setup_pulse();
+    map_perks();
+    let mut tracker = KillTracker::new(10.0);
+    assert!(tracker.time_since_last_kill(1.0).is_infinite());
+    tracker.queue_damage(0.5, 6.0);
+    tracker.queue_damage(1.0, 6.0);
+    tracker.queue_damage(2.0, 10.0);
+    assert_eq!(tracker.land_damage(1.5), 1);
+    assert!(cmp_floats(tracker.kills_total(), 1.0));
+    assert!(cmp_floats(tracker.time_since_last_kill(1.5), 0.5));
+    tracker.on_reload();
+    assert_eq!(tracker.land_damage(2.0), 1);
+    assert_eq!(tracker.kills_this_mag, 1);
+    assert!(cmp_floats(tracker.kills_total(), 2.0));
+
+    let mut weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    let cascade = Perk {
+        hash: Perks::CascadePoint.into(),
+        raw_hash: Perks::CascadePoint.into(),
+        value: 0,
+        ..Default::default()
+    };
+    //cascade point reads the time since the last kill when the sim tracks kills
+    let mut input = CalculationInput::construct_static(&weapon);
+    input.kill_events = true;
+    input.kills_total = 1.0;
+    input.time_since_last_kill = 1.0;
+    let scale = |input: &CalculationInput| {
+        get_firing_modifier(vec![cascade.clone()], input, false, &mut HashMap::new())
+            .burst_delay_scale
+    };
+    assert!(cmp_floats(scale(&input), 0.6));
+    input.time_since_last_kill = 3.0;
+    assert!(cmp_floats(scale(&input), 1.0));
+
+    weapon.add_perk(cascade);
+    let mut enemy = Enemy {
+        type_: EnemyType::MINOR,
+        ..Default::default()
+    };
+    let no_kills = weapon.calc_dps(enemy.clone(), 1.0);
+    enemy.health = no_kills.time_damage_data[0].1 * 2.5;
+    let kills = weapon.calc_dps(enemy, 1.0);
+    //shots after the first kill come out faster
+    let shot = 6;
+    assert!(
+        kills.time_damage_data[shot].0 < no_kills.time_damage_data[shot].0,
+        "with kills: {}, without: {}",
+        kills.time_damage_data[shot].0,
+        no_kills.time_damage_data[shot].0
+    );
+}
+
+#[test]
+fn test_kill_perk_values() {
+    setup_pulse();
+    map_perks();
+    let mut weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    weapon.add_perk(Perk {
+        hash: Perks::Rampage.into(),
+        raw_hash: Perks::Rampage.into(),
+        value: 0,
+        ..Default::default()
+    });
+    let enemy = Enemy::default();
+    let base_ttk = weapon.calc_ttk(0.0)[0].optimal_ttk.time_taken;
+    let base_hit = weapon.calc_dps(enemy.clone(), 1.0).time_damage_data[0].1;
+
+    //nothing dies in the ttk or a dps sim without health, the set stacks are used
+    weapon.change_perk_val(Perks::Rampage.into(), 3);
+    let ttk = weapon.calc_ttk(0.0)[0].optimal_ttk.time_taken;
+    let hit = weapon.calc_dps(enemy, 1.0).time_damage_data[0].1;
+    assert!(
+        cmp_floats_delta(base_ttk, 0.667, 0.001),
+        "ttk: {}",
+        base_ttk
+    );
+    assert!(cmp_floats_delta(ttk, 0.533, 0.001), "ttk: {}", ttk);
+    assert!(cmp_floats(base_hit, 22.0), "first hit: {}", base_hit);
+    assert!(cmp_floats_delta(hit, 29.28, 0.01), "first hit: {}", hit);
+}
+
+#[test]
+fn test_voltshot_procs() {
+    setup_pulse();
+    map_perks();
+    let base = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    let mut weapon = base.clone();
+    weapon.add_perk(Perk {
+        hash: Perks::Voltshot.into(),
+        raw_hash: Perks::Voltshot.into(),
+        value: 1,
+        ..Default::default()
+    });
+    //4 arcs of 40
+    let jolt = 160.0;
+
+    //without kills the fight starts charged and only the first shot jolts
+    let mut enemy = Enemy::default();
+    let no_kills = base.calc_dps(enemy.clone(), 1.0);
+    let charged = weapon.calc_dps(enemy.clone(), 1.0);
+    assert!(cmp_floats(
+        charged.total_damage - no_kills.total_damage,
+        jolt
+    ));
+
+    //every mag gets a kill, each reload after one charges the next shot only
+    enemy.health = no_kills.time_damage_data[0].1 * 2.5;
+    let kills = base.calc_dps(enemy.clone(), 1.0);
+    let jolted = weapon.calc_dps(enemy, 1.0);
+    let reloads = jolted.dps_per_mag.len() - 1;
+    assert_eq!(jolted.buff_windows.len(), reloads);
+    assert!(cmp_floats(
+        jolted.total_damage - kills.total_damage,
+        jolt * reloads as f64
+    ));
+    let shot_delay = weapon.firing_data.burst_delay;
+    assert!(jolted
+        .buff_windows
+        .iter()
+        .all(|window| window.start > 0.0 && cmp_floats(window.end - window.start, shot_delay)));
+}
+
+#[test]
+fn test_buff_timeline() {
+    let rampage = Perk {
+        hash: Perks::Rampage.into(),
+        raw_hash: Perks::Rampage.into(),
+        ..Default::default()
+    };
+    let mut timeline = BuffTimeline::new(&[rampage]);
+    for kill in [0.0, 1.0, 2.0, 3.0, 10.0] {
+        timeline.trigger(BuffTrigger::Kill, kill, 1);
+    }
+    let hash: u32 = Perks::Rampage.into();
+    assert_eq!(timeline.stacks(hash, 0.5), 1);
+    assert_eq!(timeline.stacks(hash, 3.5), 3);
+    assert_eq!(timeline.stacks(hash, 7.5), 0);
+    assert_eq!(timeline.stacks(hash, 10.5), 1);
+    assert!(cmp_floats(timeline.active_time(hash, 20.0), 11.0));
+
+    //kill clip only comes up after reloading with kills and ends on the next reload
+    setup_pulse();
+    map_perks();
+    let mut weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    weapon.add_perk(Perk {
+        hash: Perks::KillClip.into(),
+        raw_hash: Perks::KillClip.into(),
+        ..Default::default()
+    });
+    let mut enemy = Enemy {
+        type_: EnemyType::MINOR,
+        ..Default::default()
+    };
+    enemy.health = weapon.calc_dps(enemy.clone(), 1.0).time_damage_data[0].1 * 2.5;
+    let response = weapon.calc_dps(enemy, 1.0);
+    assert!(!response.buff_windows.is_empty());
+    assert!(response
+        .buff_windows
+        .iter()
+        .all(|window| window.start > 0.0 && window.end - window.start <= 4.0));
+}
+
+#[test]
+fn test_enhanced_perk_options() {
+    let base: u32 = Perks::Recombination.into();
+    let enhanced = 3335686050; //enhanced recombination
+    let options = get_perk_options(vec![base, enhanced]);
+    assert_eq!(options[&base].stacks, (0, 10));
+    assert_eq!(options[&enhanced].stacks, (0, 8));
+}
+
+#[test]
+fn test_perk_option_metadata() {
+    let rampage: u32 = Perks::Rampage.into();
+    let boss_spec: u32 = Perks::BossSpec.into();
+    let options = get_perk_options(vec![rampage, boss_spec]);
+
+    let rampage_options = &options[&rampage];
+    assert_eq!(rampage_options.label, "kills");
+    assert_eq!(rampage_options.default_value, 0);
+    assert_eq!(rampage_options.availability, PerkAvailability::BOTH);
+    assert_eq!(rampage_options.duration, Some(4.0));
+    assert_eq!(rampage_options.categories, vec![ModifierCategory::DAMAGE]);
+
+    let boss_spec_options = &options[&boss_spec];
+    assert_eq!(boss_spec_options.availability, PerkAvailability::PVE);
+    assert_eq!(boss_spec_options.duration, None);
+    assert!(!boss_spec_options.approximate);
+
+    let big_ones: u32 = Perks::BigOnesSpec.into();
+    let dexterity: u32 = Perks::DexterityMod.into();
+    let options = get_perk_options(vec![big_ones, dexterity]);
+    assert_eq!(options[&big_ones].availability, PerkAvailability::PVE);
+    assert_eq!(options[&dexterity].label, "mods");
+
+    let voltshot: u32 = Perks::Voltshot.into();
+    let voltshot_options = &get_perk_options(vec![voltshot])[&voltshot];
+    assert!(voltshot_options.approximate);
+    assert_eq!(voltshot_options.duration, Some(3.0));
+
+    let splash: Vec<u32> = [Perks::ChainReaction, Perks::Dragonfly, Perks::FireFly]
+        .into_iter()
+        .map(u32::from)
+        .collect();
+    let options = get_perk_options(splash.clone());
+    assert!(splash.iter().all(|perk| options[perk].approximate));
+
+    let adagio: u32 = Perks::Adagio.into();
+    let void_leech: u32 = Perks::VoidLeech.into();
+    let rat_pack: u32 = Perks::RatPack.into();
+    let options = get_perk_options(vec![adagio, void_leech, rat_pack]);
+    assert_eq!(options[&adagio].default_value, 1);
+    assert_eq!(options[&void_leech].availability, PerkAvailability::PVE);
+    assert_eq!(options[&rat_pack].default_value, 1);
+
+    let pvp_toggle = PerkOptionData::toggle().pvp_only().default_value(3);
+    assert_eq!(pvp_toggle.availability, PerkAvailability::PVP);
+    assert_eq!(pvp_toggle.default_value, 1);
+}
+
+#[test]
+fn test_perk_coverage() {
+    setup_pulse();
+    let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    let coverage = get_perk_coverage(&weapon.static_calc_input());
+    let rampage = coverage
+        .iter()
+        .find(|perk| perk.hash == u32::from(Perks::Rampage))
+        .unwrap();
+    assert!(rampage.reads_value);
+    assert!(rampage.issues.is_empty());
+    for perk in coverage.iter() {
+        assert!(
+            !perk.issues.contains(&CoverageIssue::STATIC_READS_VALUE),
+            "{} reads its value but has static options",
+            perk.perk
+        );
+        //built in perks are handled by the engine and never shown
+        if perk.hash != u32::from(Perks::BuiltIn) {
+            assert!(
+                !perk.issues.contains(&CoverageIssue::NO_OPTIONS),
+                "{} has modifiers but no options",
+                perk.perk
+            );
+        }
+    }
+}
+
+#[test]
+fn test_perk_conflicts() {
+    setup_pulse();
+    let mut weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    let perk = |_perk: Perks| Perk {
+        stat_buffs: HashMap::new(),
+        enhanced: false,
+        value: 0,
+        hash: _perk.into(),
+        raw_hash: _perk.into(),
+    };
+    assert_eq!(get_perk_slot(Perks::Rampage), PerkSlot::TRAIT);
+    assert_eq!(get_perk_slot(Perks::VeistStinger), PerkSlot::ORIGIN);
+
+    //shotgun only barrel on a pulse
+    assert!(weapon.try_add_perk(perk(Perks::FullChoke), true).is_err());
+
+    weapon.try_add_perk(perk(Perks::Rampage), false).unwrap();
+    weapon.try_add_perk(perk(Perks::KillClip), false).unwrap();
+    let err = weapon
+        .try_add_perk(perk(Perks::Outlaw), true)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Rampage") && err.contains("KillClip"));
+    //re-adding a perk just updates it
+    weapon.try_add_perk(perk(Perks::Rampage), false).unwrap();
+
+    weapon
+        .try_add_perk(perk(Perks::VeistStinger), false)
+        .unwrap();
+    assert!(weapon
+        .try_add_perk(perk(Perks::HakkeBreach), false)
+        .is_err());
+    weapon.try_add_perk(perk(Perks::HakkeBreach), true).unwrap();
+    let ids = weapon.list_perk_ids();
+    assert!(ids.contains(&Perks::HakkeBreach.into()));
+    assert!(!ids.contains(&Perks::VeistStinger.into()));
+
+    //buffs and armor mods stack with anything
+    weapon
+        .try_add_perk(perk(Perks::WellOfRadiance), false)
+        .unwrap();
+    weapon
+        .try_add_perk(perk(Perks::DexterityMod), false)
+        .unwrap();
+    weapon.try_add_perk(perk(Perks::ReserveMod), false).unwrap();
+
+    //revision zero's catalyst reuses outlaw under its own hash, it shouldn't take
+    //one of the two trait columns
+    setup_pulse();
+    let catalyst_hash = 1266037487;
+    assert_eq!(get_hash_slot(catalyst_hash), PerkSlot::EXOTIC);
+    PERS_DATA.with(|perm_data| {
+        let weapon = &mut perm_data.borrow_mut().weapon;
+        weapon.try_add_perk(perk(Perks::RatPack), false).unwrap();
+        weapon
+            .try_add_perk(
+                Perk {
+                    hash: catalyst_hash,
+                    raw_hash: catalyst_hash,
+                    ..perk(Perks::Outlaw)
+                },
+                false,
+            )
+            .unwrap();
+        weapon.try_add_perk(perk(Perks::Rampage), false).unwrap();
+        weapon.try_add_perk(perk(Perks::KillClip), false).unwrap();
+        assert!(weapon.try_add_perk(perk(Perks::Outlaw), false).is_err());
+    });
+    let exported = crate::export_state().unwrap();
+    crate::import_state(exported.clone()).unwrap();
+    assert_eq!(crate::export_state().unwrap(), exported);
+    let ids = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.list_perk_ids());
+    assert!(ids.contains(&catalyst_hash));
+    assert!(ids.contains(&Perks::RatPack.into()));
+}
+
+#[test]
+fn test_socket_rolls() {
+    setup_pulse();
+    let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    let plug = |_hash: u32, _range: i32| Plug {
+        hash: _hash,
+        stats: HashMap::from([(StatHashes::RANGE.into(), _range)]),
+    };
+    let traits = vec![
+        plug(Perks::Rampage.into(), 0),
+        plug(Perks::KillClip.into(), 0),
+    ];
+    let mut layout = SocketLayout {
+        barrel: vec![plug(1, 10), plug(2, 0)],
+        trait_1: traits.clone(),
+        trait_2: traits,
+        masterwork: vec![plug(3, 10)],
+        ..Default::default()
+    };
+    assert!(layout.validate(&weapon.weapon_type).is_ok());
+    assert_eq!(layout.roll_count(), 8);
+
+    let rolls = weapon.roll_stats(&layout).unwrap();
+    //rampage + rampage and killclip + killclip are skipped, so is the swapped pair
+    assert_eq!(rolls.len(), 2);
+    let best_range = rolls
+        .iter()
+        .map(|roll| roll.stats[&StatHashes::RANGE.into()])
+        .max()
+        .unwrap();
+    assert_eq!(best_range, 70);
+
+    //frenzy is only in the second column so its pairs have nothing to dedupe against
+    layout.trait_2.push(plug(Perks::Frenzy.into(), 0));
+    assert_eq!(weapon.roll_stats(&layout).unwrap().len(), 6);
+
+    layout.origin = vec![plug(Perks::Rampage.into(), 0)];
+    assert!(layout.validate(&weapon.weapon_type).is_err());
+    layout.origin = vec![];
+    layout.barrel.push(plug(Perks::FullChoke.into(), 0));
+    assert!(layout.validate(&weapon.weapon_type).is_err());
+
+    let many: Vec<Plug> = (10..60).map(|hash| plug(hash, 0)).collect();
+    let layout = SocketLayout {
+        barrel: many.clone(),
+        magazine: many.clone(),
+        origin: many,
+        ..Default::default()
+    };
+    assert!(layout.roll_count() > MAX_OPTIMIZER_ROLLS);
+    assert!(weapon.roll_stats(&layout).is_err());
+}
+
+#[test]
+fn test_roll_optimizer() {
+    setup_pulse();
+    let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    let plug = |_hash: u32, _range: i32, _reload: i32| Plug {
+        hash: _hash,
+        stats: HashMap::from([
+            (StatHashes::RANGE.into(), _range),
+            (StatHashes::RELOAD.into(), _reload),
+        ]),
+    };
+    let layout = SocketLayout {
+        barrel: vec![plug(1, 20, -20), plug(2, 10, 0), plug(3, 0, 0)],
+        magazine: vec![plug(4, 0, 10), plug(5, 5, -10)],
+        ..Default::default()
+    };
+    let enemy = Enemy::default();
+
+    let mut objective = RollObjective::new(RollMetric::RANGE);
+    let ranked = optimize_rolls(&weapon, &layout, &objective, &enemy, 1.0).unwrap();
+    assert_eq!(ranked.len(), 6);
+    assert_eq!(ranked[0].roll.plugs[0].hash, 1);
+    assert_eq!(ranked[0].roll.plugs[1].hash, 5);
+    assert!(ranked.windows(2).all(|pair| pair[0].score >= pair[1].score));
+
+    //no reload penalty allowed, the best range roll is now the second barrel
+    let base_reload = weapon.calc_roll_metric(RollMetric::RELOAD_TIME, &objective, &enemy, 1.0);
+    objective.constraints.push(RollConstraint {
+        metric: RollMetric::RELOAD_TIME,
+        min: None,
+        max: Some(base_reload),
+    });
+    objective.top = 1;
+    let ranked = optimize_rolls(&weapon, &layout, &objective, &enemy, 1.0).unwrap();
+    assert_eq!(ranked.len(), 1);
+    assert_eq!(ranked[0].roll.plugs[0].hash, 2);
+    assert!(ranked[0].metrics[&RollMetric::RELOAD_TIME] <= base_reload);
+}
+
+#[test]
+fn test_masterwork_stats() {
+    setup_pulse();
+    let mut weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    let range: u32 = StatHashes::RANGE.into();
+    let zoom: u32 = StatHashes::ZOOM.into();
+    let handling: u32 = StatHashes::HANDLING.into();
+
+    let mut masterwork = MasterworkData {
+        stat: range,
+        tier: 10,
+        adept: true,
+        ..Default::default()
+    };
+    weapon.set_masterwork(masterwork.clone()).unwrap();
+    let stats = weapon.get_stats();
+    //the adept bonus skips the masterwork stat itself
+    assert_eq!(stats[&range].masterwork_value, 10);
+    assert_eq!(stats[&range].val(), 60);
+    assert_eq!(stats[&handling].masterwork_value, 3);
+    //hidden stats don't get the adept bonus
+    assert_eq!(stats[&zoom].masterwork_value, 0);
+
+    masterwork.tier = 11;
+    assert!(weapon.set_masterwork(masterwork.clone()).is_err());
+
+    //crafted weapons masterwork through the enhanced intrinsic once they're leveled
+    let mut crafted = MasterworkData {
+        stat: range,
+        crafted_level: Some(5),
+        enhanced_intrinsic: true,
+        intrinsic_stats: HashMap::from([(handling, 5)]),
+        ..Default::default()
+    };
+    weapon.set_masterwork(crafted.clone()).unwrap();
+    assert_eq!(weapon.get_stats()[&range].masterwork_value, 0);
+    crafted.crafted_level = Some(20);
+    weapon.set_masterwork(crafted).unwrap();
+    let stats = weapon.get_stats();
+    assert_eq!(stats[&range].masterwork_value, 10);
+    assert_eq!(stats[&handling].masterwork_value, 5);
+}
+
+#[test]
+fn test_stat_breakdown() {
+    setup_pulse();
+    let mut weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    let range: u32 = StatHashes::RANGE.into();
+    weapon.add_perk(Perk {
+        stat_buffs: HashMap::from([(range, 40)]),
+        enhanced: false,
+        value: 0,
+        hash: 1,
+        raw_hash: 1,
+    });
+    weapon.add_perk(Perk {
+        stat_buffs: HashMap::from([(range, 20)]),
+        enhanced: false,
+        value: 0,
+        hash: 2,
+        raw_hash: 2,
+    });
+    let breakdown = weapon.stat_breakdown();
+    let range_breakdown = breakdown.iter().find(|stat| stat.stat == range).unwrap();
+    assert_eq!(range_breakdown.total, 110);
+    assert_eq!(range_breakdown.value, 100);
+    assert_eq!(range_breakdown.overflow, 10);
+    let parts: Vec<i32> = range_breakdown
+        .contributions
+        .iter()
+        .filter(|contribution| contribution.source == StatSource::PART)
+        .map(|contribution| contribution.value)
+        .collect();
+    assert_eq!(parts, vec![40, 20]);
+    for stat in breakdown.iter() {
+        let sum: i32 = stat.contributions.iter().map(|c| c.value).sum();
+        assert_eq!(sum, stat.total, "{}", stat.name);
+    }
+    //hidden stats show up even when the weapon doesn't have them set
+    for stat in [
+        StatHashes::AIM_ASSIST,
+        StatHashes::RECOIL_DIR,
+        StatHashes::AIRBORNE,
+    ] {
+        let hidden = breakdown
+            .iter()
+            .find(|breakdown| breakdown.stat == u32::from(stat))
+            .unwrap();
+        assert!(hidden.hidden);
+    }
+}
+
+#[test]
+fn test_stat_curves() {
+    setup_pulse();
+    let weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    let scalars = CurveScalars::default();
+    let curves = get_stat_curves(13, 69420, 0, &scalars).unwrap();
+    assert_eq!(curves.len(), 101);
+    assert!(curves[100].reload_time < curves[0].reload_time);
+    assert!(curves[100].ads_falloff_start > curves[0].ads_falloff_start);
+
+    //matches the loaded weapon at the same stats
+    let point = &curves[50];
+    assert!(cmp_floats(
+        point.reload_time,
+        weapon.calc_reload_time(None, None, false).reload_time
+    ));
+    assert!(cmp_floats(
+        point.ready_time,
+        weapon.calc_handling_times(None, None, false).ready_time
+    ));
+    let range = weapon.calc_range_falloff(None, None, false);
+    assert!(cmp_floats(point.ads_falloff_start, range.ads_falloff_start));
+
+    let fast = CurveScalars {
+        reload: 0.5,
+        ..Default::default()
+    };
+    let fast_point = get_stat_curve_point(13, 69420, 0, 50, &fast).unwrap();
+    assert!(cmp_floats(fast_point.reload_time, point.reload_time * 0.5));
+    assert!(get_stat_curves(13, 1, 0, &scalars).is_none());
+}
+
+#[test]
+fn test_breakpoints() {
+    setup_pulse();
+    map_perks();
+    let mut weapon = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+    let magazine: u32 = StatHashes::MAGAZINE.into();
+    let breakpoints = weapon.stat_breakpoints(magazine).unwrap();
+    let mag_breakpoints: Vec<_> = breakpoints
+        .iter()
+        .filter(|breakpoint| breakpoint.output == BreakpointOutput::MAG_SIZE)
+        .collect();
+    assert!(!mag_breakpoints.is_empty());
+    for breakpoint in mag_breakpoints.iter() {
+        weapon
+            .stats
+            .insert(magazine, Stat::from(breakpoint.value - 1));
+        assert_eq!(
+            weapon.calc_ammo_sizes(None, None, false).mag_size,
+            breakpoint.from
+        );
+        weapon.stats.insert(magazine, Stat::from(breakpoint.value));
+        assert_eq!(
+            weapon.calc_ammo_sizes(None, None, false).mag_size,
+            breakpoint.to
+        );
+    }
+    assert!(weapon.stat_breakpoints(StatHashes::RANGE.into()).is_err());
+
+    assert!(weapon.perk_breakpoints(Perks::Rampage.into(), 10).is_err());
+    weapon.add_perk(Perk {
+        stat_buffs: HashMap::new(),
+        enhanced: false,
+        value: 0,
+        hash: Perks::Rampage.into(),
+        raw_hash: Perks::Rampage.into(),
+    });
+    let rampage_breakpoints = weapon.perk_breakpoints(Perks::Rampage.into(), 0).unwrap();
+    assert!(!rampage_breakpoints.is_empty());
+    //more damage can only take shots away
+    for breakpoint in rampage_breakpoints.iter() {
+        assert!(breakpoint.to < breakpoint.from);
+        assert!(breakpoint.value > 0 && breakpoint.value <= 3);
+    }
+    //the first stack is already worth two bodyshots
+    assert!(rampage_breakpoints.contains(&Breakpoint {
+        output: BreakpointOutput::BODY_SHOTS,
+        value: 1,
+        from: 22,
+        to: 20,
+    }));
+}
+
+#[test]
+fn test_formula_db_overlay() {
+    let json = r#"{
+        "Pulse Rifle": {
+            "69420": {"name": "test_weapon", "cat": "fast", "subFam": "hard", "magProf": "big"},
+            "cat": {"fast": {
+                "range": {"vpp_start": 0.1, "offset_start": 20, "vpp_end": 0.1, "offset_end": 30, "floor_percent": 0.5},
+                "reload": {"vpp": -0.01, "offset": 2.0, "ammo_percent": 0.5},
+                "handling": {"ready": {"vpp": 0, "offset": 0.4}, "stow": {"vpp": 0, "offset": 0.4}, "ads": {"vpp": 0, "offset": 0.2}},
+                "combatant_scalars": {"minor": 1, "elite": 1, "miniboss": 1, "champion": 1, "boss": 1, "vehicle": 1}
+            }},
+            "subFam": {"hard": {"damage": 100, "crit_mult": 25.5, "pve_damage": 50, "pve_crit_mult": 0, "burst_delay": 30, "inner_burst_delay": 0}},
+            "magProf": {"big": {"mag": {"vpp": 0, "offset": 50}}}
+        }
+    }"#;
+    let (db, warnings) = FormulaDatabase::from_json(json, "test", 1234, false).unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(db.info.source, FormulaSource::OVERLAY);
+    set_formula_db(db);
+    let weapon = Weapon::generate_weapon(0, 13, 69420, 1, 3373582085).unwrap();
+    assert!(cmp_floats(weapon.firing_data.damage, 100.0));
+    assert!(cmp_floats(weapon.firing_data.crit_mult, 2.0));
+    assert_eq!(weapon.firing_data.timestamp, 1234);
+    //everything not in the file still comes from the built in tables
+    assert!(Weapon::generate_weapon(0, 9, 1294026524, 1, 3373582085).is_some());
+
+    let (db, _) = FormulaDatabase::from_json(json, "test", 1234, true).unwrap();
+    set_formula_db(db);
+    assert!(Weapon::generate_weapon(0, 13, 69420, 1, 3373582085).is_some());
+    assert!(Weapon::generate_weapon(0, 9, 1294026524, 1, 3373582085).is_none());
+
+    let broken = json.replace(r#""cat": "fast""#, r#""cat": "slow""#);
+    let err = FormulaDatabase::from_json(&broken, "test", 1234, false)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Pulse Rifle/69420/cat"));
+
+    reset_formula_db();
+    let weapon = Weapon::generate_weapon(0, 13, 69420, 1, 3373582085).unwrap();
+    assert!(!cmp_floats(weapon.firing_data.damage, 100.0));
+}
+
+#[test]
+fn test_weapon_overrides() {
+    //exotic exceptions come from the overrides block, not code
+    let forerunner = Weapon::generate_weapon(0, 17, 2984682260, 2, 3373582085).unwrap();
+    assert_eq!(forerunner.ads_multiplier, Some(2.0));
+    assert!(!forerunner.flags.flat_charge_delay);
+    let vex = Weapon::generate_weapon(4289226715, 11, 3610750208, 1, 3373582085).unwrap();
+    assert_eq!(vex.ads_multiplier, Some(1.7));
+    assert!(vex.flags.flat_charge_delay);
+    let adaptive = Weapon::generate_weapon(0, 9, 1294026524, 1, 3373582085).unwrap();
+    assert_eq!(adaptive.ads_multiplier, None);
+
+    //a weapon hash override only touches that weapon
+    let json = r#"{
+        "Hand Cannon": {
+            "cat": {}, "subFam": {}, "magProf": {},
+            "overrides": {"69420": {"firing": {"damage": 100, "crit_mult": 2, "pve_damage": 50, "pve_crit_mult": 0, "burst_delay": 30, "inner_burst_delay": 0}, "reserveId": 7}},
+            "reserves": {"7": {"formula": 42}}
+        }
+    }"#;
+    let (db, _) = FormulaDatabase::from_json(json, "test", 1234, false).unwrap();
+    set_formula_db(db);
+    let weapon = Weapon::generate_weapon(69420, 9, 1294026524, 1, 3373582085).unwrap();
+    assert!(cmp_floats(weapon.firing_data.damage, 100.0));
+    assert_eq!(weapon.ammo_formula.reserve_id, 7);
+    assert!(cmp_floats(
+        weapon.reserve_formula.solve_at(12.0, 50, 50),
+        42.0
+    ));
+    assert!(cmp_floats(
+        weapon.handling_formula.ready.vpp,
+        adaptive.handling_formula.ready.vpp
+    ));
+    let weapon = Weapon::generate_weapon(0, 9, 1294026524, 1, 3373582085).unwrap();
+    assert!(cmp_floats(
+        weapon.firing_data.damage,
+        adaptive.firing_data.damage
+    ));
+    reset_formula_db();
+}
+
+#[test]
+fn test_reserve_formulas() {
+    //leviathan's breath only gets more reserves past 80 inventory
+    let leviathan = Weapon::generate_weapon(0, 31, 1699724249, 3, 3373582085).unwrap();
+    assert!(cmp_floats(
+        leviathan.reserve_formula.solve_at(1.0, 50, 79),
+        8.0
+    ));
+    assert!(cmp_floats(
+        leviathan.reserve_formula.solve_at(1.0, 50, 80),
+        15.0
+    ));
+    //primaries have no curve
+    let adaptive = Weapon::generate_weapon(0, 9, 1294026524, 1, 3373582085).unwrap();
+    assert!(adaptive.reserve_formula.nodes.is_empty());
+    assert!(cmp_floats(
+        adaptive.reserve_formula.solve_at(12.0, 50, 50),
+        9999.0
+    ));
+    //legendary linear fusions used to point at reserve id 221, which never had a curve
+    //and fell through to primary reserves, they now use the linear fusion curve
+    let linear = Weapon::generate_weapon(0, 22, 2057203855, 3, 3373582085).unwrap();
+    assert_eq!(linear.ammo_formula.reserve_id, 2201);
+    assert!(cmp_floats(
+        linear.reserve_formula.solve_at(5.0, 50, 50),
+        19.6
+    ));
+
+    let json = r#"{
+        "Shotgun": {
+            "69420": {"name": "test_weapon", "cat": "fast", "subFam": "hard", "magProf": "big"},
+            "cat": {"fast": {
+                "range": {"vpp_start": 0.1, "offset_start": 20, "vpp_end": 0.1, "offset_end": 30, "floor_percent": 0.5},
+                "reload": {"vpp": -0.01, "offset": 2.0, "ammo_percent": 0.5},
+                "handling": {"ready": {"vpp": 0, "offset": 0.4}, "stow": {"vpp": 0, "offset": 0.4}, "ads": {"vpp": 0, "offset": 0.2}},
+                "combatant_scalars": {"minor": 1, "elite": 1, "miniboss": 1, "champion": 1, "boss": 1, "vehicle": 1}
+            }},
+            "subFam": {"hard": {"damage": 100, "crit_mult": 2, "pve_damage": 50, "pve_crit_mult": 0, "burst_delay": 30, "inner_burst_delay": 0}},
+            "magProf": {"big": {"mag": {"vpp": 0, "offset": 5}, "reserve_id": 12}},
+            "reserves": {"12": {"name": "test", "formula": {"piecewise": "magSize", "pieces": [
+                {"max": 4, "value": {"input": "invStat", "vpp": 0.1, "offset": 10}}
+            ], "default": {"sum": [{"input": "invStat", "vpp": 0.2, "offset": 10}, {"product": [2, {"input": "magStat", "vpp": 0.1}]}]}}}}
+        }
+    }"#;
+    let (db, warnings) = FormulaDatabase::from_json(json, "test", 1234, false).unwrap();
+    assert!(warnings.is_empty());
+    set_formula_db(db);
+    let weapon = Weapon::generate_weapon(0, 7, 69420, 2, 3373582085).unwrap();
+    assert!(cmp_floats(
+        weapon.reserve_formula.solve_at(4.0, 50, 50),
+        15.0
+    ));
+    assert!(cmp_floats(
+        weapon.reserve_formula.solve_at(5.0, 50, 50),
+        30.0
+    ));
+    //no stats set, so mag and inventory stat are 0
+    assert_eq!(weapon.calc_ammo_sizes(None, None, false).reserve_size, 10);
+    reset_formula_db();
+
+    //a reserve id that doesn't resolve fails validation
+    let broken = json.replace(r#""reserve_id": 12"#, r#""reserve_id": 13"#);
+    let err = FormulaDatabase::from_json(&broken, "test", 1234, false)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Shotgun/magProf/big/reserve_id"));
+}
+
+#[test]
+fn test_formula_snapshot_diff() {
+    //a patch that only touches adaptive hand cannon damage
+    let json = r#"{
+        "Hand Cannon": {
+            "cat": {}, "subFam": {}, "magProf": {},
+            "overrides": {"1294026524": {"firing": {"damage": 100, "crit_mult": 2, "pve_damage": 50, "pve_crit_mult": 0, "burst_delay": 30, "inner_burst_delay": 0}}}
+        }
+    }"#;
+    let (db, _) = FormulaDatabase::from_json(json, "patch", 1234, false).unwrap();
+    add_formula_snapshot("patch", db).unwrap();
+    assert!(add_formula_snapshot(BUILT_IN_SNAPSHOT, FormulaDatabase::built_in()).is_err());
+    let snapshots = list_formula_snapshots();
+    assert_eq!(snapshots[0].name, BUILT_IN_SNAPSHOT);
+    assert!(snapshots
+        .iter()
+        .any(|snapshot| snapshot.name == "patch" && !snapshot.compiled_in));
+
+    let mut weapon = Weapon::generate_weapon(0, 9, 1294026524, 1, 3373582085).unwrap();
+    weapon
+        .stats
+        .insert(StatHashes::RELOAD.into(), Stat::from(50));
+    let enemy = Enemy::default();
+    let diff = diff_formula_snapshots(&weapon, BUILT_IN_SNAPSHOT, "patch", &enemy, 1.0).unwrap();
+    assert!(diff.before_supported && diff.after_supported);
+    assert_eq!(diff.after.timestamp, 1234);
+    let damage = diff
+        .changes
+        .iter()
+        .find(|change| change.output == "pvpImpactDamage")
+        .unwrap();
+    assert!(!cmp_floats(damage.before.unwrap(), damage.after.unwrap()));
+    assert!(diff
+        .changes
+        .iter()
+        .any(|change| change.output.starts_with("ttk.")));
+    //the patch doesn't touch reload or handling
+    assert!(!diff
+        .changes
+        .iter()
+        .any(|change| change.output == "reloadTime"));
+    assert!(!diff
+        .changes
+        .iter()
+        .any(|change| change.output == "readyTime"));
+
+    let same = diff_formula_snapshots(&weapon, BUILT_IN_SNAPSHOT, "active", &enemy, 1.0).unwrap();
+    assert!(same.changes.is_empty());
+    assert!(diff_formula_snapshots(&weapon, BUILT_IN_SNAPSHOT, "nope", &enemy, 1.0).is_err());
+
+    //a snapshot without data for the weapon reports it instead of zeros
+    let pulse_only = r#"{"Pulse Rifle": {"cat": {}, "subFam": {}, "magProf": {}}}"#;
+    let (db, _) = FormulaDatabase::from_json(pulse_only, "pulse", 1234, true).unwrap();
+    add_formula_snapshot("pulse", db).unwrap();
+    let missing = diff_formula_snapshots(&weapon, BUILT_IN_SNAPSHOT, "pulse", &enemy, 1.0).unwrap();
+    assert!(!missing.after_supported);
+    assert!(missing.changes.iter().all(|change| change.after.is_none()));
+
+    use_formula_snapshot("patch").unwrap();
+    let patched = Weapon::generate_weapon(0, 9, 1294026524, 1, 3373582085).unwrap();
+    assert!(cmp_floats(patched.firing_data.damage, 100.0));
+    reset_formula_db();
+    assert!(remove_formula_snapshot("patch"));
+    assert!(remove_formula_snapshot("pulse"));
+    assert!(!remove_formula_snapshot("patch"));
+}
+
+#[test]
+fn test_compiled_formula_snapshot() {
+    //build_resources/formula_snapshots/2025-04-26.json is weapon_formulas.json as of that
+    //manifest, once the built in formulas change this diff should list them
+    let snapshot = list_formula_snapshots()
+        .into_iter()
+        .find(|snapshot| snapshot.name == "2025-04-26")
+        .unwrap();
+    assert!(snapshot.compiled_in);
+    assert_eq!(snapshot.error, None);
+
+    let enemy = Enemy::default();
+    for (path, _) in crate::database::DATA_POINTERS.iter() {
+        let weapon = Weapon::generate_weapon(path.1, path.0 as u8, path.1, 1, 3373582085).unwrap();
+        let diff =
+            diff_formula_snapshots(&weapon, "2025-04-26", BUILT_IN_SNAPSHOT, &enemy, 1.0).unwrap();
+        assert_eq!(diff.before_supported, diff.after_supported, "{:?}", path);
+        assert!(diff.changes.is_empty(), "{:?}: {:?}", path, diff.changes);
+    }
+}
+
+#[test]
+fn test_formula_diff_every_weapon() {
+    //every built in weapon has to get through formula_outputs, including the ones that
+    //still point at placeholder firing data
+    let enemy = Enemy::default();
+    for (path, _) in crate::database::DATA_POINTERS.iter() {
+        let weapon = Weapon::generate_weapon(path.1, path.0 as u8, path.1, 1, 3373582085).unwrap();
+        let diff =
+            diff_formula_snapshots(&weapon, BUILT_IN_SNAPSHOT, BUILT_IN_SNAPSHOT, &enemy, 1.0)
+                .unwrap();
+        assert!(diff.changes.is_empty(), "{:?}: {:?}", path, diff.changes);
+        let outputs = weapon.formula_outputs(&enemy, 1.0);
+        assert_eq!(
+            outputs.iter().any(|(name, _)| name == "dps"),
+            weapon.firing_data.burst_size > 0,
+            "{:?}",
+            path
+        );
+    }
+}
+
+#[test]
+fn test_oracle_errors() {
+    let err = LogLevel::try_from(7).unwrap_err();
+    assert_eq!(err.code(), "INVALID_INPUT");
+    assert_eq!(LogLevel::try_from(3), Ok(LogLevel::Debug));
+
+    let weapon = Weapon::generate_weapon(0, 9, 1294026524, 1, 3373582085).unwrap();
+    let err = weapon
+        .stat_breakpoints(StatHashes::RELOAD.into())
+        .unwrap_err();
+    assert_eq!(err.code(), "INVALID_INPUT");
+
+    let err = OracleError::UnknownWeapon {
+        weapon_type_id: 9,
+        intrinsic_hash: 1,
+        hash: 2,
+    };
+    assert_eq!(err.code(), "UNKNOWN_WEAPON");
+    assert!(err.to_string().contains("intrinsic: 1"));
+    let err = OracleError::MissingStat(StatHashes::CHARGE_TIME.into());
+    assert_eq!(err.code(), "MISSING_STAT");
+    assert!(err.to_string().contains("CHARGE_TIME"));
+
+    //a bow without draw time is reported instead of panicking in the built-in perk
+    let mut bow = Weapon {
+        weapon_type: WeaponType::BOW,
+        ..Weapon::default()
+    };
+    map_perks();
+    bow.add_perk(Perk {
+        stat_buffs: HashMap::new(),
+        enhanced: false,
+        value: 0,
+        hash: Perks::BuiltIn.into(),
+        raw_hash: Perks::BuiltIn.into(),
+    });
+    assert_eq!(
+        bow.check_firing_stats(),
+        Err(OracleError::MissingStat(StatHashes::DRAW_TIME.into()))
+    );
+    bow.calc_firing_data(Some(bow.static_calc_input()), None, false);
+    bow.stats
+        .insert(StatHashes::DRAW_TIME.into(), Stat::from(50));
+    bow.firing_data.burst_size = 1;
+    assert!(bow.check_firing_stats().is_ok());
+
+    //placeholder firing data is an error instead of a divide by zero in the dps sim
+    let salvations_grip = Weapon::generate_weapon(0, 23, 1315870387, 3, 3373582085).unwrap();
+    assert_eq!(salvations_grip.firing_data.burst_size, 0);
+    assert_eq!(
+        salvations_grip.check_firing_stats().unwrap_err().code(),
+        "DATA"
+    );
+
+    //NaN gets past plain comparisons, the champion setters have to reject it
+    set_champion(JsChampionType::BARRIER, 600.0).unwrap();
+    let err = set_champion_timings(0.04, 3.0, vec![0.5, f64::NAN], 8.0, 4.0, 0.0, 1.0).unwrap_err();
+    assert_eq!(err.code(), "INVALID_INPUT");
+    assert!(set_champion(JsChampionType::BARRIER, f64::NAN).is_err());
+    let champion = PERS_DATA.with(|perm_data| perm_data.borrow().enemy.champion.clone());
+    assert_eq!(
+        champion.unwrap().immune_thresholds,
+        ChampionProfile::new(ChampionType::BARRIER, 600.0).immune_thresholds
+    );
+    clear_champion();
+}
+
+#[test]
+fn test_set_weapon_errors() {
+    setup_pulse();
+    let err = crate::set_weapon(0, 99, 69420, 1, 3373582085).unwrap_err();
+    assert_eq!(err.code(), "INVALID_INPUT");
+    let err = crate::set_weapon(1234, 13, 1234, 1, 3373582085).unwrap_err();
+    assert_eq!(
+        err,
+        OracleError::UnknownWeapon {
+            weapon_type_id: 13,
+            intrinsic_hash: 1234,
+            hash: 1234,
+        }
+    );
+    //a failed set keeps the weapon that was there
+    PERS_DATA.with(|perm_data| assert_eq!(perm_data.borrow().weapon.intrinsic_hash, 69420));
+
+    assert!(crate::has_weapon_data(13, 69420, None));
+    assert!(crate::has_weapon_data(9, 0, Some(1294026524)));
+    assert!(!crate::has_weapon_data(13, 1234, None));
+    assert!(!crate::has_weapon_data(99, 69420, None));
+    crate::set_weapon(0, 13, 69420, 1, 3373582085).unwrap();
+}
+
+#[test]
+fn test_set_weapon_clears_overrides() {
+    //forerunner has an ads override and a special ammo reserve curve
+    crate::set_weapon(0, 17, 2984682260, 2, 3373582085).unwrap();
+    let masterwork = MasterworkData {
+        stat: StatHashes::RANGE.into(),
+        tier: 10,
+        ..Default::default()
+    };
+    let mut forerunner = PERS_DATA.with(|perm_data| {
+        let mut perm_data = perm_data.borrow_mut();
+        perm_data.weapon.set_masterwork(masterwork).unwrap();
+        perm_data.weapon.clone()
+    });
+    assert_eq!(forerunner.ads_multiplier, Some(2.0));
+    assert!(!forerunner.reserve_formula.nodes.is_empty());
+
+    crate::set_weapon(0, 9, 1294026524, 1, 3373582085).unwrap();
+    PERS_DATA.with(|perm_data| {
+        let weapon = &perm_data.borrow().weapon;
+        assert_eq!(weapon.ads_multiplier, None);
+        assert_eq!(weapon.flags, WeaponFlags::default());
+        assert_eq!(weapon.masterwork, MasterworkData::default());
+        assert!(weapon.reserve_formula.nodes.is_empty());
+    });
+
+    forerunner.reset();
+    assert_eq!(forerunner.ads_multiplier, None);
+    assert_eq!(forerunner.flags, WeaponFlags::default());
+    assert_eq!(forerunner.masterwork, MasterworkData::default());
+    assert_eq!(forerunner.reserve_formula, ReserveFormula::default());
+}
+
+#[test]
+fn test_state_round_trip() {
+    setup_pulse();
+    let range: u32 = StatHashes::RANGE.into();
+    PERS_DATA.with(|perm_data| {
+        let mut perm_data = perm_data.borrow_mut();
+        let rampage = Perk {
+            stat_buffs: HashMap::from([(range, 5)]),
+            enhanced: false,
+            value: 2,
+            hash: Perks::Rampage.into(),
+            raw_hash: Perks::Rampage.into(),
+        };
+        perm_data.weapon.try_add_perk(rampage, false).unwrap();
+        perm_data
+            .weapon
+            .set_masterwork(MasterworkData {
+                stat: range,
+                tier: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        perm_data.enemy.health = 5000.0;
+        perm_data.enemy.type_ = EnemyType::BOSS;
+        perm_data.enemy.champion = Some(ChampionProfile::new(ChampionType::BARRIER, 20000.0));
+        perm_data.enemy.encounter.precision_ratio = 0.5;
+        perm_data.activity.rpl = 1900;
+    });
+    let exported = crate::export_state().unwrap();
+    let before = PERS_DATA.with(|perm_data| perm_data.borrow().weapon.clone());
+
+    setup_pulse();
+    PERS_DATA.with(|perm_data| perm_data.borrow_mut().enemy = Enemy::default());
+    crate::import_state(exported.clone()).unwrap();
+    assert_eq!(crate::export_state().unwrap(), exported);
+    PERS_DATA.with(|perm_data| {
+        let perm_data = perm_data.borrow();
+        let weapon = &perm_data.weapon;
+        assert_eq!(weapon.perk_value_map, before.perk_value_map);
+        assert_eq!(weapon.stats[&range].val(), before.stats[&range].val());
+        assert_eq!(
+            weapon.stats[&range].perk_val(),
+            before.stats[&range].perk_val()
+        );
+        assert_eq!(weapon.masterwork, before.masterwork);
+        assert_eq!(perm_data.enemy.type_, EnemyType::BOSS);
+        assert!(cmp_floats(perm_data.enemy.encounter.precision_ratio, 0.5));
+        assert_eq!(perm_data.activity.rpl, 1900);
+    });
+
+    //bad states are refused without touching what's there
+    let newer = exported.replace(r#""version":1"#, r#""version":2"#);
+    assert!(crate::import_state(newer)
+        .unwrap_err()
+        .to_string()
+        .contains("version 2"));
+    let unknown = exported.replace(r#""weaponTypeId":13"#, r#""weaponTypeId":99"#);
+    assert!(crate::import_state(unknown).is_err());
+    assert!(crate::import_state("{}".to_string()).is_err());
+    let bad_threshold = exported.replace("[0.75,0.5,0.25]", "[0.75,1.5]");
+    assert!(crate::import_state(bad_threshold)
+        .unwrap_err()
+        .to_string()
+        .contains("Immune thresholds"));
+    let bad_stun = exported.replace(r#""stunDuration":4.0"#, r#""stunDuration":-1.0"#);
+    assert!(crate::import_state(bad_stun)
+        .unwrap_err()
+        .to_string()
+        .contains("stun duration"));
+    //json can't carry NaN, states built in code can
+    let mut nan_health = crate::state::SavedState::from_json(&exported).unwrap();
+    nan_health.encounter.enemy_health = f64::NAN;
+    assert!(PERS_DATA
+        .with(|perm_data| perm_data.borrow_mut().import_state(nan_health))
+        .is_err());
+    assert!(crate::set_enemy_health(f64::NAN).is_err());
+    assert_eq!(crate::export_state().unwrap(), exported);
+}
+
+#[test]
+fn test_state_fixture() {
+    //hand written so the format can't drift without breaking this
+    let fixture = r#"{
+        "version": 1,
+        "weapon": {
+            "hash": 0,
+            "weaponTypeId": 9,
+            "intrinsicHash": 1294026524,
+            "ammoType": "PRIMARY",
+            "damageType": "KINETIC",
+            "stats": {"4188031367": 50, "943549884": 50},
+            "perks": [{"hash": 1294026524}, {"hash": 3425386926, "value": 1}]
+        },
+        "encounter": {
+            "recommendedPl": 1600,
+            "overrideCap": 100,
+            "difficulty": "MASTER",
+            "playerPl": 1810,
+            "enemyType": "MINOR",
+            "enemyHealth": 0.0
+        }
+    }"#;
+    crate::import_state(fixture.to_string()).unwrap();
+    PERS_DATA.with(|perm_data| {
+        let perm_data = perm_data.borrow();
+        assert_eq!(perm_data.weapon.weapon_type, WeaponType::HANDCANNON);
+        assert_eq!(perm_data.weapon.perk_value_map[&Perks::Rampage.into()], 1);
+        assert_eq!(perm_data.weapon.stats[&StatHashes::RELOAD.into()].val(), 50);
+        assert_eq!(perm_data.enemy.type_, EnemyType::MINOR);
+        assert!(perm_data.enemy.champion.is_none());
+    });
 }
@@ -5,8 +5,10 @@ use std::{
 
 use crate::{
     activity::damage_calc::DifficultyOptions,
-    enemies::EnemyType,
-    perks::Perk,
+    enemies::{ChampionType, EnemyType},
+    error::OracleError,
+    perks::{buff_timeline::BuffWindow, Perk},
+    to_js,
     types::rs_types::StatQuadraticFormula,
     weapons::{
         ttk_calc::{BodyKillData, OptimalKillData, ResillienceSummary},
@@ -131,6 +133,10 @@ pub struct JsDpsResponse {
     pub total_time: f64,
     #[wasm_bindgen(js_name = "totalShots", readonly)]
     pub total_shots: i32,
+    #[wasm_bindgen(skip)]
+    pub kill_times: Vec<f64>,
+    #[wasm_bindgen(skip)]
+    pub buff_windows: Vec<BuffWindow>,
 }
 #[wasm_bindgen(js_class = "DpsResponse")]
 impl JsDpsResponse {
@@ -143,13 +149,23 @@ impl JsDpsResponse {
     }
     ///Returns a list of tuples of time and damage
     #[wasm_bindgen(getter, js_name = "timeDamageData")]
-    pub fn time_damage_data(&self) -> JsValue {
-        serde_wasm_bindgen::to_value(&self.time_damage_data).unwrap()
+    pub fn time_damage_data(&self) -> Result<JsValue, OracleError> {
+        to_js(&self.time_damage_data, "time damage data")
     }
     ///Returns a list of dps values for each magazine
     #[wasm_bindgen(getter, js_name = "dpsPerMag")]
-    pub fn dps_per_mag(&self) -> JsValue {
-        serde_wasm_bindgen::to_value(&self.dps_per_mag).unwrap()
+    pub fn dps_per_mag(&self) -> Result<JsValue, OracleError> {
+        to_js(&self.dps_per_mag, "dps per mag")
+    }
+    ///Returns the time of every kill, empty if the enemy has no health set
+    #[wasm_bindgen(getter, js_name = "killTimes")]
+    pub fn kill_times(&self) -> Result<JsValue, OracleError> {
+        to_js(&self.kill_times, "kill times")
+    }
+    ///Returns every stretch of time a timed perk was active and its stacks
+    #[wasm_bindgen(getter, js_name = "buffTimeline")]
+    pub fn buff_timeline(&self) -> Result<JsValue, OracleError> {
+        to_js(&self.buff_windows, "buff timeline")
     }
 }
 impl fmt::Display for JsDpsResponse {
@@ -165,6 +181,8 @@ impl From<DpsResponse> for JsDpsResponse {
             total_damage: dps.total_damage,
             total_time: dps.total_time,
             total_shots: dps.total_shots,
+            kill_times: dps.kill_times,
+            buff_windows: dps.buff_windows,
         }
     }
 }
@@ -286,6 +304,9 @@ pub struct JsStat {
     #[wasm_bindgen(js_name = "traitValue")]
     #[serde(rename = "traitValue")]
     pub trait_value: i32,
+    #[wasm_bindgen(js_name = "masterworkValue")]
+    #[serde(rename = "masterworkValue")]
+    pub masterwork_value: i32,
 }
 
 impl fmt::Display for JsStat {
@@ -300,6 +321,7 @@ impl From<Stat> for JsStat {
             base_value: stat.base_value,
             part_value: stat.part_value,
             trait_value: stat.perk_value,
+            masterwork_value: stat.masterwork_value,
         }
     }
 }
@@ -315,6 +337,13 @@ pub struct JsMetaData {
     pub api_commit: &'static str,
     #[wasm_bindgen(js_name = "apiGitBranch", readonly)]
     pub api_branch: &'static str,
+    //"BUILT_IN", "OVERLAY" or "REPLACE"
+    #[wasm_bindgen(js_name = "formulaSource", readonly, getter_with_clone)]
+    pub formula_source: String,
+    #[wasm_bindgen(js_name = "formulaVersion", readonly, getter_with_clone)]
+    pub formula_version: String,
+    #[wasm_bindgen(js_name = "formulaTimestamp", readonly)]
+    pub formula_timestamp: u32,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -383,3 +412,20 @@ impl From<JsEnemyType> for EnemyType {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[wasm_bindgen(js_name = "ChampionType")]
+pub enum JsChampionType {
+    BARRIER,
+    OVERLOAD,
+    UNSTOPPABLE,
+}
+impl From<JsChampionType> for ChampionType {
+    fn from(val: JsChampionType) -> Self {
+        match val {
+            JsChampionType::BARRIER => ChampionType::BARRIER,
+            JsChampionType::OVERLOAD => ChampionType::OVERLOAD,
+            JsChampionType::UNSTOPPABLE => ChampionType::UNSTOPPABLE,
+        }
+    }
+}
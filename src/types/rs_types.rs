@@ -2,7 +2,10 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::{enemies::EnemyType, perks::clamp};
+use crate::{
+    enemies::EnemyType,
+    perks::{buff_timeline::BuffWindow, clamp},
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct DataPointers {
@@ -17,6 +20,21 @@ pub struct DataPointers {
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct WeaponPath(pub u32, pub u32);
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct WeaponFlags {
+    pub flat_charge_delay: bool,
+}
+
+//firing and range point into the formula tables like DataPointers
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct WeaponOverride {
+    pub firing: Option<usize>,
+    pub range: Option<usize>,
+    pub ads_mult: Option<f64>,
+    pub reserve_id: Option<u32>,
+    pub flags: WeaponFlags,
+}
+
 #[derive(Debug, Clone, Default, Copy, Serialize)]
 pub struct FiringData {
     pub damage: f64,
@@ -80,23 +98,9 @@ pub struct RangeFormula {
     pub timestamp: u64,
 }
 
-//even if just linear use this
-#[derive(Debug, Clone, Copy, Default, Serialize)]
-pub struct StatQuadraticFormula {
-    pub evpp: f64,
-    pub vpp: f64,
-    pub offset: f64,
-}
-impl StatQuadraticFormula {
-    pub fn solve_at(&self, _x: f64) -> f64 {
-        self.evpp * _x * _x + self.vpp * _x + self.offset
-    }
-
-    pub fn solve_at_i(&self, x: i32) -> f64 {
-        let x = x.clamp(0, 100) as f64;
-        self.evpp * x * x + self.vpp * x + self.offset
-    }
-}
+//even if just linear use this, shared with oracle_data so formula validation solves
+//curves the same way the engine does
+pub use oracle_data::formulas::StatQuadraticFormula;
 
 #[derive(Debug, Clone, Copy, Default, Serialize)]
 pub struct ReloadFormula {
@@ -121,6 +125,15 @@ pub struct AmmoFormula {
     pub timestamp: u64,
 }
 
+//flattened reserve curves, shared with oracle_data which builds and validates them
+pub use oracle_data::reserves::{ReserveInput, ReserveNode};
+
+//no nodes means primary ammo
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReserveFormula {
+    pub nodes: Vec<ReserveNode>,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct RangeResponse {
     pub hip_falloff_start: f64,
@@ -167,6 +180,8 @@ pub struct DpsResponse {
     pub total_damage: f64,
     pub total_time: f64,
     pub total_shots: i32,
+    pub kill_times: Vec<f64>,
+    pub buff_windows: Vec<BuffWindow>,
 }
 impl DpsResponse {
     pub fn apply_rpl(&mut self, rpl: f64) {
@@ -0,0 +1,423 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::dps_calc::ExtraDamageBuffInfo;
+use super::Weapon;
+use crate::d2_enums::AmmoType;
+use crate::enemies::EnemyType;
+use crate::error::OracleError;
+use crate::perks::buff_timeline::{BuffTimeline, BuffTrigger};
+use crate::perks::lib::{time_since_last_kill, CalculationInput, ExtraDamageResponse};
+use crate::perks::{get_dmg_modifier, get_extra_damage, get_firing_modifier};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddWave {
+    pub count: u32,
+    pub health: f64,
+    //meters between adds, they're treated as standing in a line
+    pub spacing: f64,
+    pub enemy_type: EnemyType,
+    //fraction of shots that land as crits
+    pub precision_ratio: f64,
+}
+
+//every add gets its own health entry, past this the sim gets too slow for the browser
+pub const MAX_ADD_WAVE_SIZE: u32 = 1000;
+
+impl AddWave {
+    pub fn validate(&self) -> Result<(), OracleError> {
+        if self.count == 0 || self.count > MAX_ADD_WAVE_SIZE {
+            return Err(OracleError::InvalidInput(format!(
+                "Add count must be between 1 and {}, got {}",
+                MAX_ADD_WAVE_SIZE, self.count
+            )));
+        }
+        if !self.health.is_finite() || self.health <= 0.0 {
+            return Err(OracleError::InvalidInput(format!(
+                "Add health must be positive, got {}",
+                self.health
+            )));
+        }
+        if !self.spacing.is_finite() || self.spacing < 0.0 {
+            return Err(OracleError::InvalidInput(format!(
+                "Add spacing must be a non-negative number, got {}",
+                self.spacing
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AddClearResponse {
+    #[serde(rename = "killsPerMag")]
+    pub kills_per_mag: Vec<u32>,
+    pub cleared: bool,
+    #[serde(rename = "timeToClear")]
+    pub time_to_clear: f64,
+    #[serde(rename = "totalKills")]
+    pub total_kills: u32,
+    #[serde(rename = "splashKills")]
+    pub splash_kills: u32,
+    #[serde(rename = "shotsFired")]
+    pub shots_fired: i32,
+    //raw perk hash to the fraction of the clear the perk was active for
+    #[serde(rename = "perkUptime")]
+    pub perk_uptime: HashMap<u32, f64>,
+}
+
+//start, end and kills of each magazine
+type MagWindow = (f64, f64, u32);
+
+//extra damage from one perk proc, (delay, damage) of each hit
+#[derive(Debug, Clone)]
+struct ExtraHits {
+    radius: f64,
+    hits: Vec<(f64, f64)>,
+}
+impl ExtraHits {
+    fn new(_entry: &ExtraDamageResponse, _buffs: &ExtraDamageBuffInfo) -> Self {
+        let dmg = _entry.additive_damage * _buffs.get_buff_amount(_entry);
+        let hits = if _entry.hit_at_same_time && !_entry.is_dot {
+            vec![(
+                _entry.time_for_additive_damage,
+                dmg * _entry.times_to_hit as f64,
+            )]
+        } else {
+            //dots tick once per interval after they're applied, other hits start right away
+            let first = if _entry.is_dot { 1 } else { 0 };
+            (0.._entry.times_to_hit)
+                .map(|i| (_entry.time_for_additive_damage * (i + first) as f64, dmg))
+                .collect()
+        };
+        ExtraHits {
+            radius: _entry.splash_radius,
+            hits,
+        }
+    }
+}
+
+//a splash hit that lands after the shot or kill that caused it
+struct PendingHit {
+    time: f64,
+    center: usize,
+    radius: f64,
+    dmg: f64,
+}
+
+struct WaveState {
+    health: Vec<f64>,
+    spacing: f64,
+    kill_times: Vec<f64>,
+    splash_kills: u32,
+    pending: Vec<PendingHit>,
+    //on kill effects from the latest shot
+    on_kill: Vec<ExtraHits>,
+}
+impl WaveState {
+    fn target(&self) -> Option<usize> {
+        self.health.iter().position(|h| *h > 0.0)
+    }
+    fn alive_near(&self, _center: usize, _radius: f64) -> Vec<usize> {
+        (0..self.health.len())
+            .filter(|i| self.health[*i] > 0.0)
+            .filter(|i| (*i as f64 - _center as f64).abs() * self.spacing <= _radius)
+            .collect()
+    }
+    //returns the adds that died from this damage
+    fn damage(&mut self, _idx: usize, _dmg: f64) -> Option<usize> {
+        if self.health[_idx] <= 0.0 {
+            return None;
+        }
+        self.health[_idx] -= _dmg;
+        (self.health[_idx] <= 0.0).then_some(_idx)
+    }
+    fn queue(&mut self, _center: usize, _time: f64, _extra: &ExtraHits) {
+        for (delay, dmg) in _extra.hits.iter() {
+            self.pending.push(PendingHit {
+                time: _time + delay,
+                center: _center,
+                radius: _extra.radius,
+                dmg: *dmg,
+            });
+        }
+    }
+    //on kill effects start from where the add died
+    fn kill(&mut self, _idx: usize, _time: f64, _from_splash: bool) {
+        self.kill_times.push(_time);
+        if _from_splash {
+            self.splash_kills += 1;
+        }
+        for extra in self.on_kill.clone() {
+            self.queue(_idx, _time, &extra);
+        }
+    }
+    //lands queued hits up to _time in order, kills from them can queue more hits
+    fn land_hits(&mut self, _time: f64) -> u32 {
+        let mut kills = 0;
+        while let Some(next) = self
+            .pending
+            .iter()
+            .enumerate()
+            .filter(|(_, hit)| hit.time <= _time)
+            .min_by(|a, b| a.1.time.total_cmp(&b.1.time))
+            .map(|(i, _)| i)
+        {
+            let hit = self.pending.remove(next);
+            for idx in self.alive_near(hit.center, hit.radius) {
+                if let Some(dead) = self.damage(idx, hit.dmg) {
+                    self.kill(dead, hit.time, true);
+                    kills += 1;
+                }
+            }
+        }
+        kills
+    }
+}
+
+//kills only get to the timeline once they've landed, in the order they happened
+fn trigger_kills(_timeline: &mut BuffTimeline, _kill_times: &[f64], _triggered: &mut usize) {
+    for kill in _kill_times[*_triggered..].iter() {
+        _timeline.trigger(BuffTrigger::Kill, *kill, 1);
+    }
+    *_triggered = _kill_times.len();
+}
+
+pub fn calc_add_clear(_weapon: &Weapon, _wave: &AddWave, _pl_dmg_mult: f64) -> AddClearResponse {
+    let weapon = _weapon;
+    let perks = weapon.list_perks();
+    let stats = weapon.stats.clone();
+    let perk_value_map = weapon.perk_value_map_update();
+    let firing_settings = weapon.firing_data;
+
+    let tmp_dmg_prof = weapon.get_damage_profile(true);
+    let impact_dmg = tmp_dmg_prof.impact_dmg;
+    let explosion_dmg = tmp_dmg_prof.explosion_dmg;
+    let crit_mult = tmp_dmg_prof.crit_mult;
+    let combatant_mult = weapon.damage_mods.get_mod(&_wave.enemy_type);
+
+    let burst_size = firing_settings.burst_size as f64;
+    let base_mag = weapon.calc_ammo_sizes(None, None, false).mag_size;
+    let maximum_shots = if weapon.ammo_type == AmmoType::PRIMARY {
+        (base_mag * 10).max(200)
+    } else {
+        base_mag * 8 + 20
+    };
+
+    let mut pers_calc_data: HashMap<String, f64> = HashMap::new();
+    let mut reserve = weapon
+        .calc_ammo_sizes(
+            Some(weapon.static_calc_input()),
+            Some(&mut pers_calc_data),
+            false,
+        )
+        .reserve_size;
+
+    let mut wave = WaveState {
+        health: vec![_wave.health; _wave.count as usize],
+        spacing: _wave.spacing,
+        kill_times: Vec::new(),
+        splash_kills: 0,
+        pending: Vec::new(),
+        on_kill: Vec::new(),
+    };
+
+    let mut total_time = 0.0_f64;
+    let mut total_shots_fired = 0_i32;
+    let mut total_shots_hit = 0_i32;
+    let mut num_reloads = 0_i32;
+    let mut mags: Vec<MagWindow> = Vec::new();
+    let mut buff_timeline = BuffTimeline::new(&perks);
+    let mut kills_triggered = 0;
+
+    while reserve > 0 && wave.target().is_some() && total_shots_fired <= maximum_shots {
+        let mut shots_this_mag = 0;
+        let mut kills_this_mag = 0;
+        let start_time = total_time;
+
+        let mag_calc_input = weapon.sparse_calc_input(total_shots_fired, total_time);
+        let mut mag = weapon
+            .calc_ammo_sizes(Some(mag_calc_input), Some(&mut pers_calc_data), false)
+            .mag_size
+            .min(reserve);
+        let handling_calc_input = weapon.sparse_calc_input(total_shots_fired, total_time);
+        let handling_data =
+            weapon.calc_handling_times(Some(handling_calc_input), Some(&mut pers_calc_data), false);
+
+        while mag > 0 {
+            //hits that landed since the last shot, reloads included
+            kills_this_mag += wave.land_hits(total_time);
+            trigger_kills(&mut buff_timeline, &wave.kill_times, &mut kills_triggered);
+            let target = match wave.target() {
+                Some(target) => target,
+                None => break,
+            };
+            let calc_input = CalculationInput {
+                intrinsic_hash: weapon.intrinsic_hash,
+                curr_firing_data: &firing_settings,
+                base_crit_mult: crit_mult,
+                base_mag: base_mag as f64,
+                curr_mag: mag as f64,
+                ammo_type: &weapon.ammo_type,
+                weapon_type: &weapon.weapon_type,
+                stats: &stats,
+                perk_value_map: &perk_value_map,
+                enemy_type: &_wave.enemy_type,
+                shots_fired_this_mag: shots_this_mag as f64,
+                total_shots_fired: total_shots_fired as f64,
+                total_shots_hit: total_shots_hit as f64,
+                reserves_left: reserve as f64,
+                time_total: total_time,
+                time_this_mag: (total_time - start_time),
+                damage_type: &weapon.damage_type,
+                handling_data,
+                num_reloads: num_reloads as f64,
+                has_overshield: false,
+                kill_events: true,
+                kills_total: wave.kill_times.len() as f64,
+                kills_this_mag: kills_this_mag as f64,
+                time_since_last_kill: time_since_last_kill(&wave.kill_times, total_time),
+                buffs: Some(&buff_timeline),
+            };
+            let dmg_mods = get_dmg_modifier(perks.clone(), &calc_input, false, &mut pers_calc_data);
+            let firing_mods =
+                get_firing_modifier(perks.clone(), &calc_input, false, &mut pers_calc_data);
+            let extra_dmg_responses =
+                get_extra_damage(perks.clone(), &calc_input, false, &mut pers_calc_data);
+
+            //blended the same way as a boss encounter's precision ratio
+            let shot_crit_mult =
+                1.0 + (crit_mult * dmg_mods.crit_scale - 1.0) * _wave.precision_ratio;
+            let buffs = ExtraDamageBuffInfo {
+                pl_buff: _pl_dmg_mult,
+                impact_buff: dmg_mods.impact_dmg_scale,
+                explosive_buff: dmg_mods.explosive_dmg_scale,
+                pve_buff: weapon.damage_mods.pve,
+                crit_buff: shot_crit_mult,
+                combatant_buff: combatant_mult,
+            };
+            let on_shot: Vec<ExtraHits> = extra_dmg_responses
+                .iter()
+                .filter(|entry| !entry.on_kill)
+                .map(|entry| ExtraHits::new(entry, &buffs))
+                .collect();
+            wave.on_kill = extra_dmg_responses
+                .iter()
+                .filter(|entry| entry.on_kill)
+                .map(|entry| ExtraHits::new(entry, &buffs))
+                .collect();
+
+            let dmg = ((impact_dmg * dmg_mods.impact_dmg_scale) * shot_crit_mult
+                + (explosion_dmg * dmg_mods.explosive_dmg_scale))
+                * _pl_dmg_mult
+                * combatant_mult
+                * weapon.damage_mods.pve;
+
+            let shot_burst_delay = (firing_settings.burst_delay + firing_mods.burst_delay_add)
+                * firing_mods.burst_delay_scale;
+            let shot_inner_burst_delay =
+                firing_settings.inner_burst_delay * firing_mods.inner_burst_scale;
+            let shot_burst_size = burst_size + firing_mods.burst_size_add;
+
+            let hits = if (firing_settings.one_ammo && burst_size > 1.0)
+                || firing_settings.inner_burst_delay == 0.0
+            {
+                shot_burst_size as i32
+            } else {
+                1
+            };
+
+            //DAMAGE///////////////////////
+            for _ in 0..hits {
+                if let Some(target) = wave.target() {
+                    if let Some(dead) = wave.damage(target, dmg) {
+                        wave.kill(dead, total_time, false);
+                        kills_this_mag += 1;
+                    }
+                }
+            }
+            for extra in on_shot.iter() {
+                wave.queue(target, total_time, extra);
+            }
+            kills_this_mag += wave.land_hits(total_time);
+            total_shots_hit += hits;
+            trigger_kills(&mut buff_timeline, &wave.kill_times, &mut kills_triggered);
+            ///////////////////////////////
+
+            let spec_delay = if firing_settings.one_ammo && burst_size > 1.0 {
+                shot_burst_delay + shot_inner_burst_delay * (shot_burst_size - 1.0)
+            } else if shots_this_mag % burst_size as i32 == 0 {
+                shot_burst_delay
+            } else {
+                shot_inner_burst_delay
+            };
+            total_shots_fired += 1;
+            shots_this_mag += 1;
+            mag -= 1;
+            if wave.target().is_some() {
+                total_time += spec_delay;
+            }
+            buff_timeline.shot(total_time);
+        }
+
+        reserve -= shots_this_mag;
+        mags.push((start_time, total_time, kills_this_mag));
+
+        //RELOAD///////////////////////
+        if wave.target().is_some() && reserve > 0 {
+            let mut reload_input = weapon.sparse_calc_input(total_shots_fired, total_time);
+            reload_input.kill_events = true;
+            reload_input.kills_total = wave.kill_times.len() as f64;
+            reload_input.kills_this_mag = kills_this_mag as f64;
+            reload_input.time_since_last_kill = time_since_last_kill(&wave.kill_times, total_time);
+            reload_input.buffs = Some(&buff_timeline);
+            let reload_time = weapon
+                .calc_reload_time(Some(reload_input), Some(&mut pers_calc_data), false)
+                .reload_time;
+            buff_timeline.reload(total_time);
+            total_time += reload_time;
+            buff_timeline.trigger(BuffTrigger::KillReload, total_time, kills_this_mag);
+            num_reloads += 1;
+        }
+        ///////////////////////////////
+    }
+
+    //dots and delayed hits still land after the last shot
+    let late_kills = wave.land_hits(f64::INFINITY);
+    trigger_kills(&mut buff_timeline, &wave.kill_times, &mut kills_triggered);
+    if let Some(mag) = mags.last_mut() {
+        mag.2 += late_kills;
+    }
+
+    let cleared = wave.target().is_none();
+    let end_time = wave.kill_times.last().copied().unwrap_or(total_time);
+    let mut perk_uptime = HashMap::new();
+    for perk in perks
+        .iter()
+        .filter(|perk| buff_timeline.has_rule(perk.hash))
+    {
+        let uptime = if end_time > 0.0 {
+            buff_timeline.active_time(perk.hash, end_time) / end_time
+        } else {
+            0.0
+        };
+        perk_uptime.insert(perk.raw_hash, uptime);
+    }
+
+    AddClearResponse {
+        kills_per_mag: mags.iter().map(|mag| mag.2).collect(),
+        cleared,
+        time_to_clear: if cleared { end_time } else { f64::INFINITY },
+        total_kills: wave.kill_times.len() as u32,
+        splash_kills: wave.splash_kills,
+        shots_fired: total_shots_fired,
+        perk_uptime,
+    }
+}
+
+impl Weapon {
+    pub fn calc_add_clear(&self, _wave: &AddWave, _pl_dmg_mult: f64) -> AddClearResponse {
+        calc_add_clear(self, _wave, _pl_dmg_mult)
+    }
+}
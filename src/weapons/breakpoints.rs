@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::{Stat, Weapon};
+use crate::d2_enums::StatHashes;
+use crate::error::OracleError;
+use crate::perks::{
+    get_magazine_modifier, get_reserve_modifier, perk_options_handler::get_perk_options,
+};
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum BreakpointOutput {
+    MAG_SIZE,
+    RESERVES,
+    //bodyshots only
+    BODY_SHOTS,
+    //headshots + bodyshots of the fastest kill
+    OPTIMAL_SHOTS,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Breakpoint {
+    pub output: BreakpointOutput,
+    //stat value or perk value the new output starts at
+    pub value: i32,
+    pub from: i32,
+    pub to: i32,
+}
+
+//walks _values in order and records every change of each output
+fn find_breakpoints(
+    _values: impl Iterator<Item = i32>,
+    mut _outputs: impl FnMut(i32) -> Vec<(BreakpointOutput, i32)>,
+) -> Vec<Breakpoint> {
+    let mut last: HashMap<BreakpointOutput, i32> = HashMap::new();
+    let mut breakpoints = Vec::new();
+    for value in _values {
+        for (output, result) in _outputs(value) {
+            match last.insert(output, result) {
+                Some(prev) if prev != result => breakpoints.push(Breakpoint {
+                    output,
+                    value,
+                    from: prev,
+                    to: result,
+                }),
+                _ => {}
+            }
+        }
+    }
+    breakpoints
+}
+
+impl Weapon {
+    //stat values from 0 to 100 where the mag size or reserves change, only
+    //magazine and inventory size drive integer outputs
+    pub fn stat_breakpoints(&self, _stat: u32) -> Result<Vec<Breakpoint>, OracleError> {
+        let stat_value =
+            |hash: StatHashes| self.stats.get(&hash.into()).unwrap_or(&Stat::new()).val();
+        let mut cached_data = HashMap::new();
+        let calc_input = self.static_calc_input();
+        let mag_modifiers =
+            get_magazine_modifier(self.list_perks(), &calc_input, false, &mut cached_data);
+        let inv_modifiers =
+            get_reserve_modifier(self.list_perks(), &calc_input, false, &mut cached_data);
+        let ammo_at = |mag_stat: i32, inv_stat: i32| {
+            let ammo = self.calc_ammo_sizes_at(
+                mag_stat,
+                mag_modifiers.clone(),
+                inv_stat,
+                inv_modifiers.clone(),
+            );
+            vec![
+                (BreakpointOutput::MAG_SIZE, ammo.mag_size),
+                (BreakpointOutput::RESERVES, ammo.reserve_size),
+            ]
+        };
+        match StatHashes::from(_stat) {
+            StatHashes::MAGAZINE => {
+                let inv_stat = stat_value(StatHashes::INVENTORY_SIZE);
+                Ok(find_breakpoints(0..=100, |value| ammo_at(value, inv_stat)))
+            }
+            StatHashes::INVENTORY_SIZE => {
+                let mag_stat = stat_value(StatHashes::MAGAZINE);
+                Ok(find_breakpoints(0..=100, |value| ammo_at(mag_stat, value))
+                    .into_iter()
+                    .filter(|breakpoint| breakpoint.output == BreakpointOutput::RESERVES)
+                    .collect())
+            }
+            stat => Err(OracleError::InvalidInput(format!(
+                "{:?} doesn't change any integer outputs",
+                stat
+            ))),
+        }
+    }
+
+    //perk values where the shots to kill change at _resilience, the perk has to be on
+    //the weapon and values go up to its max stacks
+    pub fn perk_breakpoints(
+        &self,
+        _perk_hash: u32,
+        _resilience: i32,
+    ) -> Result<Vec<Breakpoint>, OracleError> {
+        let perk = self
+            .perks
+            .values()
+            .find(|perk| perk.raw_hash == _perk_hash || perk.hash == _perk_hash)
+            .ok_or_else(|| {
+                OracleError::InvalidInput(format!("Perk {} isn't on the weapon", _perk_hash))
+            })?;
+        let (min, max) = get_perk_options(vec![perk.raw_hash])
+            .remove(&perk.raw_hash)
+            .map_or((0, 1), |options| options.stacks);
+        let hash = perk.hash;
+        let mut weapon = self.clone();
+        Ok(find_breakpoints(min as i32..=max as i32, |value| {
+            weapon.change_perk_val(hash, value as u32);
+            weapon
+                .calc_ttk(0.0)
+                .into_iter()
+                .find(|summary| summary.value == _resilience)
+                .map_or(Vec::new(), |summary| {
+                    vec![
+                        (BreakpointOutput::BODY_SHOTS, summary.body_ttk.bodyshots),
+                        (
+                            BreakpointOutput::OPTIMAL_SHOTS,
+                            summary.optimal_ttk.headshots + summary.optimal_ttk.bodyshots,
+                        ),
+                    ]
+                })
+        }))
+    }
+}
@@ -0,0 +1,158 @@
+use serde::Serialize;
+
+use super::Weapon;
+use crate::enemies::{ChampionProfile, ChampionType, Enemy, EnemyType};
+use crate::perks::get_anti_champion_mods;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChampionTtkResponse {
+    //from the anti-champion pseudo mods and arbalest only, see get_anti_champion_mods
+    #[serde(rename = "antiChampion")]
+    pub anti_champion: Vec<ChampionType>,
+    #[serde(rename = "canStun")]
+    pub can_stun: bool,
+    pub killed: bool,
+    #[serde(rename = "timeToKill")]
+    pub time_to_kill: f64,
+    #[serde(rename = "healthRemaining")]
+    pub health_remaining: f64,
+    #[serde(rename = "hitsTaken")]
+    pub hits_taken: i32,
+    pub stuns: i32,
+    #[serde(rename = "timeStunned")]
+    pub time_stunned: f64,
+    #[serde(rename = "timeImmune")]
+    pub time_immune: f64,
+    #[serde(rename = "healthRegenerated")]
+    pub health_regenerated: f64,
+}
+
+//replays the dps timeline against a champion, so everything the dps calc knows about
+//(perks, reloads, extra damage) is already baked into the hits
+pub fn calc_champion_ttk(
+    _weapon: &Weapon,
+    _enemy: &Enemy,
+    _champion: &ChampionProfile,
+    _pl_dmg_mult: f64,
+    _rpl_mult: f64,
+) -> ChampionTtkResponse {
+    let mut enemy = _enemy.clone();
+    enemy.type_ = EnemyType::CHAMPION;
+    //the champion is the only target, it doesn't respawn
+    enemy.health = 0.0;
+    let timeline = _weapon.calc_dps(enemy, _pl_dmg_mult).time_damage_data;
+
+    let anti_champion = get_anti_champion_mods(_weapon.list_perks());
+    let can_stun = anti_champion.contains(&_champion.champion_type);
+    let max_health = _champion.health;
+
+    let mut thresholds = _champion.immune_thresholds.clone();
+    thresholds.sort_by(|a, b| b.total_cmp(a));
+    let mut next_threshold = 0;
+
+    let mut health = max_health;
+    let mut last_damage_time = 0.0_f64;
+    let mut last_hit_time = 0.0_f64;
+    let mut stunned_until = f64::NEG_INFINITY;
+    let mut immune_until = f64::NEG_INFINITY;
+    let mut immune_periods: Vec<(f64, f64)> = Vec::new();
+
+    let mut hits_taken = 0;
+    let mut stuns = 0;
+    let mut time_stunned = 0.0;
+    let mut health_regenerated = 0.0;
+    let mut time_to_kill = f64::INFINITY;
+
+    for (time, damage) in timeline {
+        //REGEN////////////////////////
+        let regen_start = last_hit_time
+            .max(last_damage_time + _champion.regen_delay)
+            .max(stunned_until);
+        if time > regen_start {
+            let heal = (_champion.regen_per_second * max_health * (time - regen_start))
+                .min(max_health - health);
+            health += heal;
+            health_regenerated += heal;
+        }
+        last_hit_time = time;
+        ///////////////////////////////
+
+        //STUNS////////////////////////
+        let mut immune = time < immune_until;
+        let stunned = time < stunned_until;
+        if can_stun && !stunned {
+            //barriers only stun when the immune phase is broken
+            let stun_ready = time >= stunned_until + _champion.stun_cooldown;
+            if immune || (_champion.champion_type != ChampionType::BARRIER && stun_ready) {
+                if let Some(period) = immune_periods.last_mut().filter(|_| immune) {
+                    period.1 = time;
+                    immune_until = time;
+                    immune = false;
+                }
+                stunned_until = time + _champion.stun_duration;
+                time_stunned += _champion.stun_duration;
+                stuns += 1;
+            }
+        }
+        if immune {
+            continue;
+        }
+        ///////////////////////////////
+
+        //DAMAGE///////////////////////
+        let mut damage = damage * _rpl_mult;
+        if time < stunned_until {
+            damage *= _champion.stunned_damage_mult;
+        }
+        health -= damage;
+        hits_taken += 1;
+        last_damage_time = time;
+        if next_threshold < thresholds.len() && health / max_health <= thresholds[next_threshold] {
+            health = health.max(thresholds[next_threshold] * max_health);
+            next_threshold += 1;
+            if health > 0.0 {
+                immune_until = time + _champion.immune_duration;
+                immune_periods.push((time, immune_until));
+            }
+        }
+        if health <= 0.0 {
+            health = 0.0;
+            time_to_kill = time;
+            break;
+        }
+        ///////////////////////////////
+    }
+    //don't count stun or immune time that runs past the last hit
+    if stunned_until > last_hit_time {
+        time_stunned -= stunned_until - last_hit_time;
+    }
+    let time_immune = immune_periods
+        .iter()
+        .map(|(start, end)| end.min(last_hit_time) - start)
+        .sum();
+
+    ChampionTtkResponse {
+        anti_champion,
+        can_stun,
+        killed: time_to_kill.is_finite(),
+        time_to_kill,
+        health_remaining: health / max_health,
+        hits_taken,
+        stuns,
+        time_stunned,
+        time_immune,
+        health_regenerated,
+    }
+}
+
+impl Weapon {
+    pub fn calc_champion_ttk(
+        &self,
+        _enemy: &Enemy,
+        _champion: &ChampionProfile,
+        _pl_dmg_mult: f64,
+        _rpl_mult: f64,
+    ) -> ChampionTtkResponse {
+        calc_champion_ttk(self, _enemy, _champion, _pl_dmg_mult, _rpl_mult)
+    }
+}
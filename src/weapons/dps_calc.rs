@@ -4,8 +4,9 @@ use std::{cell::RefCell, rc::Rc};
 use super::Weapon;
 use crate::d2_enums::{AmmoType, WeaponType};
 use crate::enemies::Enemy;
+use crate::perks::buff_timeline::{BuffTimeline, BuffTrigger};
 use crate::perks::lib::{
-    CalculationInput, ExtraDamageResponse, RefundResponse, ReloadOverrideResponse,
+    CalculationInput, ExtraDamageResponse, KillTracker, RefundResponse, ReloadOverrideResponse,
 };
 use crate::perks::*;
 use crate::types::rs_types::DpsResponse;
@@ -148,6 +149,9 @@ pub fn complex_dps_calc(_weapon: Weapon, _enemy: Enemy, _pl_dmg_mult: f64) -> Dp
 
     let mut pers_calc_data: HashMap<String, f64> = HashMap::new();
 
+    let mut kills = KillTracker::new(_enemy.health);
+    let mut buff_timeline = BuffTimeline::new(&perks);
+
     let mut reserve = weapon
         .calc_ammo_sizes(
             Some(weapon.static_calc_input()),
@@ -199,6 +203,11 @@ pub fn complex_dps_calc(_weapon: Weapon, _enemy: Enemy, _pl_dmg_mult: f64) -> Dp
                 handling_data,
                 num_reloads: num_reloads as f64,
                 has_overshield: false,
+                kill_events: _enemy.health > 0.0,
+                kills_total: kills.kills_total(),
+                kills_this_mag: kills.kills_this_mag as f64,
+                time_since_last_kill: kills.time_since_last_kill(total_time),
+                buffs: Some(&buff_timeline),
             };
             let dmg_mods = get_dmg_modifier(
                 perks.clone(),
@@ -217,14 +226,24 @@ pub fn complex_dps_calc(_weapon: Weapon, _enemy: Enemy, _pl_dmg_mult: f64) -> Dp
             );
             ///////////////////////////////
 
+            //ENCOUNTER///////////////////
+            let shot_crit_mult = _enemy
+                .encounter
+                .crit_scale(crit_mult * dmg_mods.crit_scale, total_time);
+            let encounter_dmg_scale = _enemy.encounter.damage_scale(total_time);
+            ///////////////////////////////
+
             let dmg = {
-                ((impact_dmg * dmg_mods.impact_dmg_scale) * (crit_mult * dmg_mods.crit_scale)
+                ((impact_dmg * dmg_mods.impact_dmg_scale) * shot_crit_mult
                     + (explosion_dmg * dmg_mods.explosive_dmg_scale))
                     * _pl_dmg_mult
                     * weapon.damage_mods.get_mod(&_enemy.type_)
                     * weapon.damage_mods.pve
+                    * encounter_dmg_scale
             };
 
+            let data_before_shot = time_damage_data.len();
+
             let shot_burst_delay =
                 (burst_delay + firing_mods.burst_delay_add) * firing_mods.burst_delay_scale;
             let shot_inner_burst_delay = inner_burst_delay * firing_mods.inner_burst_scale;
@@ -301,22 +320,34 @@ pub fn complex_dps_calc(_weapon: Weapon, _enemy: Enemy, _pl_dmg_mult: f64) -> Dp
                 handling_data,
                 num_reloads: num_reloads as f64,
                 has_overshield: false,
+                kill_events: _enemy.health > 0.0,
+                kills_total: kills.kills_total(),
+                kills_this_mag: kills.kills_this_mag as f64,
+                time_since_last_kill: kills.time_since_last_kill(total_time),
+                buffs: Some(&buff_timeline),
             };
             ///////////////////////////////
 
             //EXTRA DMG////////////////////
+            //on kill damage hits whatever was around the target, not the next one
+            //so it's left to the add clear calc
             let extra_dmg_responses = get_extra_damage(
                 perks.clone(),
                 &after_shot_input_data,
                 false,
                 &mut pers_calc_data,
-            );
+            )
+            .into_iter()
+            .filter(|entry| !entry.on_kill)
+            .collect();
             let buffs = ExtraDamageBuffInfo {
-                pl_buff: _pl_dmg_mult,
+                pl_buff: _pl_dmg_mult * _enemy.encounter.damage_scale(total_time),
                 impact_buff: dmg_mods.impact_dmg_scale,
                 explosive_buff: dmg_mods.explosive_dmg_scale,
                 pve_buff: weapon.damage_mods.pve,
-                crit_buff: crit_mult * dmg_mods.crit_scale,
+                crit_buff: _enemy
+                    .encounter
+                    .crit_scale(crit_mult * dmg_mods.crit_scale, total_time),
                 combatant_buff: weapon.damage_mods.get_mod(&_enemy.type_),
             };
             let tmp_out_data = calc_extra_dmg(total_time, extra_dmg_responses, buffs);
@@ -324,6 +355,17 @@ pub fn complex_dps_calc(_weapon: Weapon, _enemy: Enemy, _pl_dmg_mult: f64) -> Dp
             total_time += tmp_out_data.extra_time;
             total_shots_hit += tmp_out_data.extra_hits;
             time_damage_data.extend(tmp_out_data.extra_time_dmg);
+            buff_timeline.shot(total_time);
+            ///////////////////////////////
+
+            //KILLS////////////////////////
+            for (time, dmg) in time_damage_data[data_before_shot..].iter() {
+                kills.queue_damage(*time, *dmg);
+            }
+            let new_kills = kills.land_damage(total_time);
+            for kill in kills.kill_times[kills.kill_times.len() - new_kills..].iter() {
+                buff_timeline.trigger(BuffTrigger::Kill, *kill, 1);
+            }
             ///////////////////////////////
 
             //RELOAD OVERRIDE//////////////
@@ -409,20 +451,31 @@ pub fn complex_dps_calc(_weapon: Weapon, _enemy: Enemy, _pl_dmg_mult: f64) -> Dp
             handling_data,
             num_reloads: num_reloads as f64,
             has_overshield: false,
+            kill_events: _enemy.health > 0.0,
+            kills_total: kills.kills_total(),
+            kills_this_mag: kills.kills_this_mag as f64,
+            time_since_last_kill: kills.time_since_last_kill(total_time),
+            buffs: Some(&buff_timeline),
         };
         let reload_responses =
             weapon.calc_reload_time(Some(reload_input_data), Some(&mut pers_calc_data), false);
+        buff_timeline.reload(total_time);
         total_time += reload_responses.reload_time;
+        buff_timeline.trigger(BuffTrigger::KillReload, total_time, kills.kills_this_mag);
         ///////////////////////////////
         num_reloads += 1;
+        kills.on_reload();
     }
     //sort time_damage_data by time
-    time_damage_data.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    time_damage_data.sort_by(|a, b| a.0.total_cmp(&b.0));
+    buff_timeline.finish(total_time);
     DpsResponse {
         dps_per_mag,
         time_damage_data,
         total_damage,
         total_time,
         total_shots: total_shots_fired,
+        kill_times: kills.kill_times,
+        buff_windows: buff_timeline.windows().to_vec(),
     }
 }
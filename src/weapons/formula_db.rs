@@ -0,0 +1,464 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use oracle_data::formulas as data;
+use oracle_data::reserves as reserve_data;
+use oracle_data::validate::{
+    has_errors, parse_weapon_formulas, validate_weapon_formulas, IssueLevel,
+};
+use serde::Serialize;
+
+use crate::{
+    database,
+    error::OracleError,
+    types::rs_types::{
+        AmmoFormula, DamageMods, DataPointers, FiringData, HandlingFormula, RangeFormula,
+        ReloadFormula, ReserveFormula, ReserveInput, ReserveNode, StatQuadraticFormula,
+        WeaponFlags, WeaponOverride, WeaponPath,
+    },
+};
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum FormulaSource {
+    BUILT_IN,
+    //loaded entries on top of the built in ones
+    OVERLAY,
+    //only the loaded entries
+    REPLACE,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FormulaDbInfo {
+    pub source: FormulaSource,
+    pub version: String,
+    //newest formula timestamp for the built in tables, the loaded timestamp otherwise
+    pub timestamp: u64,
+}
+
+//the tables Weapon::generate_weapon reads from, built in ones are copied out of the
+//generated database module
+#[derive(Debug, Clone)]
+pub struct FormulaDatabase {
+    pub info: FormulaDbInfo,
+    pointers: HashMap<WeaponPath, DataPointers>,
+    overrides: HashMap<WeaponPath, WeaponOverride>,
+    //keyed by family id and reserve id
+    reserves: HashMap<WeaponPath, ReserveFormula>,
+    pub range: Vec<RangeFormula>,
+    pub handling: Vec<HandlingFormula>,
+    pub reload: Vec<ReloadFormula>,
+    pub scalar: Vec<DamageMods>,
+    pub firing: Vec<FiringData>,
+    pub ammo: Vec<AmmoFormula>,
+}
+
+impl FormulaDatabase {
+    pub fn built_in() -> Self {
+        let mut db = FormulaDatabase {
+            info: FormulaDbInfo {
+                source: FormulaSource::BUILT_IN,
+                version: "built-in".to_string(),
+                timestamp: 0,
+            },
+            pointers: HashMap::from(database::DATA_POINTERS),
+            overrides: HashMap::from(database::WEAPON_OVERRIDES),
+            reserves: database::RESERVE_DATA
+                .iter()
+                .map(|(path, nodes)| {
+                    (
+                        path.clone(),
+                        ReserveFormula {
+                            nodes: nodes.to_vec(),
+                        },
+                    )
+                })
+                .collect(),
+            range: database::RANGE_DATA.to_vec(),
+            handling: database::HANDLING_DATA.to_vec(),
+            reload: database::RELOAD_DATA.to_vec(),
+            scalar: database::SCALAR_DATA.to_vec(),
+            firing: database::FIRING_DATA.to_vec(),
+            ammo: database::AMMO_DATA.to_vec(),
+        };
+        db.info.timestamp = db.newest_timestamp();
+        db
+    }
+
+    fn newest_timestamp(&self) -> u64 {
+        self.range
+            .iter()
+            .map(|f| f.timestamp)
+            .chain(self.handling.iter().map(|f| f.timestamp))
+            .chain(self.reload.iter().map(|f| f.timestamp))
+            .chain(self.scalar.iter().map(|f| f.timestamp))
+            .chain(self.firing.iter().map(|f| f.timestamp))
+            .chain(self.ammo.iter().map(|f| f.timestamp))
+            .max()
+            .unwrap_or(0)
+    }
+
+    //_json is in the weapon_formulas.json format and gets the same validation as the
+    //build, every formula in it is stamped with _timestamp. returns the warnings
+    pub fn from_json(
+        _json: &str,
+        _version: &str,
+        _timestamp: u64,
+        _replace: bool,
+    ) -> Result<(Self, Vec<String>), OracleError> {
+        let (formulas, mut issues) = parse_weapon_formulas(_json);
+        issues.extend(validate_weapon_formulas(&formulas));
+        if has_errors(&issues) {
+            let errors: Vec<String> = issues
+                .iter()
+                .filter(|issue| issue.level == IssueLevel::ERROR)
+                .map(|issue| issue.to_string())
+                .collect();
+            return Err(OracleError::Data(format!(
+                "Invalid formula database:\n{}",
+                errors.join("\n")
+            )));
+        }
+        let warnings = issues.iter().map(|issue| issue.to_string()).collect();
+        let tables = data::build_formula_tables(&formulas, |_| _timestamp);
+
+        let info = FormulaDbInfo {
+            source: if _replace {
+                FormulaSource::REPLACE
+            } else {
+                FormulaSource::OVERLAY
+            },
+            version: _version.to_string(),
+            timestamp: _timestamp,
+        };
+        let mut db = if _replace {
+            FormulaDatabase {
+                info,
+                pointers: HashMap::new(),
+                overrides: HashMap::new(),
+                reserves: HashMap::new(),
+                range: Vec::new(),
+                handling: Vec::new(),
+                reload: Vec::new(),
+                scalar: Vec::new(),
+                firing: Vec::new(),
+                ammo: Vec::new(),
+            }
+        } else {
+            FormulaDatabase {
+                info,
+                ..FormulaDatabase::built_in()
+            }
+        };
+        //loaded entries go after the existing ones and win on the same weapon path
+        let offset = DataPointers {
+            h: db.handling.len(),
+            r: db.range.len(),
+            rl: db.reload.len(),
+            s: db.scalar.len(),
+            f: db.firing.len(),
+            a: db.ammo.len(),
+        };
+        db.range
+            .extend(tables.range.into_iter().map(RangeFormula::from));
+        db.handling
+            .extend(tables.handling.into_iter().map(HandlingFormula::from));
+        db.reload
+            .extend(tables.reload.into_iter().map(ReloadFormula::from));
+        db.scalar
+            .extend(tables.scalar.into_iter().map(DamageMods::from));
+        db.firing
+            .extend(tables.firing.into_iter().map(FiringData::from));
+        db.ammo
+            .extend(tables.ammo.into_iter().map(AmmoFormula::from));
+        for (path, pointers) in tables.data_pointers {
+            db.pointers.insert(
+                WeaponPath(path.0, path.1),
+                DataPointers {
+                    h: pointers.h + offset.h,
+                    r: pointers.r + offset.r,
+                    rl: pointers.rl + offset.rl,
+                    s: pointers.s + offset.s,
+                    f: pointers.f + offset.f,
+                    a: pointers.a + offset.a,
+                },
+            );
+        }
+        for (path, weapon_override) in tables.overrides {
+            db.overrides.insert(
+                WeaponPath(path.0, path.1),
+                WeaponOverride {
+                    firing: weapon_override.firing.map(|f| f + offset.f),
+                    range: weapon_override.range.map(|r| r + offset.r),
+                    ads_mult: weapon_override.ads_mult,
+                    reserve_id: weapon_override.reserve_id,
+                    flags: WeaponFlags {
+                        flat_charge_delay: weapon_override.flags.flat_charge_delay,
+                    },
+                },
+            );
+        }
+        for (path, nodes) in tables.reserves {
+            db.reserves
+                .insert(WeaponPath(path.0, path.1), ReserveFormula { nodes });
+        }
+        Ok((db, warnings))
+    }
+
+    //the weapon's own entry first, then its intrinsic
+    pub fn get_data_pointers(
+        &self,
+        _weapon_type_id: u8,
+        _intrinsic_hash: u32,
+        _weapon_hash: u32,
+    ) -> Option<DataPointers> {
+        self.pointers
+            .get(&WeaponPath(_weapon_type_id as u32, _weapon_hash))
+            .or_else(|| {
+                self.pointers
+                    .get(&WeaponPath(_weapon_type_id as u32, _intrinsic_hash))
+            })
+            .cloned()
+    }
+
+    //same lookup order as the data pointers, overrides aren't merged
+    pub fn get_override(
+        &self,
+        _weapon_type_id: u8,
+        _intrinsic_hash: u32,
+        _weapon_hash: u32,
+    ) -> Option<WeaponOverride> {
+        self.overrides
+            .get(&WeaponPath(_weapon_type_id as u32, _weapon_hash))
+            .or_else(|| {
+                self.overrides
+                    .get(&WeaponPath(_weapon_type_id as u32, _intrinsic_hash))
+            })
+            .cloned()
+    }
+
+    //reserve ids are per family, ones that don't resolve get primary ammo
+    pub fn get_reserves(&self, _weapon_type_id: u8, _reserve_id: u32) -> ReserveFormula {
+        self.reserves
+            .get(&WeaponPath(_weapon_type_id as u32, _reserve_id))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+//snapshot names that always resolve, to the compiled in tables and to whatever
+//with_formula_db currently reads from
+pub const BUILT_IN_SNAPSHOT: &str = "built-in";
+pub const ACTIVE_SNAPSHOT: &str = "active";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FormulaSnapshotInfo {
+    pub name: String,
+    #[serde(flatten)]
+    pub info: FormulaDbInfo,
+    //compiled into the engine from build_resources/formula_snapshots
+    #[serde(rename = "compiledIn")]
+    pub compiled_in: bool,
+    //why a compiled in snapshot failed to load, it can't be used or diffed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+type Snapshot = (Result<FormulaDatabase, OracleError>, bool);
+
+//compiled in snapshots were validated by build.rs, one that still fails to parse is
+//kept with its error so listing and using it reports why instead of it going missing
+fn compiled_snapshots() -> HashMap<String, Snapshot> {
+    database::FORMULA_SNAPSHOTS
+        .iter()
+        .map(|(name, timestamp, json)| {
+            let db = FormulaDatabase::from_json(json, name, *timestamp, true).map(|(db, _)| db);
+            (name.to_string(), (db, true))
+        })
+        .collect()
+}
+
+thread_local! {
+    static FORMULA_DB: RefCell<FormulaDatabase> = RefCell::new(FormulaDatabase::built_in());
+    //with whether the snapshot is compiled in
+    static FORMULA_SNAPSHOTS: RefCell<HashMap<String, Snapshot>> =
+        RefCell::new(compiled_snapshots());
+}
+
+pub fn with_formula_db<T>(_func: impl FnOnce(&FormulaDatabase) -> T) -> T {
+    FORMULA_DB.with(|db| _func(&db.borrow()))
+}
+
+pub fn set_formula_db(_db: FormulaDatabase) {
+    FORMULA_DB.with(|db| *db.borrow_mut() = _db);
+}
+
+pub fn reset_formula_db() {
+    set_formula_db(FormulaDatabase::built_in());
+}
+
+pub fn with_formula_snapshot<T>(
+    _name: &str,
+    _func: impl FnOnce(&FormulaDatabase) -> T,
+) -> Result<T, OracleError> {
+    match _name {
+        BUILT_IN_SNAPSHOT => Ok(_func(&FormulaDatabase::built_in())),
+        ACTIVE_SNAPSHOT => Ok(with_formula_db(_func)),
+        _ => FORMULA_SNAPSHOTS.with(|snapshots| match snapshots.borrow().get(_name) {
+            Some((Ok(db), _)) => Ok(_func(db)),
+            Some((Err(err), _)) => Err(err.clone()),
+            None => Err(OracleError::InvalidInput(format!(
+                "No formula snapshot named \"{}\"",
+                _name
+            ))),
+        }),
+    }
+}
+
+//a snapshot with the same name is replaced, compiled in ones included
+pub fn add_formula_snapshot(_name: &str, _db: FormulaDatabase) -> Result<(), OracleError> {
+    if matches!(_name, BUILT_IN_SNAPSHOT | ACTIVE_SNAPSHOT) {
+        return Err(OracleError::InvalidInput(format!(
+            "\"{}\" is a reserved snapshot name",
+            _name
+        )));
+    }
+    FORMULA_SNAPSHOTS.with(|snapshots| {
+        snapshots
+            .borrow_mut()
+            .insert(_name.to_string(), (Ok(_db), false))
+    });
+    Ok(())
+}
+
+pub fn remove_formula_snapshot(_name: &str) -> bool {
+    FORMULA_SNAPSHOTS.with(|snapshots| snapshots.borrow_mut().remove(_name).is_some())
+}
+
+//makes a snapshot the active database, weapons set afterwards use it
+pub fn use_formula_snapshot(_name: &str) -> Result<(), OracleError> {
+    let db = with_formula_snapshot(_name, |db| db.clone())?;
+    set_formula_db(db);
+    Ok(())
+}
+
+//built-in first, the rest sorted by timestamp
+pub fn list_formula_snapshots() -> Vec<FormulaSnapshotInfo> {
+    let mut snapshots: Vec<FormulaSnapshotInfo> = FORMULA_SNAPSHOTS.with(|snapshots| {
+        snapshots
+            .borrow()
+            .iter()
+            .map(|(name, (db, compiled_in))| match db {
+                Ok(db) => FormulaSnapshotInfo {
+                    name: name.clone(),
+                    info: db.info.clone(),
+                    compiled_in: *compiled_in,
+                    error: None,
+                },
+                Err(err) => FormulaSnapshotInfo {
+                    name: name.clone(),
+                    info: FormulaDbInfo {
+                        source: FormulaSource::REPLACE,
+                        version: name.clone(),
+                        timestamp: 0,
+                    },
+                    compiled_in: *compiled_in,
+                    error: Some(err.to_string()),
+                },
+            })
+            .collect()
+    });
+    snapshots.sort_by(|a, b| {
+        a.info
+            .timestamp
+            .cmp(&b.info.timestamp)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    snapshots.insert(
+        0,
+        FormulaSnapshotInfo {
+            name: BUILT_IN_SNAPSHOT.to_string(),
+            info: FormulaDatabase::built_in().info,
+            compiled_in: true,
+            error: None,
+        },
+    );
+    snapshots
+}
+
+impl From<data::RangeFormula> for RangeFormula {
+    fn from(_formula: data::RangeFormula) -> Self {
+        RangeFormula {
+            start: _formula.start,
+            end: _formula.end,
+            floor_percent: _formula.floor_percent,
+            pve_floor_percent: _formula.pve_floor_percent,
+            fusion: _formula.fusion,
+            timestamp: _formula.timestamp,
+        }
+    }
+}
+
+impl From<data::HandlingFormula> for HandlingFormula {
+    fn from(_formula: data::HandlingFormula) -> Self {
+        HandlingFormula {
+            ready: _formula.ready,
+            stow: _formula.stow,
+            ads: _formula.ads,
+            timestamp: _formula.timestamp,
+        }
+    }
+}
+
+impl From<data::ReloadFormula> for ReloadFormula {
+    fn from(_formula: data::ReloadFormula) -> Self {
+        ReloadFormula {
+            reload_data: _formula.reload_data,
+            ammo_percent: _formula.ammo_percent,
+            timestamp: _formula.timestamp,
+        }
+    }
+}
+
+impl From<data::DamageMods> for DamageMods {
+    fn from(_formula: data::DamageMods) -> Self {
+        DamageMods {
+            pve: _formula.pve,
+            minor: _formula.minor,
+            elite: _formula.elite,
+            miniboss: _formula.miniboss,
+            champion: _formula.champion,
+            boss: _formula.boss,
+            vehicle: _formula.vehicle,
+            timestamp: _formula.timestamp,
+        }
+    }
+}
+
+impl From<data::FiringData> for FiringData {
+    fn from(_formula: data::FiringData) -> Self {
+        FiringData {
+            damage: _formula.damage,
+            crit_mult: _formula.crit_mult,
+            pve_damage: _formula.pve_damage,
+            pve_crit_mult: _formula.pve_crit_mult,
+            burst_delay: _formula.burst_delay,
+            inner_burst_delay: _formula.inner_burst_delay,
+            burst_size: _formula.burst_size,
+            one_ammo: _formula.one_ammo,
+            charge: _formula.charge,
+            timestamp: _formula.timestamp,
+        }
+    }
+}
+
+impl From<data::AmmoFormula> for AmmoFormula {
+    fn from(_formula: data::AmmoFormula) -> Self {
+        AmmoFormula {
+            mag: _formula.mag,
+            round_to: _formula.round_to,
+            reserve_id: _formula.reserve_id,
+            timestamp: _formula.timestamp,
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::{
+    formula_db::{with_formula_snapshot, FormulaDatabase, FormulaDbInfo},
+    Weapon,
+};
+use crate::enemies::Enemy;
+use crate::error::OracleError;
+
+//outputs closer than this count as unchanged, float noise isn't a patch note
+const DIFF_TOLERANCE: f64 = 1e-9;
+
+//output name and value, in the order formula_outputs lists them
+type FormulaOutputs = Vec<(String, f64)>;
+
+//an output missing on one side means that snapshot has no data for the weapon
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutputChange {
+    pub output: String,
+    pub before: Option<f64>,
+    pub after: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FormulaDiff {
+    pub before: FormulaDbInfo,
+    pub after: FormulaDbInfo,
+    #[serde(rename = "beforeSupported")]
+    pub before_supported: bool,
+    #[serde(rename = "afterSupported")]
+    pub after_supported: bool,
+    pub changes: Vec<OutputChange>,
+}
+
+impl Weapon {
+    //the same weapon with its perks, stats and masterwork but formulas from _db
+    pub fn with_formulas(&self, _db: &FormulaDatabase) -> Option<Weapon> {
+        let mut weapon = Weapon::generate_weapon_from(
+            _db,
+            self.hash,
+            self.weapon_type as u8,
+            self.raw_intrinsic_hash(),
+            0,
+            0,
+        )?;
+        weapon.perks = self.perks.clone();
+        weapon.stats = self.stats.clone();
+        weapon.masterwork = self.masterwork.clone();
+        weapon.perk_value_map = self.perk_value_map.clone();
+        weapon.ammo_type = self.ammo_type;
+        weapon.damage_type = self.damage_type;
+        Some(weapon)
+    }
+
+    //everything the formulas feed into, with static traits like the roll optimizer.
+    //stats and dps are pve, ttk is pvp, dps runs against _enemy
+    pub fn formula_outputs(&self, _enemy: &Enemy, _pl_dmg_mult: f64) -> FormulaOutputs {
+        let input = Some(self.static_calc_input());
+        let range = self.calc_range_falloff(input.clone(), None, false);
+        let handling = self.calc_handling_times(input.clone(), None, false);
+        let reload = self.calc_reload_time(input.clone(), None, false);
+        let ammo = self.calc_ammo_sizes(input.clone(), None, false);
+        let firing = self.calc_firing_data(input, None, false);
+        let mut outputs: FormulaOutputs = [
+            ("hipFalloffStart", range.hip_falloff_start),
+            ("hipFalloffEnd", range.hip_falloff_end),
+            ("adsFalloffStart", range.ads_falloff_start),
+            ("adsFalloffEnd", range.ads_falloff_end),
+            ("floorPercent", range.floor_percent),
+            ("readyTime", handling.ready_time),
+            ("stowTime", handling.stow_time),
+            ("adsTime", handling.ads_time),
+            ("reloadTime", reload.reload_time),
+            ("ammoTime", reload.ammo_time),
+            ("magSize", ammo.mag_size as f64),
+            ("reserveSize", ammo.reserve_size as f64),
+            ("pvpImpactDamage", firing.pvp_impact_damage),
+            ("pvpExplosionDamage", firing.pvp_explosion_damage),
+            ("pvpCritMult", firing.pvp_crit_mult),
+            ("pveImpactDamage", firing.pve_impact_damage),
+            ("pveExplosionDamage", firing.pve_explosion_damage),
+            ("pveCritMult", firing.pve_crit_mult),
+            ("burstDelay", firing.burst_delay),
+            ("burstSize", firing.burst_size as f64),
+            ("innerBurstDelay", firing.inner_burst_delay),
+            ("rpm", firing.rpm),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+        for summary in self.calc_ttk(0.0) {
+            outputs.push((
+                format!("ttk.{}.optimal", summary.value),
+                summary.optimal_ttk.time_taken,
+            ));
+            outputs.push((
+                format!("ttk.{}.body", summary.value),
+                summary.body_ttk.time_taken,
+            ));
+        }
+        //placeholder firing data (burst size 0) can't be simulated, the dps outputs are
+        //left out like any other output the weapon has no data for
+        if self.firing_data.burst_size < 1 {
+            return outputs;
+        }
+        let dps = self.calc_dps(_enemy.clone(), _pl_dmg_mult);
+        outputs.push(("dpsTotalDamage".to_string(), dps.total_damage));
+        outputs.push((
+            "dps".to_string(),
+            if dps.total_time > 0.0 {
+                dps.total_damage / dps.total_time
+            } else {
+                0.0
+            },
+        ));
+        outputs
+    }
+}
+
+fn snapshot_outputs(
+    _weapon: &Weapon,
+    _snapshot: &str,
+    _enemy: &Enemy,
+    _pl_dmg_mult: f64,
+) -> Result<(FormulaDbInfo, Option<FormulaOutputs>), OracleError> {
+    with_formula_snapshot(_snapshot, |db| {
+        let outputs = _weapon
+            .with_formulas(db)
+            .map(|weapon| weapon.formula_outputs(_enemy, _pl_dmg_mult));
+        (db.info.clone(), outputs)
+    })
+}
+
+//evaluates _weapon under both snapshots and lists the outputs that differ, in
+//formula_outputs order
+pub fn diff_formula_snapshots(
+    _weapon: &Weapon,
+    _before: &str,
+    _after: &str,
+    _enemy: &Enemy,
+    _pl_dmg_mult: f64,
+) -> Result<FormulaDiff, OracleError> {
+    let (before_info, before) = snapshot_outputs(_weapon, _before, _enemy, _pl_dmg_mult)?;
+    let (after_info, after) = snapshot_outputs(_weapon, _after, _enemy, _pl_dmg_mult)?;
+    let before_values: HashMap<&str, f64> = before
+        .iter()
+        .flatten()
+        .map(|(name, value)| (name.as_str(), *value))
+        .collect();
+    let after_values: HashMap<&str, f64> = after
+        .iter()
+        .flatten()
+        .map(|(name, value)| (name.as_str(), *value))
+        .collect();
+    //both sides have the same output names unless one of them is missing
+    let names = before
+        .as_ref()
+        .or(after.as_ref())
+        .cloned()
+        .unwrap_or_default();
+    let changes = names
+        .into_iter()
+        .filter_map(|(name, _)| {
+            let before = before_values.get(name.as_str()).cloned();
+            let after = after_values.get(name.as_str()).cloned();
+            let unchanged = match (before, after) {
+                //placeholder data gives NaN rpm and the like, NaN on both sides isn't a change
+                (Some(before), Some(after)) => {
+                    before == after
+                        || (before.is_nan() && after.is_nan())
+                        || (before - after).abs() < DIFF_TOLERANCE
+                }
+                _ => false,
+            };
+            (!unchanged).then_some(OutputChange {
+                output: name,
+                before,
+                after,
+            })
+        })
+        .collect();
+    Ok(FormulaDiff {
+        before: before_info,
+        after: after_info,
+        before_supported: before.is_some(),
+        after_supported: after.is_some(),
+        changes,
+    })
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::d2_enums::StatHashes;
+use crate::error::OracleError;
+
+pub const MAX_MASTERWORK_TIER: u32 = 10;
+pub const ADEPT_STAT_BONUS: i32 = 3;
+//weapon level a crafted weapon can enhance its intrinsic at
+pub const ENHANCED_INTRINSIC_LEVEL: u32 = 20;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MasterworkData {
+    //stat hash the masterwork boosts, 0 for none
+    #[serde(default)]
+    pub stat: u32,
+    #[serde(default)]
+    pub tier: u32,
+    //fully masterworked adepts get a flat bonus to every stat besides the masterwork stat
+    #[serde(default)]
+    pub adept: bool,
+    //None for weapons that aren't crafted
+    #[serde(default, rename = "craftedLevel")]
+    pub crafted_level: Option<u32>,
+    #[serde(default, rename = "enhancedIntrinsic")]
+    pub enhanced_intrinsic: bool,
+    //stat bonuses of the enhanced frame plug
+    #[serde(default, rename = "intrinsicStats")]
+    pub intrinsic_stats: HashMap<u32, i32>,
+}
+impl MasterworkData {
+    pub fn validate(&self) -> Result<(), OracleError> {
+        if self.tier > MAX_MASTERWORK_TIER {
+            return Err(OracleError::InvalidInput(format!(
+                "Masterwork tier {} is above the max of {}",
+                self.tier, MAX_MASTERWORK_TIER
+            )));
+        }
+        if self.enhanced_intrinsic && self.crafted_level.is_none() {
+            return Err(OracleError::InvalidInput(
+                "Only crafted weapons can have an enhanced intrinsic".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn has_enhanced_intrinsic(&self) -> bool {
+        self.enhanced_intrinsic
+            && self
+                .crafted_level
+                .map_or(false, |level| level >= ENHANCED_INTRINSIC_LEVEL)
+    }
+
+    //crafted weapons get their masterwork from enhancing the intrinsic
+    pub fn effective_tier(&self) -> u32 {
+        if self.has_enhanced_intrinsic() {
+            MAX_MASTERWORK_TIER
+        } else {
+            self.tier.min(MAX_MASTERWORK_TIER)
+        }
+    }
+
+    //stat hash to the total masterwork, adept and enhanced intrinsic bump, only
+    //for stats in _stats
+    pub fn stat_bumps(&self, _stats: impl Iterator<Item = u32>) -> HashMap<u32, i32> {
+        let tier = self.effective_tier();
+        let mut bumps = HashMap::new();
+        for hash in _stats {
+            let mut bump = 0;
+            let masterwork_stat = hash == self.stat && self.stat != 0;
+            if masterwork_stat {
+                bump += tier as i32;
+            }
+            //in game the masterwork stat stays at +10 on adepts, only the others get +3
+            if self.adept
+                && tier == MAX_MASTERWORK_TIER
+                && !masterwork_stat
+                && adept_bonus_stat(hash.into())
+            {
+                bump += ADEPT_STAT_BONUS;
+            }
+            if self.has_enhanced_intrinsic() {
+                bump += self.intrinsic_stats.get(&hash).cloned().unwrap_or(0);
+            }
+            if bump != 0 {
+                bumps.insert(hash, bump);
+            }
+        }
+        bumps
+    }
+}
+
+//bar stats only, rpm, impact, ammo and hidden stats don't get the adept bonus
+fn adept_bonus_stat(_stat: StatHashes) -> bool {
+    _stat.is_weapon_stat()
+        && !_stat.is_hidden_stat()
+        && !matches!(
+            _stat,
+            StatHashes::RPM
+                | StatHashes::IMPACT
+                | StatHashes::MAGAZINE
+                | StatHashes::INVENTORY_SIZE
+                | StatHashes::AMMO_CAPACITY
+        )
+}
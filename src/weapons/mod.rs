@@ -1,6 +1,16 @@
+pub mod add_clear_calc;
+pub mod breakpoints;
+pub mod champion_calc;
 pub mod dps_calc;
+pub mod formula_db;
+pub mod formula_diff;
+pub mod masterwork;
 pub mod reserve_calc;
+pub mod roll_optimizer;
+pub mod sockets;
+pub mod stat_breakdown;
 pub mod stat_calc;
+pub mod stat_curves;
 pub mod ttk_calc;
 pub mod weapon_constructor;
 
@@ -10,15 +20,19 @@ use serde::{Deserialize, Serialize};
 
 use crate::d2_enums::{AmmoType, DamageType, StatHashes, WeaponType};
 use crate::enemies::Enemy;
+use crate::error::OracleError;
 use crate::perks::{
-    get_magazine_modifier, get_reserve_modifier, get_stat_bumps, lib::CalculationInput, Perk,
+    get_magazine_modifier, get_reserve_modifier, get_stat_bumps, lib::CalculationInput,
+    perk_slots::check_perk_conflicts, Perk,
 };
 
 use crate::types::rs_types::{
     AmmoFormula, DamageMods, DpsResponse, FiringData, HandlingFormula, RangeFormula, ReloadFormula,
+    ReserveFormula, WeaponFlags,
 };
 
 use self::dps_calc::complex_dps_calc;
+use self::masterwork::MasterworkData;
 
 #[derive(Debug, Clone)]
 pub struct PsuedoWeapon {}
@@ -28,6 +42,8 @@ pub struct Stat {
     pub base_value: i32,
     pub part_value: i32,
     pub perk_value: i32,
+    //masterwork, adept and enhanced intrinsic bumps
+    pub masterwork_value: i32,
 }
 impl Stat {
     pub fn new() -> Stat {
@@ -35,13 +51,14 @@ impl Stat {
             base_value: 0,
             part_value: 0,
             perk_value: 0,
+            masterwork_value: 0,
         }
     }
     pub fn val(&self) -> i32 {
-        (self.base_value + self.part_value).clamp(0, 100)
+        (self.base_value + self.part_value + self.masterwork_value).clamp(0, 100)
     }
     pub fn perk_val(&self) -> i32 {
-        (self.base_value + self.part_value + self.perk_value).clamp(0, 100)
+        (self.base_value + self.part_value + self.masterwork_value + self.perk_value).clamp(0, 100)
     }
 }
 
@@ -57,6 +74,7 @@ impl From<i32> for Stat {
             base_value: _val,
             part_value: 0,
             perk_value: 0,
+            masterwork_value: 0,
         }
     }
 }
@@ -68,6 +86,7 @@ pub struct Weapon {
 
     pub perks: HashMap<u32, Perk>,
     pub stats: HashMap<u32, Stat>,
+    pub masterwork: MasterworkData,
     #[serde(skip)]
     pub perk_value_map: HashMap<u32, u32>,
 
@@ -77,6 +96,10 @@ pub struct Weapon {
     pub ammo_formula: AmmoFormula,
     pub handling_formula: HandlingFormula,
     pub reload_formula: ReloadFormula,
+    pub reserve_formula: ReserveFormula,
+    //from a formula database override, the weapon type default otherwise
+    pub ads_multiplier: Option<f64>,
+    pub flags: WeaponFlags,
 
     pub weapon_type: WeaponType,
     pub damage_type: DamageType,
@@ -87,10 +110,38 @@ impl Weapon {
         self.perks.insert(_perk.hash, _perk);
         self.update_stats();
     }
+    //same as add_perk but refuses perks that can't go on this weapon, with _replace
+    //a perk already in a single perk slot like the barrel gets swapped out
+    pub fn try_add_perk(&mut self, _perk: Perk, _replace: bool) -> Result<(), OracleError> {
+        if let Err(conflict) = check_perk_conflicts(&_perk, &self.list_perks(), &self.weapon_type) {
+            match conflict.replaceable() {
+                Some(hashes) if _replace => {
+                    for hash in hashes {
+                        self.perks.remove(hash);
+                    }
+                }
+                _ => return Err(OracleError::InvalidInput(conflict.to_string())),
+            }
+        }
+        self.add_perk(_perk);
+        Ok(())
+    }
+    //the intrinsic hash the weapon was generated with, enhanced frames included
+    pub fn raw_intrinsic_hash(&self) -> u32 {
+        self.perks
+            .get(&self.intrinsic_hash)
+            .map_or(self.intrinsic_hash, |perk| perk.raw_hash)
+    }
     pub fn remove_perk(&mut self, _perk_hash: u32) {
         self.perks.remove(&_perk_hash);
         self.update_stats();
     }
+    pub fn set_masterwork(&mut self, _masterwork: MasterworkData) -> Result<(), OracleError> {
+        _masterwork.validate()?;
+        self.masterwork = _masterwork;
+        self.update_stats();
+        Ok(())
+    }
     pub fn reset_perks(&mut self) {
         self.perks = HashMap::new();
         self.update_stats();
@@ -130,6 +181,7 @@ impl Weapon {
     pub fn reset(&mut self) {
         self.perks = HashMap::new();
         self.stats = HashMap::new();
+        self.masterwork = MasterworkData::default();
         self.hash = 0;
         self.damage_mods = DamageMods::default();
         self.firing_data = FiringData::default();
@@ -137,13 +189,20 @@ impl Weapon {
         self.ammo_formula = AmmoFormula::default();
         self.handling_formula = HandlingFormula::default();
         self.reload_formula = ReloadFormula::default();
+        self.reserve_formula = ReserveFormula::default();
+        self.ads_multiplier = None;
+        self.flags = WeaponFlags::default();
     }
 
-    pub fn static_calc_input(&self) -> CalculationInput {
+    pub fn static_calc_input(&self) -> CalculationInput<'_> {
         CalculationInput::construct_static(self)
     }
 
-    pub fn sparse_calc_input(&self, _total_shots_fired: i32, _total_time: f64) -> CalculationInput {
+    pub fn sparse_calc_input(
+        &self,
+        _total_shots_fired: i32,
+        _total_time: f64,
+    ) -> CalculationInput<'_> {
         CalculationInput::construct_pve_sparse(
             self.intrinsic_hash,
             &self.firing_data,
@@ -165,7 +224,7 @@ impl Weapon {
         _total_shots_hit: f64,
         _total_time: f64,
         _overshield: bool,
-    ) -> CalculationInput {
+    ) -> CalculationInput<'_> {
         let base_mag = self.calc_ammo_sizes(None, None, true).mag_size as f64;
         let mut tmp = CalculationInput::construct_pvp(
             self.intrinsic_hash,
@@ -194,7 +253,9 @@ impl Weapon {
         let inter_var = get_stat_bumps(self.list_perks(), input, false, &mut HashMap::new());
         let dynamic_stats = &inter_var[0];
         let static_stats = &inter_var[1];
+        let masterwork_stats = self.masterwork.stat_bumps(self.stats.keys().cloned());
         for (key, stat) in &mut self.stats {
+            stat.masterwork_value = masterwork_stats.get(key).cloned().unwrap_or(0);
             let a = static_stats.get(key);
             let b = dynamic_stats.get(key);
             if let Some(value) = a {
@@ -205,6 +266,27 @@ impl Weapon {
             }
         }
     }
+    //charge and draw time feed the fire rate, without them firing data is off
+    pub fn check_firing_stats(&self) -> Result<(), OracleError> {
+        let required = match self.weapon_type {
+            WeaponType::FUSIONRIFLE | WeaponType::LINEARFUSIONRIFLE => {
+                Some(StatHashes::CHARGE_TIME)
+            }
+            WeaponType::BOW => Some(StatHashes::DRAW_TIME),
+            _ => None,
+        };
+        if let Some(required) = required.filter(|stat| !self.stats.contains_key(&(*stat).into())) {
+            return Err(OracleError::MissingStat(required.into()));
+        }
+        //placeholder subFam entries have a burst size of 0, the sims can't fire those
+        if self.firing_data.burst_size < 1 {
+            return Err(OracleError::Data(format!(
+                "Weapon {} has no firing data, burst size is {}",
+                self.hash, self.firing_data.burst_size
+            )));
+        }
+        Ok(())
+    }
     pub fn calc_dps(&self, _enemy: Enemy, _pl_dmg_mult: f64) -> DpsResponse {
         complex_dps_calc(self.clone(), _enemy, _pl_dmg_mult)
     }
@@ -217,6 +299,7 @@ impl Default for Weapon {
 
             perks: HashMap::new(),
             stats: HashMap::new(),
+            masterwork: MasterworkData::default(),
             perk_value_map: HashMap::new(),
 
             damage_mods: DamageMods::default(),
@@ -226,6 +309,9 @@ impl Default for Weapon {
             ammo_formula: AmmoFormula::default(),
             handling_formula: HandlingFormula::default(),
             reload_formula: ReloadFormula::default(),
+            reserve_formula: ReserveFormula::default(),
+            ads_multiplier: None,
+            flags: WeaponFlags::default(),
 
             weapon_type: WeaponType::UNKNOWN,
             damage_type: DamageType::UNKNOWN,
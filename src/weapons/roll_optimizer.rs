@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::sockets::{SocketLayout, WeaponRoll};
+use super::Weapon;
+use crate::enemies::Enemy;
+use crate::error::OracleError;
+
+//anything past this gets too slow to run in the browser, dps sims especially
+pub const MAX_OPTIMIZER_ROLLS: usize = 50_000;
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RollMetric {
+    //ads falloff start in meters
+    RANGE,
+    RELOAD_TIME,
+    READY_TIME,
+    ADS_TIME,
+    MAG_SIZE,
+    //optimal pvp ttk at the objective's resilience
+    TTK,
+    //total damage over total time of the dps sim against the current enemy
+    DPS,
+}
+impl RollMetric {
+    pub fn higher_is_better(&self) -> bool {
+        matches!(
+            self,
+            RollMetric::RANGE | RollMetric::MAG_SIZE | RollMetric::DPS
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RollConstraint {
+    pub metric: RollMetric,
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+}
+impl RollConstraint {
+    fn allows(&self, _value: f64) -> bool {
+        self.min.map_or(true, |min| _value >= min) && self.max.map_or(true, |max| _value <= max)
+    }
+}
+
+fn default_top() -> usize {
+    10
+}
+fn default_resilience() -> i32 {
+    10
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollObjective {
+    pub metric: RollMetric,
+    #[serde(default)]
+    pub constraints: Vec<RollConstraint>,
+    #[serde(default = "default_top")]
+    pub top: usize,
+    #[serde(default = "default_resilience")]
+    pub resilience: i32,
+    #[serde(default)]
+    pub pvp: bool,
+}
+impl RollObjective {
+    pub fn new(_metric: RollMetric) -> Self {
+        RollObjective {
+            metric: _metric,
+            constraints: Vec::new(),
+            top: default_top(),
+            resilience: default_resilience(),
+            pvp: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RankedRoll {
+    pub roll: WeaponRoll,
+    pub score: f64,
+    //the objective metric and every constrained metric
+    pub metrics: HashMap<RollMetric, f64>,
+}
+
+impl Weapon {
+    pub fn calc_roll_metric(
+        &self,
+        _metric: RollMetric,
+        _objective: &RollObjective,
+        _enemy: &Enemy,
+        _pl_dmg_mult: f64,
+    ) -> f64 {
+        let pvp = _objective.pvp;
+        match _metric {
+            RollMetric::RANGE => {
+                self.calc_range_falloff(Some(self.static_calc_input()), None, pvp)
+                    .ads_falloff_start
+            }
+            RollMetric::RELOAD_TIME => {
+                self.calc_reload_time(Some(self.static_calc_input()), None, pvp)
+                    .reload_time
+            }
+            RollMetric::READY_TIME => {
+                self.calc_handling_times(Some(self.static_calc_input()), None, pvp)
+                    .ready_time
+            }
+            RollMetric::ADS_TIME => {
+                self.calc_handling_times(Some(self.static_calc_input()), None, pvp)
+                    .ads_time
+            }
+            RollMetric::MAG_SIZE => {
+                self.calc_ammo_sizes(Some(self.static_calc_input()), None, pvp)
+                    .mag_size as f64
+            }
+            RollMetric::TTK => self
+                .calc_ttk(0.0)
+                .iter()
+                .find(|summary| summary.value == _objective.resilience)
+                .map_or(f64::INFINITY, |summary| summary.optimal_ttk.time_taken),
+            RollMetric::DPS => {
+                let dps = self.calc_dps(_enemy.clone(), _pl_dmg_mult);
+                if dps.total_time > 0.0 {
+                    dps.total_damage / dps.total_time
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+//scores every roll in _layout, drops the ones that break a constraint and
+//returns the best _objective.top rolls
+pub fn optimize_rolls(
+    _weapon: &Weapon,
+    _layout: &SocketLayout,
+    _objective: &RollObjective,
+    _enemy: &Enemy,
+    _pl_dmg_mult: f64,
+) -> Result<Vec<RankedRoll>, OracleError> {
+    _layout.check_roll_count()?;
+    let mut ranked = Vec::new();
+    'rolls: for roll in _layout.rolls() {
+        let weapon = _weapon.apply_roll(_layout, &roll);
+        let mut metrics = HashMap::new();
+        for constraint in _objective.constraints.iter() {
+            let value = *metrics.entry(constraint.metric).or_insert_with(|| {
+                weapon.calc_roll_metric(constraint.metric, _objective, _enemy, _pl_dmg_mult)
+            });
+            if !constraint.allows(value) {
+                continue 'rolls;
+            }
+        }
+        let score = *metrics.entry(_objective.metric).or_insert_with(|| {
+            weapon.calc_roll_metric(_objective.metric, _objective, _enemy, _pl_dmg_mult)
+        });
+        ranked.push(RankedRoll {
+            roll: WeaponRoll::from(roll.as_slice()),
+            score,
+            metrics,
+        });
+    }
+    ranked.sort_by(|a, b| {
+        let order = a
+            .score
+            .partial_cmp(&b.score)
+            .unwrap_or(std::cmp::Ordering::Equal);
+        if _objective.metric.higher_is_better() {
+            order.reverse()
+        } else {
+            order
+        }
+    });
+    ranked.truncate(_objective.top);
+    Ok(ranked)
+}
@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::roll_optimizer::MAX_OPTIMIZER_ROLLS;
+use super::Weapon;
+use crate::d2_enums::WeaponType;
+use crate::error::OracleError;
+use crate::perks::perk_slots::{get_allowed_weapon_types, get_perk_slot, PerkSlot};
+use crate::perks::{enhanced_check, Perk, Perks};
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SocketType {
+    BARREL,
+    MAGAZINE,
+    TRAIT_1,
+    TRAIT_2,
+    ORIGIN,
+    MASTERWORK,
+    MOD,
+    CATALYST,
+}
+impl SocketType {
+    //perk slots that can be plugged in, perks the engine doesn't know about go anywhere
+    fn accepts(&self, _slot: PerkSlot) -> bool {
+        match self {
+            SocketType::BARREL => _slot == PerkSlot::BARREL,
+            SocketType::MAGAZINE => _slot == PerkSlot::MAGAZINE,
+            //exotics keep their unique perks in the trait columns
+            SocketType::TRAIT_1 | SocketType::TRAIT_2 => {
+                matches!(_slot, PerkSlot::TRAIT | PerkSlot::EXOTIC)
+            }
+            SocketType::ORIGIN => _slot == PerkSlot::ORIGIN,
+            SocketType::MASTERWORK => _slot == PerkSlot::MASTERWORK,
+            SocketType::MOD => _slot == PerkSlot::MOD,
+            SocketType::CATALYST => matches!(_slot, PerkSlot::TRAIT | PerkSlot::EXOTIC),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Plug {
+    pub hash: u32,
+    //static stat bonuses, same as the stats passed to addTrait
+    #[serde(default)]
+    pub stats: HashMap<u32, i32>,
+}
+
+//allowed plugs for each socket of a weapon, empty sockets are left out of rolls
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SocketLayout {
+    #[serde(default)]
+    pub barrel: Vec<Plug>,
+    #[serde(default)]
+    pub magazine: Vec<Plug>,
+    #[serde(default, rename = "trait1")]
+    pub trait_1: Vec<Plug>,
+    #[serde(default, rename = "trait2")]
+    pub trait_2: Vec<Plug>,
+    #[serde(default)]
+    pub origin: Vec<Plug>,
+    #[serde(default)]
+    pub masterwork: Vec<Plug>,
+    #[serde(default, rename = "mod")]
+    pub mod_: Vec<Plug>,
+    #[serde(default)]
+    pub catalyst: Vec<Plug>,
+}
+impl SocketLayout {
+    pub fn sockets(&self) -> Vec<(SocketType, &[Plug])> {
+        [
+            (SocketType::BARREL, &self.barrel),
+            (SocketType::MAGAZINE, &self.magazine),
+            (SocketType::TRAIT_1, &self.trait_1),
+            (SocketType::TRAIT_2, &self.trait_2),
+            (SocketType::ORIGIN, &self.origin),
+            (SocketType::MASTERWORK, &self.masterwork),
+            (SocketType::MOD, &self.mod_),
+            (SocketType::CATALYST, &self.catalyst),
+        ]
+        .into_iter()
+        .filter(|(_, plugs)| !plugs.is_empty())
+        .map(|(socket, plugs)| (socket, plugs.as_slice()))
+        .collect()
+    }
+
+    pub fn validate(&self, _weapon_type: &WeaponType) -> Result<(), OracleError> {
+        for (socket, plugs) in self.sockets() {
+            for plug in plugs {
+                let perk = Perks::from(enhanced_check(plug.hash).0);
+                if perk == Perks::Ignore {
+                    continue;
+                }
+                let slot = get_perk_slot(perk);
+                if !socket.accepts(slot) {
+                    return Err(OracleError::InvalidInput(format!(
+                        "{:?} is a {:?} perk and can't go in the {:?} socket",
+                        perk, slot, socket
+                    )));
+                }
+                if let Some(types) = get_allowed_weapon_types(perk) {
+                    if *_weapon_type != WeaponType::UNKNOWN && !types.contains(_weapon_type) {
+                        return Err(OracleError::InvalidInput(format!(
+                            "{:?} can't be used on a {:?}",
+                            perk, _weapon_type
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    //upper bound, rolls with the same trait in both columns or the traits swapped are skipped
+    pub fn roll_count(&self) -> usize {
+        self.sockets()
+            .iter()
+            .map(|(_, plugs)| plugs.len())
+            .product()
+    }
+
+    pub fn check_roll_count(&self) -> Result<(), OracleError> {
+        let count = self.roll_count();
+        if count > MAX_OPTIMIZER_ROLLS {
+            return Err(OracleError::InvalidInput(format!(
+                "{} possible rolls, the limit is {}",
+                count, MAX_OPTIMIZER_ROLLS
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn rolls(&self) -> RollIter<'_> {
+        let sockets = self.sockets();
+        RollIter {
+            idx: vec![0; sockets.len()],
+            done: sockets.is_empty(),
+            sockets,
+        }
+    }
+
+    fn contains(&self, _hash: u32) -> bool {
+        let hash = enhanced_check(_hash).0;
+        self.sockets()
+            .iter()
+            .any(|(_, plugs)| plugs.iter().any(|plug| enhanced_check(plug.hash).0 == hash))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RollPlug {
+    pub socket: SocketType,
+    pub hash: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeaponRoll {
+    pub plugs: Vec<RollPlug>,
+}
+
+impl From<&[(SocketType, &Plug)]> for WeaponRoll {
+    fn from(_roll: &[(SocketType, &Plug)]) -> Self {
+        WeaponRoll {
+            plugs: _roll
+                .iter()
+                .map(|(socket, plug)| RollPlug {
+                    socket: *socket,
+                    hash: plug.hash,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RollStats {
+    pub roll: WeaponRoll,
+    //stat hash to the final value with perks, clamped to 0-100
+    pub stats: HashMap<u32, i32>,
+}
+
+pub struct RollIter<'a> {
+    sockets: Vec<(SocketType, &'a [Plug])>,
+    idx: Vec<usize>,
+    done: bool,
+}
+impl<'a> RollIter<'a> {
+    fn advance(&mut self) {
+        for i in (0..self.idx.len()).rev() {
+            self.idx[i] += 1;
+            if self.idx[i] < self.sockets[i].1.len() {
+                return;
+            }
+            self.idx[i] = 0;
+        }
+        self.done = true;
+    }
+    fn current(&self) -> Vec<(SocketType, &'a Plug)> {
+        self.sockets
+            .iter()
+            .zip(self.idx.iter())
+            .map(|((socket, plugs), i)| (*socket, &plugs[*i]))
+            .collect()
+    }
+    fn column(&self, _socket: SocketType) -> &'a [Plug] {
+        self.sockets
+            .iter()
+            .find(|(socket, _)| *socket == _socket)
+            .map_or(&[], |(_, plugs)| *plugs)
+    }
+    //trait 1 + trait 2 is the same roll as trait 2 + trait 1, only the order
+    //with the lower hash in the first column is kept when both orders exist
+    fn is_swapped_pair(&self, _roll: &[(SocketType, &Plug)]) -> bool {
+        let find = |_socket: SocketType| {
+            _roll
+                .iter()
+                .find(|(socket, _)| *socket == _socket)
+                .map(|(_, plug)| *plug)
+        };
+        let (Some(first), Some(second)) = (find(SocketType::TRAIT_1), find(SocketType::TRAIT_2))
+        else {
+            return false;
+        };
+        first.hash > second.hash
+            && self.column(SocketType::TRAIT_1).contains(second)
+            && self.column(SocketType::TRAIT_2).contains(first)
+    }
+}
+impl<'a> Iterator for RollIter<'a> {
+    type Item = Vec<(SocketType, &'a Plug)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let roll = self.current();
+            self.advance();
+            let trait_hashes: Vec<u32> = roll
+                .iter()
+                .filter(|(socket, _)| matches!(socket, SocketType::TRAIT_1 | SocketType::TRAIT_2))
+                .map(|(_, plug)| enhanced_check(plug.hash).0)
+                .collect();
+            if trait_hashes.len() == 2 && trait_hashes[0] == trait_hashes[1] {
+                continue;
+            }
+            if self.is_swapped_pair(&roll) {
+                continue;
+            }
+            return Some(roll);
+        }
+        None
+    }
+}
+
+impl Weapon {
+    //swaps every perk that comes from _layout for the plugs in _roll, anything else
+    //like buffs and armor mods stays, perk values carry over when the perk was already on
+    pub fn apply_roll(&self, _layout: &SocketLayout, _roll: &[(SocketType, &Plug)]) -> Weapon {
+        let mut weapon = self.clone();
+        let old_values: HashMap<u32, u32> = weapon
+            .perks
+            .values()
+            .map(|perk| (perk.hash, perk.value))
+            .collect();
+        weapon
+            .perks
+            .retain(|_, perk| !_layout.contains(perk.raw_hash));
+        for (_, plug) in _roll {
+            let data = enhanced_check(plug.hash);
+            weapon.perks.insert(
+                data.0,
+                Perk {
+                    stat_buffs: plug.stats.clone(),
+                    enhanced: data.1,
+                    value: old_values.get(&data.0).cloned().unwrap_or(0),
+                    raw_hash: plug.hash,
+                    hash: data.0,
+                },
+            );
+        }
+        weapon.update_stats();
+        weapon
+    }
+
+    //masterwork and mod bonuses are static part stats so they stack on the barrel and mag
+    //like in game, the single socket each has keeps them from doubling up
+    pub fn roll_stats(&self, _layout: &SocketLayout) -> Result<Vec<RollStats>, OracleError> {
+        _layout.check_roll_count()?;
+        Ok(_layout
+            .rolls()
+            .map(|roll| {
+                let weapon = self.apply_roll(_layout, &roll);
+                RollStats {
+                    roll: WeaponRoll::from(roll.as_slice()),
+                    stats: weapon
+                        .stats
+                        .iter()
+                        .map(|(hash, stat)| (*hash, stat.perk_val()))
+                        .collect(),
+                }
+            })
+            .collect())
+    }
+}
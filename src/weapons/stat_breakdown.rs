@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::{Stat, Weapon};
+use crate::d2_enums::StatHashes;
+use crate::perks::{get_stat_bumps, lib::CalculationInput, Perks};
+
+const HIDDEN_STATS: [StatHashes; 4] = [
+    StatHashes::AIM_ASSIST,
+    StatHashes::ZOOM,
+    StatHashes::RECOIL_DIR,
+    StatHashes::AIRBORNE,
+];
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum StatSource {
+    BASE,
+    //stat_buffs of barrels, mags, mods etc
+    PART,
+    //conditional bumps from perk functions
+    TRAIT,
+    MASTERWORK,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatContribution {
+    pub source: StatSource,
+    //raw perk hash, 0 for base, masterwork and the unattributed remainder
+    pub perk: u32,
+    pub name: String,
+    pub value: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatBreakdown {
+    pub stat: u32,
+    pub name: String,
+    pub hidden: bool,
+    //sum of every contribution before the 0-100 clamp
+    pub total: i32,
+    pub value: i32,
+    //points lost to the clamp, negative when the total is below 0
+    pub overflow: i32,
+    pub contributions: Vec<StatContribution>,
+}
+
+impl Weapon {
+    pub fn stat_breakdown(&self) -> Vec<StatBreakdown> {
+        let mut stats: HashMap<u32, Stat> = self.stats.clone();
+        for stat in HIDDEN_STATS {
+            stats.entry(stat.into()).or_default();
+        }
+
+        let mut perks = self.list_perks();
+        perks.sort_by_key(|perk| perk.raw_hash);
+        //perks are added one at a time and credited with the change in the running
+        //total, so perks that read each other's cached data still add up
+        let mut contributions: HashMap<u32, Vec<StatContribution>> = HashMap::new();
+        let mut previous: [HashMap<u32, i32>; 2] = Default::default();
+        for i in 0..perks.len() {
+            let name = format!("{:?}", Perks::from(perks[i].hash));
+            let raw_hash = perks[i].raw_hash;
+            let input = CalculationInput::construct_static(self);
+            let running = get_stat_bumps(perks[..=i].to_vec(), input, false, &mut HashMap::new());
+            for (source, bumps, before) in [
+                (StatSource::PART, &running[1], &previous[1]),
+                (StatSource::TRAIT, &running[0], &previous[0]),
+            ] {
+                for (hash, total) in bumps {
+                    let value = total - before.get(hash).cloned().unwrap_or(0);
+                    if value == 0 || !stats.contains_key(hash) {
+                        continue;
+                    }
+                    contributions
+                        .entry(*hash)
+                        .or_default()
+                        .push(StatContribution {
+                            source,
+                            perk: raw_hash,
+                            name: name.clone(),
+                            value,
+                        });
+                }
+            }
+            previous = running;
+        }
+
+        let mut breakdown: Vec<StatBreakdown> = stats
+            .iter()
+            .map(|(hash, stat)| {
+                let mut stat_contributions = vec![StatContribution {
+                    source: StatSource::BASE,
+                    perk: 0,
+                    name: "Base".to_string(),
+                    value: stat.base_value,
+                }];
+                if stat.masterwork_value != 0 {
+                    stat_contributions.push(StatContribution {
+                        source: StatSource::MASTERWORK,
+                        perk: 0,
+                        name: "Masterwork".to_string(),
+                        value: stat.masterwork_value,
+                    });
+                }
+                stat_contributions.extend(contributions.remove(hash).unwrap_or_default());
+                let total =
+                    stat.base_value + stat.part_value + stat.masterwork_value + stat.perk_value;
+                //perk order can still matter through the cached data, whatever the
+                //running totals miss is listed on its own so the contributions sum to total
+                let residual = total - stat_contributions.iter().map(|c| c.value).sum::<i32>();
+                if residual != 0 {
+                    stat_contributions.push(StatContribution {
+                        source: StatSource::TRAIT,
+                        perk: 0,
+                        name: "Unattributed".to_string(),
+                        value: residual,
+                    });
+                }
+                let value = stat.perk_val();
+                StatBreakdown {
+                    stat: *hash,
+                    name: format!("{:?}", StatHashes::from(*hash)),
+                    hidden: StatHashes::from(*hash).is_hidden_stat(),
+                    total,
+                    value,
+                    overflow: total - value,
+                    contributions: stat_contributions,
+                }
+            })
+            .collect();
+        breakdown.sort_by(|a, b| a.name.cmp(&b.name));
+        breakdown
+    }
+}
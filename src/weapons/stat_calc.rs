@@ -16,7 +16,7 @@ use crate::{
     },
     types::rs_types::{
         AmmoFormula, AmmoResponse, FiringResponse, HandlingFormula, HandlingResponse,
-        HealthResponse, RangeFormula, RangeResponse, ReloadFormula, ReloadResponse,
+        HealthResponse, RangeFormula, RangeResponse, ReloadFormula, ReloadResponse, ReserveFormula,
     },
 };
 
@@ -57,7 +57,15 @@ impl Weapon {
             ReloadModifierResponse::default()
         };
 
-        let mut out = self.reload_formula.calc_reload_time_formula(reload_stat);
+        self.calc_reload_time_at(reload_stat, modifiers)
+    }
+
+    pub fn calc_reload_time_at(
+        &self,
+        _reload_stat: i32,
+        modifiers: ReloadModifierResponse,
+    ) -> ReloadResponse {
+        let mut out = self.reload_formula.calc_reload_time_formula(_reload_stat);
 
         //this is some weird math that oddly works?
         //dont ask me why?????
@@ -114,7 +122,6 @@ impl Weapon {
             .get(&StatHashes::RANGE.into())
             .unwrap_or(&Stat::new())
             .val();
-        let ads_mult = get_ads_multiplier(self.weapon_type, self.intrinsic_hash).unwrap_or(1.0);
 
         let modifiers = if let Some(calc_input) = _calc_input {
             get_range_modifier(self.list_perks(), &calc_input, _pvp, cached_data)
@@ -122,6 +129,19 @@ impl Weapon {
             RangeModifierResponse::default()
         };
 
+        self.calc_range_falloff_at(range_stat, modifiers, _pvp)
+    }
+
+    pub fn calc_range_falloff_at(
+        &self,
+        range_stat: i32,
+        modifiers: RangeModifierResponse,
+        _pvp: bool,
+    ) -> RangeResponse {
+        let ads_mult = self.ads_multiplier.unwrap_or_else(|| {
+            get_ads_multiplier(self.weapon_type, self.intrinsic_hash).unwrap_or(1.0)
+        });
+
         let pve_floor = self
             .range_formula
             .pve_floor_percent
@@ -186,8 +206,16 @@ impl Weapon {
             HandlingModifierResponse::default()
         };
 
+        self.calc_handling_times_at(handling_stat, modifiers)
+    }
+
+    pub fn calc_handling_times_at(
+        &self,
+        _handling_stat: i32,
+        _modifiers: HandlingModifierResponse,
+    ) -> HandlingResponse {
         self.handling_formula
-            .calc_handling_times_formula(handling_stat, modifiers)
+            .calc_handling_times_formula(_handling_stat, _modifiers)
     }
 }
 
@@ -199,7 +227,7 @@ impl AmmoFormula {
         _reserve_stat: i32,
         _inv_modifiers: InventoryModifierResponse,
         _calc_inv: bool,
-        _inv_id: u32,
+        _reserves: &ReserveFormula,
     ) -> AmmoResponse {
         let mag_stat = (_mag_stat + _mag_modifiers.magazine_stat_add).clamp(0, 100) as f64;
         let inv_stat = (_reserve_stat + _inv_modifiers.inv_stat_add).clamp(0, 100) as f64;
@@ -220,10 +248,10 @@ impl AmmoFormula {
         let mut reserve_size = 1;
         if _calc_inv {
             reserve_size = calc_reserves(
+                _reserves,
                 raw_mag_size,
                 _mag_stat,
                 inv_stat as i32,
-                _inv_id,
                 _inv_modifiers.inv_scale,
             );
         }
@@ -251,7 +279,6 @@ impl Weapon {
             .get(&StatHashes::INVENTORY_SIZE.into())
             .unwrap_or(&Stat::new())
             .val();
-        let mut out;
         let mut default_chd_dt = HashMap::new();
         let cached_data = _cached_data.unwrap_or(&mut default_chd_dt);
         if _calc_input.is_some() {
@@ -267,24 +294,32 @@ impl Weapon {
                 _pvp,
                 cached_data,
             );
-            out = self.ammo_formula.calc_ammo_size_formula(
-                mag_stat,
-                mag_modifiers,
-                inv_stat,
-                inv_modifiers,
-                true,
-                self.ammo_formula.reserve_id,
-            );
+            self.calc_ammo_sizes_at(mag_stat, mag_modifiers, inv_stat, inv_modifiers)
         } else {
-            out = self.ammo_formula.calc_ammo_size_formula(
+            self.calc_ammo_sizes_at(
                 mag_stat,
                 MagazineModifierResponse::default(),
                 inv_stat,
                 InventoryModifierResponse::default(),
-                true,
-                self.ammo_formula.reserve_id,
-            );
+            )
         }
+    }
+
+    pub fn calc_ammo_sizes_at(
+        &self,
+        mag_stat: i32,
+        _mag_modifiers: MagazineModifierResponse,
+        inv_stat: i32,
+        _inv_modifiers: InventoryModifierResponse,
+    ) -> AmmoResponse {
+        let mut out = self.ammo_formula.calc_ammo_size_formula(
+            mag_stat,
+            _mag_modifiers,
+            inv_stat,
+            _inv_modifiers,
+            true,
+            &self.reserve_formula,
+        );
         if mag_stat > 90 && self.weapon_type == WeaponType::SNIPER {
             out.mag_size += 1;
         }
@@ -475,28 +510,14 @@ impl Weapon {
     }
 }
 
-//this should be in weapons_formulas
+//exotic exceptions are overrides in weapon_formulas.json
 fn get_ads_multiplier(weapon_type: WeaponType, intrinsic_hash: u32) -> Result<f64, ()> {
-    //EXCEPTIONS
-    const LAST_WORD: u32 = 2770223582;
-    const ACE_OF_SPACEDS: u32 = 647617635; //only during memento
-    const DEVILS_RUIN: u32 = 334466122; //only when laser
-    const REVISION_ZERO: u32 = 2770223582; //only in hunters trance
-    const CRIMSON: u32 = 1030990989;
-    const VEX_MYTHOCLAST: u32 = 3610750208;
-    const FORERUNNER: u32 = 2984682260;
-    const ERIANAS_VOW: u32 = 3174300811;
-
     Ok(match (weapon_type, intrinsic_hash) {
-        (WeaponType::SIDEARM, FORERUNNER) => 2.0,
         (WeaponType::SIDEARM, _) => 1.2,
 
         (WeaponType::SUBMACHINEGUN, 903) => 1.5,
         (WeaponType::SUBMACHINEGUN, _) => 1.4,
 
-        (WeaponType::HANDCANNON, LAST_WORD) => 1.1,
-        (WeaponType::HANDCANNON, CRIMSON) => 1.3,
-        (WeaponType::HANDCANNON, ERIANAS_VOW) => 2.4,
         (WeaponType::HANDCANNON, 907) => 1.6,
         (WeaponType::HANDCANNON, _) => 1.5,
 
@@ -511,12 +532,9 @@ fn get_ads_multiplier(weapon_type: WeaponType, intrinsic_hash: u32) -> Result<f6
 
         (WeaponType::SCOUTRIFLE, _) => 2.0,
 
-        (WeaponType::SHOTGUN, 916) => 1.2,        //Slugs
-        (WeaponType::SHOTGUN, 1394384862) => 1.2, //Chaperone
-        (WeaponType::SHOTGUN, 536517534) => 1.2,  //Duality
+        (WeaponType::SHOTGUN, 916) => 1.2, //Slugs
         (WeaponType::SHOTGUN, _) => 1.0,
 
-        (WeaponType::FUSIONRIFLE, VEX_MYTHOCLAST) => 1.7,
         (WeaponType::FUSIONRIFLE, _) => 1.3,
 
         (WeaponType::TRACERIFLE, _) => 1.6,
@@ -541,40 +559,18 @@ impl Weapon {
         let mut default_cached_data = HashMap::new();
         let cached_data = _cached_data.unwrap_or(&mut default_cached_data);
 
-        //Range/Velocity stat to m/s
-        let mut velocity = match self.weapon_type {
-            WeaponType::GLAIVE => {
-                f64::from(
-                    self.stats
-                        .get(&StatHashes::RANGE.into())
-                        .unwrap_or(&Stat::new())
-                        .perk_val()
-                        .clamp(0, 100),
-                ) * 0.52
-                    + 78.0
-            }
-            WeaponType::GRENADELAUNCHER => {
-                f64::from(
-                    self.stats
-                        .get(&StatHashes::VELOCITY.into())
-                        .unwrap_or(&Stat::new())
-                        .perk_val()
-                        .clamp(0, 100),
-                ) * 0.384
-                    + 29.6
-            }
-            WeaponType::ROCKET => {
-                f64::from(
-                    self.stats
-                        .get(&StatHashes::VELOCITY.into())
-                        .unwrap_or(&Stat::new())
-                        .perk_val()
-                        .clamp(0, 100),
-                ) * 0.13
-                    + 29.0
-            }
-            _ => 0.0,
+        //glaives use range as their velocity stat
+        let stat_hash = if self.weapon_type == WeaponType::GLAIVE {
+            StatHashes::RANGE
+        } else {
+            StatHashes::VELOCITY
         };
+        let mut velocity = self.calc_projectile_velocity_at(
+            self.stats
+                .get(&stat_hash.into())
+                .unwrap_or(&Stat::new())
+                .perk_val(),
+        );
 
         if let Some(calc_input) = _calc_input {
             velocity *= get_velocity_modifier(self.list_perks(), &calc_input, _pvp, cached_data)
@@ -585,6 +581,17 @@ impl Weapon {
 }
 
 impl Weapon {
+    //Range/Velocity stat to m/s, 0 for weapons without a projectile velocity
+    pub fn calc_projectile_velocity_at(&self, _stat: i32) -> MetersPerSecond {
+        let stat = f64::from(_stat.clamp(0, 100));
+        match self.weapon_type {
+            WeaponType::GLAIVE => stat * 0.52 + 78.0,
+            WeaponType::GRENADELAUNCHER => stat * 0.384 + 29.6,
+            WeaponType::ROCKET => stat * 0.13 + 29.0,
+            _ => 0.0,
+        }
+    }
+
     pub fn calc_perfect_draw(&self) -> Seconds {
         let stability: f64 = self
             .stats
@@ -593,7 +600,10 @@ impl Weapon {
             .perk_val()
             .clamp(0, 100)
             .into();
-        if self.perks.get(&1449897496).is_some() && self.weapon_type == WeaponType::BOW {
+        //oathkeeper is an armor exotic equipped on the weapon like any other perk, not
+        //something the weapon data knows about, so it stays a perk check over a WeaponFlags entry
+        if self.perks.contains_key(&Perks::Oathkeeper.into()) && self.weapon_type == WeaponType::BOW
+        {
             return Seconds::INFINITY;
         }
         match self.intrinsic_hash {
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+
+use super::Weapon;
+use crate::perks::lib::{
+    HandlingModifierResponse, InventoryModifierResponse, MagazineModifierResponse,
+    RangeModifierResponse, ReloadModifierResponse,
+};
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+//flat multipliers for curves, same meaning as the matching modifier response scales
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CurveScalars {
+    #[serde(default = "default_scale", rename = "reloadScale")]
+    pub reload: f64,
+    #[serde(default = "default_scale", rename = "drawScale")]
+    pub draw: f64,
+    #[serde(default = "default_scale", rename = "stowScale")]
+    pub stow: f64,
+    #[serde(default = "default_scale", rename = "adsScale")]
+    pub ads: f64,
+    #[serde(default = "default_scale", rename = "rangeScale")]
+    pub range: f64,
+    #[serde(default = "default_scale", rename = "reserveScale")]
+    pub reserve: f64,
+    #[serde(default = "default_scale", rename = "velocityScale")]
+    pub velocity: f64,
+    #[serde(default)]
+    pub pvp: bool,
+}
+impl Default for CurveScalars {
+    fn default() -> Self {
+        CurveScalars {
+            reload: 1.0,
+            draw: 1.0,
+            stow: 1.0,
+            ads: 1.0,
+            range: 1.0,
+            reserve: 1.0,
+            velocity: 1.0,
+            pvp: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct StatCurvePoint {
+    pub stat: i32,
+    #[serde(rename = "reloadTime")]
+    pub reload_time: f64,
+    #[serde(rename = "ammoTime")]
+    pub ammo_time: f64,
+    #[serde(rename = "readyTime")]
+    pub ready_time: f64,
+    #[serde(rename = "stowTime")]
+    pub stow_time: f64,
+    #[serde(rename = "adsTime")]
+    pub ads_time: f64,
+    #[serde(rename = "hipFalloffStart")]
+    pub hip_falloff_start: f64,
+    #[serde(rename = "hipFalloffEnd")]
+    pub hip_falloff_end: f64,
+    #[serde(rename = "adsFalloffStart")]
+    pub ads_falloff_start: f64,
+    #[serde(rename = "adsFalloffEnd")]
+    pub ads_falloff_end: f64,
+    #[serde(rename = "magSize")]
+    pub mag_size: i32,
+    //with the inventory stat at the same value as the magazine stat
+    #[serde(rename = "reserveSize")]
+    pub reserve_size: i32,
+    pub velocity: f64,
+}
+
+//a weapon with only formulas loaded, nothing on it reads perks or pers data
+fn curve_weapon(_weapon_type_id: u8, _intrinsic_hash: u32, _weapon_hash: u32) -> Option<Weapon> {
+    Weapon::generate_weapon(_weapon_hash, _weapon_type_id, _intrinsic_hash, 0, 0)
+}
+
+impl Weapon {
+    pub fn calc_stat_curve_point(&self, _stat: i32, _scalars: &CurveScalars) -> StatCurvePoint {
+        let stat = _stat.clamp(0, 100);
+        let reload = self.calc_reload_time_at(
+            stat,
+            ReloadModifierResponse {
+                reload_time_scale: _scalars.reload,
+                ..Default::default()
+            },
+        );
+        let handling = self.calc_handling_times_at(
+            stat,
+            HandlingModifierResponse {
+                draw_scale: _scalars.draw,
+                stow_scale: _scalars.stow,
+                ads_scale: _scalars.ads,
+                ..Default::default()
+            },
+        );
+        let range = self.calc_range_falloff_at(
+            stat,
+            RangeModifierResponse {
+                range_all_scale: _scalars.range,
+                ..Default::default()
+            },
+            _scalars.pvp,
+        );
+        let ammo = self.calc_ammo_sizes_at(
+            stat,
+            MagazineModifierResponse::default(),
+            stat,
+            InventoryModifierResponse {
+                inv_scale: _scalars.reserve,
+                ..Default::default()
+            },
+        );
+        StatCurvePoint {
+            stat,
+            reload_time: reload.reload_time,
+            ammo_time: reload.ammo_time,
+            ready_time: handling.ready_time,
+            stow_time: handling.stow_time,
+            ads_time: handling.ads_time,
+            hip_falloff_start: range.hip_falloff_start,
+            hip_falloff_end: range.hip_falloff_end,
+            ads_falloff_start: range.ads_falloff_start,
+            ads_falloff_end: range.ads_falloff_end,
+            mag_size: ammo.mag_size,
+            reserve_size: ammo.reserve_size,
+            velocity: self.calc_projectile_velocity_at(stat) * _scalars.velocity,
+        }
+    }
+}
+
+pub fn get_stat_curve_point(
+    _weapon_type_id: u8,
+    _intrinsic_hash: u32,
+    _weapon_hash: u32,
+    _stat: i32,
+    _scalars: &CurveScalars,
+) -> Option<StatCurvePoint> {
+    let weapon = curve_weapon(_weapon_type_id, _intrinsic_hash, _weapon_hash)?;
+    Some(weapon.calc_stat_curve_point(_stat, _scalars))
+}
+
+//one point per stat value from 0 to 100
+pub fn get_stat_curves(
+    _weapon_type_id: u8,
+    _intrinsic_hash: u32,
+    _weapon_hash: u32,
+    _scalars: &CurveScalars,
+) -> Option<Vec<StatCurvePoint>> {
+    let weapon = curve_weapon(_weapon_type_id, _intrinsic_hash, _weapon_hash)?;
+    Some(
+        (0..=100)
+            .map(|stat| weapon.calc_stat_curve_point(stat, _scalars))
+            .collect(),
+    )
+}
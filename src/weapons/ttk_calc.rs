@@ -5,7 +5,9 @@ use serde::Serialize;
 use crate::{
     d2_enums::WeaponType,
     logging::extern_log,
-    perks::{get_dmg_modifier, get_firing_modifier, lib::CalculationInput},
+    perks::{
+        buff_timeline::BuffTimeline, get_dmg_modifier, get_firing_modifier, lib::CalculationInput,
+    },
 };
 
 use super::{FiringData, Weapon};
@@ -46,6 +48,8 @@ pub struct ResillienceSummary {
 pub fn calc_ttk(_weapon: &Weapon, _overshield: f64) -> Vec<ResillienceSummary> {
     let mut ttk_data: Vec<ResillienceSummary> = Vec::new();
     let mut persistent_data: HashMap<String, f64> = HashMap::new();
+    //nothing dies before the kill so only fight start buffs show up here
+    let buffs = BuffTimeline::new(&_weapon.list_perks());
 
     let tmp_dmg_prof = _weapon.get_damage_profile(true);
     let impact_dmg = tmp_dmg_prof.impact_dmg;
@@ -74,12 +78,13 @@ pub fn calc_ttk(_weapon: &Weapon, _overshield: f64) -> Vec<ResillienceSummary> {
             persistent_data.insert("empowering".to_string(), 1.0);
             persistent_data.insert("debuff".to_string(), 1.0);
             persistent_data.insert("surge".to_string(), 1.0);
-            let calc_input = _weapon.pvp_calc_input(
+            let mut calc_input = _weapon.pvp_calc_input(
                 opt_bullets_fired,
                 opt_bullets_hit,
                 opt_time_taken,
                 (_overshield - opt_damage_dealt) > 0.0,
             );
+            calc_input.buffs = Some(&buffs);
             let dmg_mods = get_dmg_modifier(
                 _weapon.list_perks().clone(),
                 &calc_input,
@@ -116,7 +121,7 @@ pub fn calc_ttk(_weapon: &Weapon, _overshield: f64) -> Vec<ResillienceSummary> {
                 shot_burst_delay
             };
 
-            if _weapon.hash == 4289226715 { // vex mythoclast
+            if _weapon.flags.flat_charge_delay {
             } else if _weapon.weapon_type == WeaponType::LINEARFUSIONRIFLE {
                 shot_delay *= 1.95;
             } else if _weapon.weapon_type == WeaponType::FUSIONRIFLE {
@@ -197,12 +202,13 @@ pub fn calc_ttk(_weapon: &Weapon, _overshield: f64) -> Vec<ResillienceSummary> {
             persistent_data.insert("empowering".to_string(), 1.0);
             persistent_data.insert("debuff".to_string(), 1.0);
             persistent_data.insert("surge".to_string(), 1.0);
-            let calc_input = _weapon.pvp_calc_input(
+            let mut calc_input = _weapon.pvp_calc_input(
                 bdy_bullets_fired,
                 bdy_bullets_hit,
                 bdy_time_taken,
                 (_overshield - bdy_damage_dealt) > 0.0,
             );
+            calc_input.buffs = Some(&buffs);
             let dmg_mods = get_dmg_modifier(
                 _weapon.list_perks().clone(),
                 &calc_input,
@@ -240,7 +246,7 @@ pub fn calc_ttk(_weapon: &Weapon, _overshield: f64) -> Vec<ResillienceSummary> {
                 shot_burst_delay
             };
 
-            if _weapon.hash == 4289226715 { //vex mythoclast
+            if _weapon.flags.flat_charge_delay {
             } else if _weapon.weapon_type == WeaponType::LINEARFUSIONRIFLE {
                 shot_delay *= 1.95;
             } else if _weapon.weapon_type == WeaponType::FUSIONRIFLE {
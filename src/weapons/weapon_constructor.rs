@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use crate::{
     d2_enums::{AmmoType, BungieHash, DamageType, WeaponType},
     database,
+    error::OracleError,
     perks::{enhanced_check, Perk},
     types::rs_types::{
         AmmoFormula, DamageMods, DataPointers, HandlingFormula, RangeFormula, ReloadFormula,
@@ -10,21 +11,11 @@ use crate::{
     },
 };
 
-use super::{FiringData, Weapon};
-
-fn get_data_pointers(
-    _weapon_type_id: u8,
-    intrinsic_hash: BungieHash,
-    weapon_hash: BungieHash,
-) -> Option<DataPointers> {
-    let pointer_map: HashMap<WeaponPath, DataPointers> = HashMap::from(database::DATA_POINTERS);
-    if let Some(weapon) = pointer_map.get(&WeaponPath(_weapon_type_id as u32, weapon_hash)) {
-        return Some(*weapon);
-    }
-    pointer_map
-        .get(&WeaponPath(_weapon_type_id as u32, intrinsic_hash))
-        .cloned()
-}
+use super::{
+    formula_db::{with_formula_db, FormulaDatabase},
+    masterwork::MasterworkData,
+    Weapon,
+};
 
 impl Weapon {
     pub fn generate_weapon(
@@ -34,21 +25,77 @@ impl Weapon {
         _ammo_type_id: u32,
         _damage_type_id: u32,
     ) -> Option<Weapon> {
-        let data_pointer_result = get_data_pointers(_weapon_type_id, _intrinsic_hash, _hash);
-
-        let data_pointer = data_pointer_result?;
-
-        let range_formula: RangeFormula = database::RANGE_DATA[data_pointer.r];
-
-        let handling_formula: HandlingFormula = database::HANDLING_DATA[data_pointer.h];
-
-        let reload_formula: ReloadFormula = database::RELOAD_DATA[data_pointer.rl];
+        with_formula_db(|db| {
+            Weapon::generate_weapon_from(
+                db,
+                _hash,
+                _weapon_type_id,
+                _intrinsic_hash,
+                _ammo_type_id,
+                _damage_type_id,
+            )
+        })
+    }
 
-        let damage_mods: DamageMods = database::SCALAR_DATA[data_pointer.s];
+    //generate_weapon with the reason there's no weapon instead of None
+    pub fn try_generate_weapon(
+        _hash: u32,
+        _weapon_type_id: u8,
+        _intrinsic_hash: u32,
+        _ammo_type_id: u32,
+        _damage_type_id: u32,
+    ) -> Result<Weapon, OracleError> {
+        if WeaponType::from(_weapon_type_id as u32) == WeaponType::UNKNOWN {
+            return Err(OracleError::InvalidInput(format!(
+                "Unknown weapon type id {}",
+                _weapon_type_id
+            )));
+        }
+        Weapon::generate_weapon(
+            _hash,
+            _weapon_type_id,
+            _intrinsic_hash,
+            _ammo_type_id,
+            _damage_type_id,
+        )
+        .ok_or(OracleError::UnknownWeapon {
+            weapon_type_id: _weapon_type_id,
+            intrinsic_hash: _intrinsic_hash,
+            hash: _hash,
+        })
+    }
 
-        let firing_data: FiringData = database::FIRING_DATA[data_pointer.f];
+    //whether generate_weapon would find formulas in the active database
+    pub fn has_weapon_data(_weapon_type_id: u8, _intrinsic_hash: u32, _hash: u32) -> bool {
+        with_formula_db(|db| {
+            db.get_data_pointers(_weapon_type_id, _intrinsic_hash, _hash)
+                .is_some()
+        })
+    }
 
-        let ammo_formula: AmmoFormula = database::AMMO_DATA[data_pointer.a];
+    //same as generate_weapon with formulas from _db instead of the active database
+    pub fn generate_weapon_from(
+        _db: &FormulaDatabase,
+        _hash: u32,
+        _weapon_type_id: u8,
+        _intrinsic_hash: u32,
+        _ammo_type_id: u32,
+        _damage_type_id: u32,
+    ) -> Option<Weapon> {
+        let data_pointer = _db.get_data_pointers(_weapon_type_id, _intrinsic_hash, _hash)?;
+        let weapon_override = _db
+            .get_override(_weapon_type_id, _intrinsic_hash, _hash)
+            .unwrap_or_default();
+        let mut ammo_formula = _db.ammo[data_pointer.a];
+        if let Some(reserve_id) = weapon_override.reserve_id {
+            ammo_formula.reserve_id = reserve_id;
+        }
+        let range_formula = _db.range[weapon_override.range.unwrap_or(data_pointer.r)];
+        let handling_formula = _db.handling[data_pointer.h];
+        let reload_formula = _db.reload[data_pointer.rl];
+        let damage_mods = _db.scalar[data_pointer.s];
+        let firing_data = _db.firing[weapon_override.firing.unwrap_or(data_pointer.f)];
+        let reserve_formula = _db.get_reserves(_weapon_type_id, ammo_formula.reserve_id);
 
         let weapon_type = WeaponType::from(_weapon_type_id as u32);
         let ammo_type = AmmoType::from(_ammo_type_id);
@@ -80,6 +127,7 @@ impl Weapon {
                 ),
             ]),
             stats: HashMap::new(),
+            masterwork: MasterworkData::default(),
             perk_value_map: HashMap::from([(intrinsic_alias, 0), (0, 0)]),
             damage_mods,
             ammo_formula,
@@ -87,6 +135,9 @@ impl Weapon {
             handling_formula,
             reload_formula,
             range_formula,
+            reserve_formula,
+            ads_multiplier: weapon_override.ads_mult,
+            flags: weapon_override.flags,
             ammo_type,
             damage_type,
             weapon_type,